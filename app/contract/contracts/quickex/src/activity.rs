@@ -0,0 +1,100 @@
+use crate::storage::{
+    get_activity_count, get_activity_page, set_activity_count, set_activity_page,
+};
+use crate::types::{ActivityEntry, Escrow};
+use soroban_sdk::{Address, Env, Vec};
+
+/// Entries stored per activity page
+pub const ACTIVITY_PAGE_SIZE: u32 = 32;
+
+/// Most entries one `read` returns
+pub const MAX_ACTIVITY_LIMIT: u32 = 100;
+
+/// Append an escrow's current status to its sender's and recipient's feeds
+pub fn record(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let entry = ActivityEntry {
+        escrow_id,
+        status: escrow.status,
+        ledger: env.ledger().sequence(),
+    };
+
+    append(env, &escrow.from, &entry);
+    if escrow.to != escrow.from {
+        append(env, &escrow.to, &entry);
+    }
+}
+
+fn append(env: &Env, owner: &Address, entry: &ActivityEntry) {
+    let count = get_activity_count(env, owner);
+    let page = count / ACTIVITY_PAGE_SIZE;
+
+    let mut entries = get_activity_page(env, owner, page);
+    entries.push_back(entry.clone());
+    set_activity_page(env, owner, page, &entries);
+    set_activity_count(env, owner, count + 1);
+}
+
+/// Read one stored page of an owner's activity, oldest first
+///
+/// The cursor is the page index; pass the returned cursor back for the next
+/// page until it is `None`. Each call loads a single storage page, where
+/// `read` walks past every skipped entry, and since entries only append a
+/// cursor stays valid while the feed grows.
+pub fn read_page(env: &Env, owner: &Address, cursor: u32) -> (Vec<ActivityEntry>, Option<u32>) {
+    let next = cursor.saturating_add(1);
+    let more = get_activity_count(env, owner) > next.saturating_mul(ACTIVITY_PAGE_SIZE);
+    (get_activity_page(env, owner, cursor), more.then_some(next))
+}
+
+/// Read an owner's activity from `from_ledger` on, oldest first
+///
+/// Skips the first `offset` matching entries and returns at most `limit`
+/// (capped at `MAX_ACTIVITY_LIMIT`). Entries are appended in ledger order,
+/// so the starting page is found by binary search and only the pages
+/// actually returned are loaded.
+pub fn read(
+    env: &Env,
+    owner: &Address,
+    from_ledger: u32,
+    offset: u32,
+    limit: u32,
+) -> Vec<ActivityEntry> {
+    let mut result = Vec::new(env);
+    let limit = limit.min(MAX_ACTIVITY_LIMIT);
+    let count = get_activity_count(env, owner);
+    if limit == 0 || count == 0 {
+        return result;
+    }
+
+    // First page whose last entry is at or after `from_ledger`
+    let pages = count.div_ceil(ACTIVITY_PAGE_SIZE);
+    let (mut lo, mut hi) = (0, pages);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let last = get_activity_page(env, owner, mid).last_unchecked();
+        if last.ledger < from_ledger {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let mut skipped = 0;
+    for page in lo..pages {
+        for entry in get_activity_page(env, owner, page).iter() {
+            if entry.ledger < from_ledger {
+                continue;
+            }
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+            result.push_back(entry);
+            if result.len() == limit {
+                return result;
+            }
+        }
+    }
+
+    result
+}
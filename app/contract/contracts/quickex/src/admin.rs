@@ -1,6 +1,12 @@
 use crate::errors::QuickexError;
-use crate::events::{publish_admin_changed, publish_contract_paused};
-use soroban_sdk::{symbol_short, Address, Env, Symbol};
+use crate::escrow::MAX_BPS;
+use crate::events::{
+    publish_admin_changed, publish_auditor_set, publish_contract_paused,
+    publish_token_min_amount_set, publish_token_paused,
+};
+use crate::storage;
+use crate::types::ContractConfig;
+use soroban_sdk::{symbol_short, token, Address, Env, Symbol};
 
 #[allow(dead_code)]
 const ADMIN_KEY: Symbol = symbol_short!("ADMIN");
@@ -32,13 +38,28 @@ pub fn get_admin(env: &Env) -> Option<Address> {
     env.storage().instance().get(&ADMIN_KEY)
 }
 
+/// Get the admin, failing with `NotInitialized` before `initialize`
+pub fn admin(env: &Env) -> Result<Address, QuickexError> {
+    storage::get_admin(env).ok_or(QuickexError::NotInitialized)
+}
+
 /// Require that the caller is the admin
-#[allow(dead_code)]
 pub fn require_admin(env: &Env, caller: &Address) -> Result<(), QuickexError> {
     caller.require_auth();
 
-    match get_admin(env) {
+    match crate::storage::get_admin(env) {
+        Some(admin) if admin == *caller => Ok(()),
+        _ => Err(QuickexError::Unauthorized),
+    }
+}
+
+/// Check that `caller` is the admin or an auditor
+pub fn require_admin_or_auditor(env: &Env, caller: &Address) -> Result<(), QuickexError> {
+    caller.require_auth();
+
+    match crate::storage::get_admin(env) {
         Some(admin) if admin == *caller => Ok(()),
+        _ if storage::is_auditor(env, caller) => Ok(()),
         _ => Err(QuickexError::Unauthorized),
     }
 }
@@ -70,6 +91,83 @@ pub fn set_paused(env: &Env, caller: Address, new_state: bool) -> Result<(), Qui
     Ok(())
 }
 
+/// Grant or revoke the auditor role (Admin only)
+///
+/// Auditors can read reports that expose private amounts but can't change
+/// anything.
+pub fn set_auditor(
+    env: &Env,
+    caller: Address,
+    auditor: Address,
+    enabled: bool,
+) -> Result<(), QuickexError> {
+    require_admin(env, &caller)?;
+
+    storage::set_auditor(env, &auditor, enabled);
+    publish_auditor_set(env, auditor, enabled);
+
+    Ok(())
+}
+
+/// Block or unblock new escrows and funding in one token (Admin only)
+///
+/// For an incident on a single asset: escrows already funded in the token
+/// can still be settled or refunded, and other tokens are unaffected.
+pub fn set_token_paused(
+    env: &Env,
+    caller: Address,
+    token: Address,
+    paused: bool,
+) -> Result<(), QuickexError> {
+    require_admin(env, &caller)?;
+
+    storage::set_token_paused(env, &token, paused);
+    publish_token_paused(env, token, paused);
+
+    Ok(())
+}
+
+/// Fail with `TokenPaused` if new escrows in `token` are blocked
+pub fn require_token_not_paused(env: &Env, token: &Address) -> Result<(), QuickexError> {
+    if storage::is_token_paused(env, token) {
+        return Err(QuickexError::TokenPaused);
+    }
+    Ok(())
+}
+
+/// Set the smallest amount an escrow in `token` may hold (Admin only)
+///
+/// Dust thresholds depend on the token's decimals, so each token gets its
+/// own; 0 removes it, leaving only the rule that amounts are positive. The
+/// token's reported decimals go in the event so operators can sanity check
+/// the scale.
+pub fn set_token_min_amount(
+    env: &Env,
+    caller: Address,
+    token: Address,
+    min: i128,
+) -> Result<(), QuickexError> {
+    require_admin(env, &caller)?;
+
+    if min < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    let decimals = token::Client::new(env, &token).decimals();
+    storage::set_token_min_amount(env, &token, min);
+    publish_token_min_amount_set(env, token, min, decimals);
+
+    Ok(())
+}
+
+/// Fail with `AmountBelowMinimum` if `amount` is under `token`'s minimum
+pub fn require_min_amount(env: &Env, token: &Address, amount: i128) -> Result<(), QuickexError> {
+    if amount < storage::get_token_min_amount(env, token) {
+        return Err(QuickexError::AmountBelowMinimum);
+    }
+    Ok(())
+}
+
 /// Check if the contract is paused
 pub fn is_paused(env: &Env) -> bool {
     env.storage().instance().get(&PAUSED_KEY).unwrap_or(false)
@@ -84,3 +182,66 @@ pub fn require_not_paused(env: &Env) -> Result<(), QuickexError> {
     }
     Ok(())
 }
+
+/// Check a basis-point setting does not exceed 100%
+pub fn validate_bps(bps: u32) -> Result<(), QuickexError> {
+    if bps > MAX_BPS {
+        return Err(QuickexError::InvalidFeeBps);
+    }
+    Ok(())
+}
+
+/// Check a cooling-off amount threshold (zero disables cooling-off)
+pub fn validate_cooling_off(threshold: i128) -> Result<(), QuickexError> {
+    if threshold < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    Ok(())
+}
+
+/// Check a keeper reward against the cap
+pub fn validate_keeper_reward(reward_bps: u32) -> Result<(), QuickexError> {
+    if reward_bps > crate::escrow::MAX_KEEPER_REWARD_BPS {
+        return Err(QuickexError::InvalidFeeBps);
+    }
+    Ok(())
+}
+
+/// Check a full configuration with the same rules as the individual setters
+pub fn validate_config(config: &ContractConfig) -> Result<(), QuickexError> {
+    validate_bps(config.dispute_bond_bps)?;
+    validate_bps(config.insurance_share_bps)?;
+    validate_cooling_off(config.cooling_off_threshold)?;
+    Ok(())
+}
+
+/// Replace every tunable parameter at once (Admin only)
+///
+/// Nothing is written unless the whole configuration is valid.
+pub fn set_config(env: &Env, caller: Address, config: ContractConfig) -> Result<(), QuickexError> {
+    require_admin(env, &caller)?;
+    validate_config(&config)?;
+
+    storage::set_dispute_bond_bps(env, config.dispute_bond_bps);
+    storage::set_insurance_share_bps(env, config.insurance_share_bps);
+    storage::set_max_escrow_lifetime(env, config.max_escrow_lifetime_secs);
+    storage::set_cooling_off(env, config.cooling_off_threshold, config.cooling_off_delay);
+    storage::set_nullifier_retention(env, config.nullifier_retention_secs);
+    storage::set_salt_recovery_delay(env, config.salt_recovery_delay_secs);
+
+    Ok(())
+}
+
+/// Read the current value of every tunable parameter
+pub fn get_config(env: &Env) -> ContractConfig {
+    let (cooling_off_threshold, cooling_off_delay) = storage::get_cooling_off(env);
+    ContractConfig {
+        dispute_bond_bps: storage::get_dispute_bond_bps(env),
+        insurance_share_bps: storage::get_insurance_share_bps(env),
+        max_escrow_lifetime_secs: storage::get_max_escrow_lifetime(env),
+        cooling_off_threshold,
+        cooling_off_delay,
+        nullifier_retention_secs: storage::get_nullifier_retention(env),
+        salt_recovery_delay_secs: storage::get_salt_recovery_delay(env),
+    }
+}
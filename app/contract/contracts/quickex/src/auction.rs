@@ -0,0 +1,144 @@
+use crate::commitment::create_amount_commitment;
+use crate::errors::QuickexError;
+use crate::events::publish_auction_finalized;
+use crate::features::{self, Feature};
+use crate::storage::{get_auction, get_bid, increment_auction_counter, put_auction, set_bid};
+use crate::types::Auction;
+use soroban_sdk::{Address, Bytes, BytesN, Env, Symbol};
+
+/// Seconds after bidding closes during which bids can be revealed
+pub const REVEAL_PERIOD: u64 = 24 * 60 * 60;
+
+fn find_auction(env: &Env, auction_id: u64) -> Result<Auction, QuickexError> {
+    get_auction(env, auction_id).ok_or(QuickexError::AuctionNotFound)
+}
+
+/// Open a sealed-bid auction for `item`
+///
+/// Bids are accepted until the ledger timestamp `reveal_after`, then revealed
+/// for `REVEAL_PERIOD` seconds before the auction can be finalized.
+pub fn open_auction(
+    env: &Env,
+    seller: Address,
+    item: Symbol,
+    reveal_after: u64,
+) -> Result<u64, QuickexError> {
+    features::require_enabled(env, Feature::Commitments)?;
+
+    if reveal_after <= env.ledger().timestamp() {
+        return Err(QuickexError::InvalidExpiry);
+    }
+
+    seller.require_auth();
+
+    let auction_id = increment_auction_counter(env);
+    put_auction(
+        env,
+        auction_id,
+        &Auction {
+            seller,
+            item,
+            reveal_after,
+            bid_count: 0,
+            highest_bidder: None,
+            highest_bid: 0,
+            finalized: false,
+        },
+    );
+
+    Ok(auction_id)
+}
+
+/// Place or replace a hidden bid
+///
+/// `commitment` is `create_amount_commitment(bidder, amount, salt)` for the
+/// amount bid; it is opened with `reveal_bid` once bidding closes.
+pub fn submit_bid(
+    env: &Env,
+    bidder: Address,
+    auction_id: u64,
+    commitment: BytesN<32>,
+) -> Result<(), QuickexError> {
+    let mut auction = find_auction(env, auction_id)?;
+
+    if env.ledger().timestamp() >= auction.reveal_after {
+        return Err(QuickexError::BiddingClosed);
+    }
+
+    bidder.require_auth();
+
+    if get_bid(env, auction_id, &bidder).is_none() {
+        auction.bid_count += 1;
+        put_auction(env, auction_id, &auction);
+    }
+    set_bid(env, auction_id, &bidder, Some(&commitment));
+
+    Ok(())
+}
+
+/// Open a bid and record it if it is the highest so far
+///
+/// Each bid is revealed once. On equal amounts the earlier reveal wins.
+pub fn reveal_bid(
+    env: &Env,
+    bidder: Address,
+    auction_id: u64,
+    amount: i128,
+    salt: Bytes,
+) -> Result<(), QuickexError> {
+    let mut auction = find_auction(env, auction_id)?;
+
+    let now = env.ledger().timestamp();
+    if now < auction.reveal_after {
+        return Err(QuickexError::AuctionNotEnded);
+    }
+    if auction.finalized || now >= auction.reveal_after.saturating_add(REVEAL_PERIOD) {
+        return Err(QuickexError::AuctionFinalized);
+    }
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    let commitment = get_bid(env, auction_id, &bidder).ok_or(QuickexError::CommitmentNotFound)?;
+    if create_amount_commitment(env, bidder.clone(), amount, salt)? != commitment {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    bidder.require_auth();
+    set_bid(env, auction_id, &bidder, None);
+
+    if amount > auction.highest_bid {
+        auction.highest_bid = amount;
+        auction.highest_bidder = Some(bidder);
+        put_auction(env, auction_id, &auction);
+    }
+
+    Ok(())
+}
+
+/// Close an auction after its reveal period and return the winner, if any
+/// bid was revealed
+///
+/// Anyone may finalize, so a seller can't hold the result back.
+pub fn finalize_auction(env: &Env, auction_id: u64) -> Result<Option<Address>, QuickexError> {
+    let mut auction = find_auction(env, auction_id)?;
+
+    if auction.finalized {
+        return Err(QuickexError::AuctionFinalized);
+    }
+    if env.ledger().timestamp() < auction.reveal_after.saturating_add(REVEAL_PERIOD) {
+        return Err(QuickexError::AuctionNotEnded);
+    }
+
+    auction.finalized = true;
+    put_auction(env, auction_id, &auction);
+
+    publish_auction_finalized(
+        env,
+        auction_id,
+        auction.highest_bidder.clone(),
+        auction.highest_bid,
+    );
+
+    Ok(auction.highest_bidder)
+}
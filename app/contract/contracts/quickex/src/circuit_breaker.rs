@@ -0,0 +1,60 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::storage::{
+    get_daily_outflow, get_daily_outflow_cap, set_daily_outflow, set_daily_outflow_cap,
+};
+use soroban_sdk::{Address, Env};
+
+/// Length of an outflow window in seconds
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn today(env: &Env) -> u64 {
+    env.ledger().timestamp() / SECONDS_PER_DAY
+}
+
+/// Amount released so far in the current day
+fn used_today(env: &Env) -> i128 {
+    let (day, used) = get_daily_outflow(env);
+    if day == today(env) {
+        used
+    } else {
+        0
+    }
+}
+
+/// Set the cap on escrow amounts released per day (Admin only)
+///
+/// Zero disables the breaker.
+pub fn set_cap(env: &Env, caller: Address, cap: i128) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    if cap < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    set_daily_outflow_cap(env, cap);
+    Ok(())
+}
+
+/// Headroom left under today's cap, or `i128::MAX` when no cap is set
+pub fn remaining(env: &Env) -> i128 {
+    let cap = get_daily_outflow_cap(env);
+    if cap == 0 {
+        return i128::MAX;
+    }
+    (cap - used_today(env)).max(0)
+}
+
+/// Count `amount` against today's cap, refusing a release that would exceed it
+pub fn consume(env: &Env, amount: i128) -> Result<(), QuickexError> {
+    let cap = get_daily_outflow_cap(env);
+    if cap == 0 {
+        return Ok(());
+    }
+
+    let used = used_today(env);
+    if amount > cap - used {
+        return Err(QuickexError::DailyLimitExceeded);
+    }
+
+    set_daily_outflow(env, today(env), used + amount);
+    Ok(())
+}
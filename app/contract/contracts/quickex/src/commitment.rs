@@ -1,18 +1,102 @@
+use crate::admin;
 use crate::errors::QuickexError;
-use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
+use crate::escrow::{new_escrow, open_escrow};
+use crate::events::publish_test_mode_changed;
+use crate::features::{self, Feature};
+use crate::merkle;
+use crate::storage::{
+    get_anchored_leaf, get_anchored_root, get_commitment_id, get_escrow, get_max_proof_len,
+    get_nullifier_retention, get_registered_commitment, get_root_history, get_root_history_count,
+    increment_commitment_counter, is_commitment_salted, is_test_mode, put_anchored_leaf,
+    put_anchored_root, put_registered_commitment, put_root_history, record_commitment_salted,
+    remove_escrow, set_root_history_count,
+};
+use crate::types::{
+    AnchoredRoot, CommitmentParams, CommitmentProofBundle, CommitmentRecord, EscrowEntry,
+    EscrowStatus, MixedCommitment, TermsPreimage,
+};
+use soroban_sdk::{symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+/// Version of the commitment preimage layout
+pub const COMMITMENT_SCHEME_VERSION: u32 = 1;
+
+/// Version of the asset-bound layout, which adds the token after the owner
+pub const ASSET_COMMITMENT_SCHEME_VERSION: u32 = 2;
+
+/// Version of the timestamped layout, which adds the creation time after the amount
+pub const TIMESTAMPED_COMMITMENT_SCHEME_VERSION: u32 = 3;
+
+/// Number of hash rounds applied to the preimage
+pub const COMMITMENT_HASH_ROUNDS: u32 = 1;
+
+/// Maximum salt length in bytes
+pub const MAX_SALT_LEN: u32 = 1024;
+
+/// Number of recent anchored roots kept for historical lookups
+pub const ROOT_HISTORY_LEN: u32 = 256;
+
+/// Maximum number of commitments handled by one batch call
+pub const MAX_COMMITMENT_BATCH: u32 = 64;
+
+/// Network passphrase of the Stellar public network, where test mode is refused
+const MAINNET_PASSPHRASE: &[u8] = b"Public Global Stellar Network ; September 2015";
+
+/// Default time a spent commitment is kept before it can be pruned (30 days)
+pub const DEFAULT_NULLIFIER_RETENTION: u64 = 30 * 24 * 60 * 60;
+
+/// Default cap on inclusion proof length, enough for trees of 2^32 leaves
+pub const DEFAULT_MAX_PROOF_LEN: u32 = 32;
+
+/// Parameters clients need to reconstruct commitments off-chain
+pub fn commitment_params(env: &Env) -> CommitmentParams {
+    CommitmentParams {
+        algorithm: symbol_short!("sha256"),
+        rounds: COMMITMENT_HASH_ROUNDS,
+        domain: Bytes::new(env),
+        version: COMMITMENT_SCHEME_VERSION,
+        max_salt_len: MAX_SALT_LEN,
+    }
+}
+
+/// Parameters of the asset-bound scheme used by `create_asset_amount_commitment`
+pub fn asset_commitment_params(env: &Env) -> CommitmentParams {
+    CommitmentParams {
+        version: ASSET_COMMITMENT_SCHEME_VERSION,
+        ..commitment_params(env)
+    }
+}
+
+/// Parameters of the timestamped scheme used by `create_timestamped_commitment`
+pub fn timestamped_commitment_params(env: &Env) -> CommitmentParams {
+    CommitmentParams {
+        version: TIMESTAMPED_COMMITMENT_SCHEME_VERSION,
+        ..commitment_params(env)
+    }
+}
 
 pub fn create_amount_commitment(
     env: &Env,
     owner: Address,
     amount: i128,
     salt: Bytes,
+) -> Result<BytesN<32>, QuickexError> {
+    amount_commitment(env, owner, amount, salt, !is_test_mode(env))
+}
+
+/// The scheme 1 hash, with or without the salt
+fn amount_commitment(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+    salted: bool,
 ) -> Result<BytesN<32>, QuickexError> {
     if amount < 0 {
         return Err(QuickexError::InvalidAmount);
     }
 
     // Cap salt length as a safeguard
-    if salt.len() > 1024 {
+    if salt.len() > MAX_SALT_LEN {
         return Err(QuickexError::InvalidSalt);
     }
 
@@ -29,13 +113,211 @@ pub fn create_amount_commitment(
         payload.push_back(*b);
     }
 
-    // Append salt
-    payload.append(&salt);
+    // Test mode leaves the salt out so integrations get reproducible hashes
+    if salted {
+        payload.append(&salt);
+    }
 
     // Return SHA256 hash
     Ok(env.crypto().sha256(&payload).into())
 }
 
+/// Find the scheme 1 deposit that `owner`, `amount` and `salt` open
+///
+/// Tries the salted hash first, then the salt-free one for deposits made
+/// under test mode, so a withdrawal doesn't depend on the mode at the time.
+pub fn find_amount_deposit(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<(BytesN<32>, EscrowEntry), QuickexError> {
+    let salted = amount_commitment(env, owner.clone(), amount, salt.clone(), true)?;
+    if let Some(entry) = get_escrow(env, &salted.clone().into()) {
+        return Ok((salted, entry));
+    }
+
+    let unsalted = amount_commitment(env, owner, amount, salt, false)?;
+    if !is_commitment_salted(env, &unsalted) {
+        if let Some(entry) = get_escrow(env, &unsalted.clone().into()) {
+            return Ok((unsalted, entry));
+        }
+    }
+    Err(QuickexError::CommitmentNotFound)
+}
+
+/// Remember whether a commitment entering the contract was made under test mode
+pub fn record_mode(env: &Env, commitment: &BytesN<32>) {
+    record_commitment_salted(env, commitment, !is_test_mode(env));
+}
+
+/// Switch the deterministic commitment test mode on or off (Admin only)
+///
+/// INSECURE: while on, `create_amount_commitment` ignores the salt, so any
+/// commitment can be opened by guessing the amount. Meant only for
+/// integration testing, and refused on the public network. Commitments
+/// keep the mode they were deposited or registered under.
+pub fn set_test_mode(env: &Env, caller: Address, enabled: bool) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let mainnet: BytesN<32> = env
+        .crypto()
+        .sha256(&Bytes::from_slice(env, MAINNET_PASSPHRASE))
+        .into();
+    if enabled && env.ledger().network_id() == mainnet {
+        return Err(QuickexError::TestModeForbidden);
+    }
+
+    crate::storage::set_test_mode(env, enabled);
+    publish_test_mode_changed(env, enabled, &caller);
+    Ok(())
+}
+
+/// Create a commitment binding the token as well as the amount (scheme v2)
+///
+/// The preimage is `xdr(owner) || xdr(token) || amount_be_i128 || salt`, so
+/// the same amount of two assets never shares a commitment.
+pub fn create_asset_amount_commitment(
+    env: &Env,
+    owner: Address,
+    token: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    if salt.len() > MAX_SALT_LEN {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut payload = owner.to_xdr(env);
+    payload.append(&token.to_xdr(env));
+    payload.extend_from_array(&amount.to_be_bytes());
+    payload.append(&salt);
+
+    Ok(env.crypto().sha256(&payload).into())
+}
+
+/// Commitment bound to its creation time (scheme v3)
+///
+/// The preimage is `xdr(owner) || amount_be_i128 || created_at_be_u64 || salt`,
+/// so an old commitment can't be passed off as a new one: it only opens
+/// with the timestamp it was made at.
+pub fn timestamped_commitment(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    created_at: u64,
+    salt: Bytes,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    if salt.len() > MAX_SALT_LEN {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut payload = owner.to_xdr(env);
+    payload.extend_from_array(&amount.to_be_bytes());
+    payload.extend_from_array(&created_at.to_be_bytes());
+    payload.append(&salt);
+
+    Ok(env.crypto().sha256(&payload).into())
+}
+
+/// Create a scheme v3 commitment stamped with the current ledger time
+///
+/// Returns the hash and the timestamp used; both are needed to verify it.
+pub fn create_timestamped_commitment(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<(BytesN<32>, u64), QuickexError> {
+    let created_at = env.ledger().timestamp();
+    let commitment = timestamped_commitment(env, owner, amount, created_at, salt)?;
+    Ok((commitment, created_at))
+}
+
+/// Check a scheme v3 commitment against its opening and creation time
+pub fn verify_timestamped_commitment(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    amount: i128,
+    created_at: u64,
+    salt: Bytes,
+) -> bool {
+    match timestamped_commitment(env, owner, amount, created_at, salt) {
+        Ok(hash) => hash == commitment,
+        Err(_) => false,
+    }
+}
+
+/// Recompute the commitments for many openings by one owner
+///
+/// Lets clients check a whole set before submitting spends. `amounts` and
+/// `salts` pair up by position and must be the same length.
+pub fn recompute_batch(
+    env: &Env,
+    owner: Address,
+    amounts: Vec<i128>,
+    salts: Vec<Bytes>,
+) -> Result<Vec<BytesN<32>>, QuickexError> {
+    if amounts.len() != salts.len() || amounts.len() > MAX_COMMITMENT_BATCH {
+        return Err(QuickexError::InvalidBatch);
+    }
+
+    let mut commitments = Vec::new(env);
+    for (amount, salt) in amounts.iter().zip(salts.iter()) {
+        commitments.push_back(create_amount_commitment(env, owner.clone(), amount, salt)?);
+    }
+    Ok(commitments)
+}
+
+/// Create an amount commitment with contract randomness mixed into the salt
+///
+/// The effective salt is `sha256(salt || 32 PRNG bytes)`, so the commitment
+/// stays hiding even if the client's salt came from a weak RNG. The client
+/// must keep the returned salt; its own input salt no longer opens the
+/// commitment.
+pub fn create_mixed_commitment(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<MixedCommitment, QuickexError> {
+    if salt.len() > MAX_SALT_LEN {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut entropy = salt;
+    entropy.extend_from_array(&env.prng().gen::<[u8; 32]>());
+    let effective_salt: Bytes = env.crypto().sha256(&entropy).to_bytes().into();
+
+    let commitment = create_amount_commitment(env, owner, amount, effective_salt.clone())?;
+
+    Ok(MixedCommitment {
+        commitment,
+        effective_salt,
+    })
+}
+
+/// Lowercase hex encoding of a commitment, as 64 ASCII bytes
+pub fn commitment_to_hex(env: &Env, commitment: &BytesN<32>) -> Bytes {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut hex = [0u8; 64];
+    for (i, byte) in commitment.to_array().iter().enumerate() {
+        hex[2 * i] = DIGITS[(byte >> 4) as usize];
+        hex[2 * i + 1] = DIGITS[(byte & 0x0f) as usize];
+    }
+    Bytes::from_array(env, &hex)
+}
+
 pub fn verify_amount_commitment(
     env: &Env,
     commitment: BytesN<32>,
@@ -48,3 +330,360 @@ pub fn verify_amount_commitment(
         Err(_) => false,
     }
 }
+
+/// Recompute the commitment for the given opening and compare it in one go
+///
+/// Returns whether it matched along with the recomputed hash, so a failed
+/// verification can be debugged without a second call.
+pub fn verify_and_diff(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<(bool, BytesN<32>), QuickexError> {
+    let recomputed = create_amount_commitment(env, owner, amount, salt)?;
+    Ok((recomputed == commitment, recomputed))
+}
+
+/// Commit to complete escrow terms without revealing them
+///
+/// The hash covers the owner, the XDR encoding of `terms` and the salt, so
+/// every field is bound canonically.
+pub fn create_terms_commitment(
+    env: &Env,
+    owner: Address,
+    terms: TermsPreimage,
+    salt: Bytes,
+) -> Result<BytesN<32>, QuickexError> {
+    if salt.len() > MAX_SALT_LEN {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut payload = owner.to_xdr(env);
+    payload.append(&terms.to_xdr(env));
+    payload.append(&salt);
+
+    Ok(env.crypto().sha256(&payload).into())
+}
+
+/// Reveal committed terms and open an escrow with exactly those terms
+///
+/// Fails with `TermsMismatch` if the reveal doesn't reproduce `commitment`.
+pub fn create_escrow_from_terms(
+    env: &Env,
+    owner: Address,
+    commitment: BytesN<32>,
+    terms: TermsPreimage,
+    salt: Bytes,
+) -> Result<u64, QuickexError> {
+    if create_terms_commitment(env, owner.clone(), terms.clone(), salt)? != commitment {
+        return Err(QuickexError::TermsMismatch);
+    }
+
+    let mut escrow = new_escrow(
+        env,
+        owner,
+        terms.recipient,
+        terms.token,
+        terms.amount,
+        Bytes::new(env),
+    );
+    escrow.deadline = terms.deadline;
+    escrow.arbiter = terms.arbiter;
+    escrow.fee_bps = terms.fee_bps;
+
+    open_escrow(env, escrow)
+}
+
+pub fn register_commitment(
+    env: &Env,
+    owner: Address,
+    commitment: BytesN<32>,
+) -> Result<u64, QuickexError> {
+    features::require_enabled(env, Feature::Commitments)?;
+    owner.require_auth();
+    store_registered(env, owner, commitment, None)
+}
+
+/// Register a scheme v2 commitment along with the token it binds
+pub fn register_asset_commitment(
+    env: &Env,
+    owner: Address,
+    token: Address,
+    commitment: BytesN<32>,
+) -> Result<u64, QuickexError> {
+    features::require_enabled(env, Feature::Commitments)?;
+    owner.require_auth();
+    store_registered(env, owner, commitment, Some(token))
+}
+
+/// Register a batch of commitments and anchor their Merkle root
+///
+/// Each leaf gets its own registry entry as with `register_commitment`, and
+/// the root is recorded so inclusion proofs can be checked against it later.
+/// The whole batch fails if any leaf is already registered.
+pub fn register_anchored(
+    env: &Env,
+    owner: Address,
+    leaves: Vec<BytesN<32>>,
+) -> Result<BytesN<32>, QuickexError> {
+    features::require_enabled(env, Feature::Commitments)?;
+
+    if leaves.is_empty() || leaves.len() > MAX_COMMITMENT_BATCH {
+        return Err(QuickexError::InvalidBatch);
+    }
+
+    owner.require_auth();
+
+    for leaf in leaves.iter() {
+        store_registered(env, owner.clone(), leaf, None)?;
+    }
+
+    let root = merkle::root(env, &leaves).ok_or(QuickexError::InvalidBatch)?;
+    for (index, leaf) in leaves.iter().enumerate() {
+        put_anchored_leaf(env, &leaf, &root, index as u32);
+    }
+    put_anchored_root(
+        env,
+        &root,
+        &AnchoredRoot {
+            owner,
+            leaf_count: leaves.len(),
+            anchored_at: env.ledger().sequence(),
+        },
+    );
+    push_root_history(env, &root);
+
+    Ok(root)
+}
+
+/// Append an anchored root to the ring of the last `ROOT_HISTORY_LEN` roots
+fn push_root_history(env: &Env, root: &BytesN<32>) {
+    let count = get_root_history_count(env);
+    put_root_history(
+        env,
+        count % ROOT_HISTORY_LEN,
+        &(root.clone(), env.ledger().sequence()),
+    );
+    set_root_history_count(env, count + 1);
+}
+
+/// Latest anchored root recorded at or before `ledger`, with its ledger
+///
+/// Only the last `ROOT_HISTORY_LEN` roots are kept, so None also covers
+/// ledgers older than the ring reaches. Entries are in ledger order, so this
+/// binary-searches the ring one slot read at a time.
+pub fn root_at_or_before(env: &Env, ledger: u32) -> Option<(BytesN<32>, u32)> {
+    let count = get_root_history_count(env);
+    let oldest = count.saturating_sub(ROOT_HISTORY_LEN);
+
+    // Find the first entry after `ledger`; the one before it is the answer
+    let (mut lo, mut hi) = (oldest, count);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let (_, at) = get_root_history(env, mid % ROOT_HISTORY_LEN)?;
+        if at <= ledger {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+
+    if lo == oldest {
+        return None;
+    }
+    get_root_history(env, (lo - 1) % ROOT_HISTORY_LEN)
+}
+
+/// Check an inclusion proof for `leaf` against an anchored root
+///
+/// Returns false for roots that were never anchored. Proofs longer than the
+/// configured maximum are refused with `ProofTooLong` before any hashing.
+pub fn verify_anchored(
+    env: &Env,
+    root: BytesN<32>,
+    leaf: BytesN<32>,
+    proof: Vec<BytesN<32>>,
+) -> Result<bool, QuickexError> {
+    if proof.len() > get_max_proof_len(env) {
+        return Err(QuickexError::ProofTooLong);
+    }
+    Ok(get_anchored_root(env, &root).is_some() && merkle::verify(env, &root, &leaf, &proof))
+}
+
+/// Store a registry entry; the caller has checked the feature flag and auth
+fn store_registered(
+    env: &Env,
+    owner: Address,
+    commitment: BytesN<32>,
+    token: Option<Address>,
+) -> Result<u64, QuickexError> {
+    if get_commitment_id(env, &commitment).is_some() {
+        return Err(QuickexError::CommitmentAlreadyExists);
+    }
+
+    if token.is_none() {
+        record_mode(env, &commitment);
+    }
+
+    let id = increment_commitment_counter(env);
+    let record = CommitmentRecord {
+        commitment,
+        owner,
+        registered_at: env.ledger().sequence(),
+        token,
+    };
+    put_registered_commitment(env, id, &record);
+
+    Ok(id)
+}
+
+/// Load a registered commitment for a read entry point
+pub fn find_registered(env: &Env, id: u64) -> Result<CommitmentRecord, QuickexError> {
+    get_registered_commitment(env, id).ok_or_else(|| commitment_missing(env))
+}
+
+/// Load the record of an anchored root for a read entry point
+pub fn find_anchored(env: &Env, root: &BytesN<32>) -> Result<AnchoredRoot, QuickexError> {
+    get_anchored_root(env, root).ok_or_else(|| commitment_missing(env))
+}
+
+/// Load the commitment-keyed escrow entry for a read entry point
+pub fn find_entry(env: &Env, commitment: BytesN<32>) -> Result<EscrowEntry, QuickexError> {
+    get_escrow(env, &commitment.into()).ok_or_else(|| commitment_missing(env))
+}
+
+/// Gather the public parameters of a registered commitment for off-chain checks
+///
+/// With the bundle and the preimage a verifier rebuilds `sha256` over
+/// `xdr(owner) || amount_be_i128 || salt` (scheme 1) or
+/// `xdr(owner) || xdr(token) || amount_be_i128 || salt` (scheme 2), leaving
+/// the salt out when `salted` is false, and compares it to `commitment`.
+pub fn export_proof(
+    env: &Env,
+    commitment: BytesN<32>,
+) -> Result<CommitmentProofBundle, QuickexError> {
+    let id = get_commitment_id(env, &commitment).ok_or_else(|| commitment_missing(env))?;
+    let record = find_registered(env, id)?;
+    let anchor = get_anchored_leaf(env, &commitment);
+    let status = get_escrow(env, &commitment.clone().into())
+        .map_or(EscrowStatus::Pending, |entry| entry.status);
+
+    let params = match record.token {
+        Some(_) => asset_commitment_params(env),
+        None => commitment_params(env),
+    };
+
+    Ok(CommitmentProofBundle {
+        commitment,
+        scheme: params.version,
+        algorithm: params.algorithm,
+        domain: params.domain,
+        salted: record.token.is_some() || is_commitment_salted(env, &record.commitment),
+        owner: record.owner,
+        token: record.token,
+        registered_at: record.registered_at,
+        merkle_index: anchor.as_ref().map(|(_, index)| *index),
+        root: anchor.map(|(root, _)| root),
+        status,
+    })
+}
+
+/// Why a commitment lookup came back empty
+fn commitment_missing(env: &Env) -> QuickexError {
+    if features::is_enabled(env, Feature::Commitments) {
+        QuickexError::CommitmentNotFound
+    } else {
+        QuickexError::FeatureDisabled
+    }
+}
+
+/// Verify an opening against a registered commitment
+///
+/// Records that carry a token are checked with the asset-bound scheme using
+/// that token; older records with the token-less scheme.
+pub fn verify_registered(env: &Env, id: u64, owner: Address, amount: i128, salt: Bytes) -> bool {
+    let Some(record) = get_registered_commitment(env, id) else {
+        return false;
+    };
+
+    opens_record(env, record, owner, amount, salt)
+}
+
+/// Verify an opening, but only for commitments in the on-chain registry
+///
+/// Fails with `CommitmentNotRegistered` for unknown hashes, so only
+/// commitments someone registered can be accepted, then with
+/// `CommitmentMismatch` if the opening is wrong.
+pub fn verify_and_require_registered(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<(), QuickexError> {
+    let id = get_commitment_id(env, &commitment).ok_or(QuickexError::CommitmentNotRegistered)?;
+    let record = get_registered_commitment(env, id).ok_or(QuickexError::CommitmentNotRegistered)?;
+
+    if !opens_record(env, record, owner, amount, salt) {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+    Ok(())
+}
+
+/// Whether an opening matches a registry record under the record's scheme
+fn opens_record(
+    env: &Env,
+    record: CommitmentRecord,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> bool {
+    match record.token {
+        Some(token) => create_asset_amount_commitment(env, owner, token, amount, salt)
+            .is_ok_and(|hash| hash == record.commitment),
+        None => verify_amount_commitment(env, record.commitment, owner, amount, salt),
+    }
+}
+
+/// Remove spent commitment entries whose retention period has passed (Admin only)
+///
+/// A spent entry is the nullifier that stops a commitment being withdrawn
+/// twice. Once its funds are gone and the retention period since the deposit
+/// has elapsed the entry can go: a replayed withdrawal still fails, it just
+/// reports `CommitmentNotFound` instead of `AlreadySpent`. Pending entries and
+/// entries still within retention are skipped.
+///
+/// Returns the number of entries removed.
+pub fn prune_nullifiers(
+    env: &Env,
+    caller: Address,
+    ids: Vec<BytesN<32>>,
+) -> Result<u32, QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let now = env.ledger().timestamp();
+    let retention = get_nullifier_retention(env);
+    let mut pruned = 0;
+
+    for id in ids.iter() {
+        let commitment: Bytes = id.into();
+        let Some(entry) = get_escrow(env, &commitment) else {
+            continue;
+        };
+
+        if entry.status != EscrowStatus::Spent {
+            continue;
+        }
+
+        if now < entry.created_at.saturating_add(retention) {
+            continue;
+        }
+
+        remove_escrow(env, &commitment);
+        pruned += 1;
+    }
+
+    Ok(pruned)
+}
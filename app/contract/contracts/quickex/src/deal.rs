@@ -0,0 +1,93 @@
+use crate::errors::QuickexError;
+use crate::escrow::find_escrow;
+use crate::reporting::is_open;
+use crate::storage::{
+    get_deal, get_escrow_by_id, get_escrow_deal, increment_deal_counter, put_deal, set_escrow_deal,
+};
+use crate::types::{Deal, DealStatus, DealSummary, EscrowStatus};
+use soroban_sdk::{Address, Env, Vec};
+
+/// Most escrows one deal can link
+pub const MAX_DEAL_ESCROWS: u32 = 16;
+
+/// Start an empty deal owned by `owner`
+pub fn create_deal(env: &Env, owner: Address) -> u64 {
+    owner.require_auth();
+
+    let deal_id = increment_deal_counter(env);
+    put_deal(
+        env,
+        deal_id,
+        &Deal {
+            owner,
+            escrows: Vec::new(env),
+        },
+    );
+
+    deal_id
+}
+
+/// Add an escrow to a deal
+///
+/// The caller must own the deal and be the escrow's sender or recipient.
+/// An escrow belongs to at most one deal.
+pub fn link_escrow(
+    env: &Env,
+    deal_id: u64,
+    escrow_id: u64,
+    caller: Address,
+) -> Result<(), QuickexError> {
+    let mut deal = get_deal(env, deal_id).ok_or(QuickexError::DealNotFound)?;
+    let escrow = find_escrow(env, escrow_id)?;
+
+    if caller != deal.owner || (caller != escrow.from && caller != escrow.to) {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    if get_escrow_deal(env, escrow_id).is_some() {
+        return Err(QuickexError::EscrowAlreadyLinked);
+    }
+    if deal.escrows.len() >= MAX_DEAL_ESCROWS {
+        return Err(QuickexError::TooManyDealEscrows);
+    }
+
+    deal.escrows.push_back(escrow_id);
+    put_deal(env, deal_id, &deal);
+    set_escrow_deal(env, escrow_id, deal_id);
+
+    Ok(())
+}
+
+/// A deal's linked escrows and their aggregate status
+///
+/// Open while any linked escrow is unsettled, or while none are linked;
+/// Complete once every one was released; Failed if all settled but some
+/// were refunded or expired instead.
+pub fn deal_summary(env: &Env, deal_id: u64) -> Option<DealSummary> {
+    let deal = get_deal(env, deal_id)?;
+
+    let mut status = if deal.escrows.is_empty() {
+        DealStatus::Open
+    } else {
+        DealStatus::Complete
+    };
+    for escrow_id in deal.escrows.iter() {
+        let Some(escrow) = get_escrow_by_id(env, escrow_id) else {
+            continue;
+        };
+        if is_open(escrow.status) {
+            status = DealStatus::Open;
+            break;
+        }
+        if escrow.status != EscrowStatus::Released {
+            status = DealStatus::Failed;
+        }
+    }
+
+    Some(DealSummary {
+        owner: deal.owner,
+        escrows: deal.escrows,
+        status,
+    })
+}
@@ -0,0 +1,337 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::escrow::{
+    advance, pay_recipient, pay_to_recipient, refund_sender, renew, return_funds,
+    settlement_breakdown, MAX_BPS,
+};
+use crate::events::{
+    publish_dispute_message, publish_dispute_opened, publish_dispute_resolved,
+    publish_dispute_split, publish_evidence_submitted,
+};
+use crate::insurance::accrue_fee;
+use crate::ranking;
+use crate::receiver;
+use crate::reporting::{adjust_owed, record_settlement};
+use crate::reputation;
+use crate::storage::{
+    get_admin, get_dispute_bond_bps, get_dispute_messages, get_escrow_by_id, get_evidence,
+    put_escrow_by_id, set_dispute_bond_bps, set_dispute_messages, set_evidence, DataKey,
+};
+use crate::types::{DisputeMessage, Escrow, EscrowStatus, Resolution};
+use crate::yield_adapter;
+use soroban_sdk::{token, Address, BytesN, Env, Vec};
+
+/// Maximum number of messages kept for one dispute
+pub const MAX_DISPUTE_MESSAGES: u32 = 32;
+
+/// Maximum number of evidence hashes anchored on one escrow
+pub const MAX_EVIDENCE: u32 = 32;
+
+/// Set the bond, in bps of the escrow amount, posted to open a dispute (Admin only)
+///
+/// Zero disables bonds.
+pub fn set_bond_bps(env: &Env, caller: Address, bond_bps: u32) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    admin::validate_bps(bond_bps)?;
+
+    set_dispute_bond_bps(env, bond_bps);
+    Ok(())
+}
+
+/// Put a pending escrow into dispute on behalf of either counterparty
+///
+/// The opener posts the configured bond, which is held on the escrow until
+/// the dispute is resolved.
+pub fn open_dispute(env: &Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if caller != escrow.from && caller != escrow.to {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    start_dispute(env, caller, escrow_id, &mut escrow, true)
+}
+
+/// Move an escrow into dispute for `caller`, who must already have authorized
+///
+/// With `bonded` the caller posts the configured bond; without it the
+/// dispute holds none.
+pub(crate) fn start_dispute(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    bonded: bool,
+) -> Result<(), QuickexError> {
+    advance(env, escrow_id, escrow, EscrowStatus::Disputed)?;
+
+    let bond = if bonded {
+        escrow.amount * get_dispute_bond_bps(env) as i128 / MAX_BPS as i128
+    } else {
+        0
+    };
+    if bond > 0 {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(&caller, env.current_contract_address(), &bond);
+        adjust_owed(env, DataKey::BondsHeld(escrow.token.clone()), bond);
+    }
+
+    escrow.dispute_opener = Some(caller.clone());
+    escrow.dispute_bond = bond;
+    put_escrow_by_id(env, escrow_id, escrow);
+    reputation::record_dispute_opened(env, escrow, &caller);
+
+    publish_dispute_opened(env, escrow_id, caller, bond);
+
+    Ok(())
+}
+
+/// Record the hash of a negotiation message on a disputed escrow
+///
+/// Either party or the resolver may post. Messages keep their posting order
+/// and can't be edited, giving a tamper-evident record of the negotiation.
+pub fn post_message(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+    msg_hash: BytesN<32>,
+) -> Result<(), QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let resolver = escrow.arbiter.clone().or_else(|| get_admin(env));
+    if caller != escrow.from && caller != escrow.to && Some(caller.clone()) != resolver {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    if escrow.status != EscrowStatus::Disputed {
+        return Err(QuickexError::EscrowNotDisputed);
+    }
+
+    let mut messages = get_dispute_messages(env, escrow_id);
+    if messages.len() >= MAX_DISPUTE_MESSAGES {
+        return Err(QuickexError::DisputeLogFull);
+    }
+
+    let index = messages.len();
+    messages.push_back(DisputeMessage {
+        sender: caller.clone(),
+        msg_hash: msg_hash.clone(),
+        timestamp: env.ledger().timestamp(),
+    });
+    set_dispute_messages(env, escrow_id, &messages);
+
+    publish_dispute_message(env, escrow_id, caller, msg_hash, index);
+
+    Ok(())
+}
+
+/// Anchor the hash of a piece of evidence on an escrow for its arbiter
+///
+/// Only the sender and recipient may submit, before or during a dispute but
+/// not once the escrow is settled. Hashes keep their submission order.
+pub fn submit_evidence(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    evidence_hash: BytesN<32>,
+) -> Result<(), QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if caller != escrow.from && caller != escrow.to {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    if !matches!(
+        escrow.status,
+        EscrowStatus::Pending | EscrowStatus::Disputed
+    ) {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    let mut evidence = get_evidence(env, escrow_id);
+    if evidence.len() >= MAX_EVIDENCE {
+        return Err(QuickexError::DisputeLogFull);
+    }
+
+    let index = evidence.len();
+    evidence.push_back(evidence_hash.clone());
+    set_evidence(env, escrow_id, &evidence);
+
+    publish_evidence_submitted(env, escrow_id, caller, evidence_hash, index);
+
+    Ok(())
+}
+
+/// Return the `offset..offset + limit` window of a dispute's messages
+pub fn messages(env: &Env, escrow_id: u64, offset: u32, limit: u32) -> Vec<DisputeMessage> {
+    let messages = get_dispute_messages(env, escrow_id);
+    let end = offset.saturating_add(limit).min(messages.len());
+    if offset >= end {
+        return Vec::new(env);
+    }
+    messages.slice(offset..end)
+}
+
+/// Settle a disputed escrow, releasing it to the recipient or refunding the sender
+///
+/// Only the escrow's arbiter may rule, or the admin when no arbiter was set.
+/// The bond goes back to the opener if the ruling favours them and to the
+/// counterparty otherwise.
+pub fn resolve_dispute(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    release: bool,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+    require_resolver(env, &escrow, &caller)?;
+
+    let next = if release {
+        EscrowStatus::Released
+    } else {
+        EscrowStatus::Refunded
+    };
+    advance(env, escrow_id, &mut escrow, next)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    let bond_to = settle_bond(env, &escrow, if release { MAX_BPS } else { 0 });
+    reputation::record_dispute_loss(env, &escrow, release);
+
+    if release {
+        pay_recipient(env, escrow_id, &escrow);
+    } else {
+        refund_sender(env, escrow_id, &escrow);
+    }
+
+    publish_dispute_resolved(env, escrow_id, release, bond_to);
+
+    Ok(())
+}
+
+/// Settle a disputed escrow by splitting it between the parties
+///
+/// The protocol fee is taken first, then `to_recipient_bps` of what remains
+/// goes to the recipient and the rest back to the sender. A split of 0 is a
+/// full refund and, like `resolve_dispute`, takes no fee. A full award either
+/// way settles the bond like `resolve_dispute`; a partial one returns it to
+/// the opener.
+pub fn resolve_dispute_split(
+    env: &Env,
+    escrow_id: u64,
+    arbiter: Address,
+    to_recipient_bps: u32,
+) -> Result<(), QuickexError> {
+    admin::validate_bps(to_recipient_bps)?;
+
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+    require_resolver(env, &escrow, &arbiter)?;
+
+    let next = if to_recipient_bps == 0 {
+        EscrowStatus::Refunded
+    } else {
+        EscrowStatus::Released
+    };
+    advance(env, escrow_id, &mut escrow, next)?;
+    escrow.split_bps = Some(to_recipient_bps);
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    yield_adapter::recall(env, escrow_id, &escrow);
+    record_settlement(env, &escrow.token, escrow.amount);
+    ranking::remove(env, &escrow.token, escrow_id);
+
+    let breakdown = settlement_breakdown(env, &escrow, &Resolution::Split(to_recipient_bps));
+    let fee = breakdown.protocol_fee;
+    accrue_fee(env, &escrow.token, fee - escrow.fee_paid);
+
+    let to_recipient = breakdown.to_recipient;
+    let to_sender = breakdown.to_sender;
+
+    pay_to_recipient(env, escrow_id, &escrow, to_recipient);
+    return_funds(env, escrow_id, &escrow, to_sender, false);
+
+    if to_recipient_bps == 0 || to_recipient_bps == MAX_BPS {
+        reputation::record_dispute_loss(env, &escrow, to_recipient_bps == MAX_BPS);
+    }
+    let bond_to = settle_bond(env, &escrow, to_recipient_bps);
+
+    publish_dispute_split(env, escrow_id, to_recipient, to_sender, fee, bond_to);
+    receiver::notify_escrow_event(env, escrow_id, escrow.status);
+    renew(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Check that `caller` may rule on the escrow: its arbiter, or the admin if it has none
+pub(crate) fn require_resolver(
+    env: &Env,
+    escrow: &Escrow,
+    caller: &Address,
+) -> Result<(), QuickexError> {
+    let resolver = escrow.arbiter.clone().or_else(|| get_admin(env));
+    if Some(caller.clone()) != resolver {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+    Ok(())
+}
+
+/// Give the dispute bond back to the opener
+pub(crate) fn return_bond(env: &Env, escrow: &Escrow) -> Option<Address> {
+    let opener = escrow.dispute_opener.clone()?;
+
+    if escrow.dispute_bond > 0 {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &opener,
+            &escrow.dispute_bond,
+        );
+        adjust_owed(
+            env,
+            DataKey::BondsHeld(escrow.token.clone()),
+            -escrow.dispute_bond,
+        );
+    }
+
+    Some(opener)
+}
+
+/// Who the dispute bond goes to when the recipient is awarded
+/// `to_recipient_bps` of the escrow, or `None` if it was never disputed
+///
+/// A full award either way goes to the winner: with two counterparties that
+/// is the opener on a win and the other side when the bond is forfeited. A
+/// partial award returns it to the opener.
+pub(crate) fn bond_recipient(escrow: &Escrow, to_recipient_bps: u32) -> Option<Address> {
+    let opener = escrow.dispute_opener.clone()?;
+
+    Some(match to_recipient_bps {
+        0 => escrow.from.clone(),
+        MAX_BPS => escrow.to.clone(),
+        _ => opener,
+    })
+}
+
+/// Pay the dispute bond as `bond_recipient` rules and return who got it
+fn settle_bond(env: &Env, escrow: &Escrow, to_recipient_bps: u32) -> Option<Address> {
+    let winner = bond_recipient(escrow, to_recipient_bps)?;
+
+    if escrow.dispute_bond > 0 {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &winner,
+            &escrow.dispute_bond,
+        );
+        adjust_owed(
+            env,
+            DataKey::BondsHeld(escrow.token.clone()),
+            -escrow.dispute_bond,
+        );
+    }
+
+    Some(winner)
+}
@@ -16,4 +16,78 @@ pub enum QuickexError {
     InvalidCommitment = 10,
     ContractPaused = 11,
     CommitmentAlreadyExists = 12,
+    EscrowIdMismatch = 13,
+    EscrowNotFound = 14,
+    EscrowAlreadyResolved = 15,
+    MemoTooLong = 16,
+    IndexFull = 17,
+    InvalidFeeBps = 18,
+    TooManyTemplates = 19,
+    TemplateNotFound = 20,
+    InvalidStateTransition = 21,
+    InsufficientInsurancePool = 22,
+    NotAllowedRecipient = 23,
+    InvalidRecipientList = 24,
+    TooManyWatchers = 25,
+    AlreadyWatching = 26,
+    EscrowFrozen = 27,
+    EscrowNotFrozen = 28,
+    DeadlineTooFar = 29,
+    EscrowNotExpired = 30,
+    SplitMismatch = 31,
+    HandleSaltNotSet = 32,
+    InvalidReportWindow = 33,
+    CoolingOffActive = 34,
+    SaltBlobTooLarge = 35,
+    SaltEscrowNotFound = 36,
+    RecoveryDelayActive = 37,
+    FeatureDisabled = 38,
+    InvalidExpiry = 39,
+    InvalidRate = 40,
+    AmountOverflow = 41,
+    Overfunded = 42,
+    InvalidBatch = 43,
+    NothingToWithdraw = 44,
+    YieldAdapterNotSet = 45,
+    TermsMismatch = 46,
+    DailyLimitExceeded = 47,
+    CannotReassignAfterAcceptance = 48,
+    TokenMismatch = 49,
+    EscrowNotDisputed = 50,
+    DisputeLogFull = 51,
+    InvalidClaimCode = 52,
+    TestModeForbidden = 53,
+    RefundAddressLocked = 54,
+    NotInitialized = 55,
+    CommitmentNotRegistered = 56,
+    TokenPaused = 57,
+    EscrowFlagged = 58,
+    TooManyFlags = 59,
+    SelfViewerNotAllowed = 60,
+    TooManyViewers = 61,
+    DestinationNotWhitelisted = 62,
+    AuctionNotFound = 63,
+    BiddingClosed = 64,
+    AuctionNotEnded = 65,
+    AuctionFinalized = 66,
+    NoteNotFound = 67,
+    AttestationAlreadyAttached = 68,
+    AttestationsMissing = 69,
+    TvlScanOutOfOrder = 70,
+    AmountBelowMinimum = 71,
+    PrivacyNotEnabled = 72,
+    IdempotencyConflict = 73,
+    ProofTooLong = 74,
+    ReceiverNotRegistered = 75,
+    DealNotFound = 76,
+    TooManyDealEscrows = 77,
+    EscrowAlreadyLinked = 78,
+    TooManyFunders = 79,
+    EscrowIdNotImportable = 80,
+    EscrowAlreadyExists = 81,
+    UnknownMigration = 82,
+    StorageVersionMismatch = 83,
+    EscrowAlreadyReleased = 84,
+    InvalidImportRecord = 85,
+    InvalidProofLen = 86,
 }
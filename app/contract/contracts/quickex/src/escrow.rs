@@ -0,0 +1,2288 @@
+use crate::activity;
+use crate::admin;
+use crate::circuit_breaker;
+use crate::dispute::{bond_recipient, require_resolver, return_bond, start_dispute};
+use crate::errors::QuickexError;
+use crate::events::{
+    publish_attestation_attached, publish_credit_withdrawn, publish_escrow_accepted,
+    publish_escrow_cancelled, publish_escrow_created, publish_escrow_deadline_approaching,
+    publish_escrow_expired, publish_escrow_flagged, publish_escrow_frozen, publish_escrow_funded,
+    publish_escrow_memo_updated, publish_escrow_refunded, publish_escrow_released,
+    publish_escrow_renewed, publish_escrow_split_released, publish_escrow_swept,
+    publish_funding_shortfall, publish_funds_credited, publish_recipient_reassigned,
+    publish_viewer_added, publish_watcher_added,
+};
+use crate::features::{self, Feature};
+use crate::ranking;
+use crate::receiver;
+use crate::reporting::{adjust_tvl, is_open, record_settlement, record_token};
+use crate::reputation;
+use crate::storage::{
+    get_admin, get_claimable_escrows, get_cooling_off, get_deadline_warning_window,
+    get_default_fee_bps, get_escrow_by_id, get_escrow_flags, get_escrow_handle, get_event_detail,
+    get_fee_timing, get_frozen_by, get_funders, get_handle_salt, get_handle_salt_version,
+    get_idempotency_key, get_keeper_incentive, get_max_escrow_lifetime, get_open_escrows,
+    get_pair_escrows, get_require_attestations, get_viewers, get_watchers, get_withdrawable,
+    has_withdraw_whitelist, increment_escrow_counter, is_fee_exempt, is_paused, is_receiver,
+    is_withdraw_destination, push_imported_id, put_escrow_by_id, set_claimable_escrows,
+    set_denomination, set_escrow_flags, set_escrow_handle, set_escrow_notify, set_frozen_by,
+    set_funders, set_handle_salt, set_handle_salt_version, set_idempotency_key, set_open_escrows,
+    set_pair_escrows, set_viewers, set_watchers, set_withdraw_destination, set_withdrawable,
+};
+use crate::types::{
+    Denomination, Escrow, EscrowBatchItem, EscrowStatus, FeeTiming, Resolution, SettlementBreakdown,
+};
+use crate::yield_adapter;
+use soroban_sdk::{symbol_short, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec};
+
+/// Maximum memo length in bytes
+pub const MAX_MEMO_LEN: u32 = 64;
+
+/// Escrow ids below this are reserved for `import_escrow`; created escrows
+/// are numbered from here up
+pub const RESERVED_ESCROW_IDS: u64 = 1_000_000;
+
+/// Most accounts that can contribute to one shared-funding escrow
+pub const MAX_FUNDERS: u32 = 16;
+
+/// Basis points denominator (100%)
+pub const MAX_BPS: u32 = 10_000;
+
+/// Largest keeper reward an admin can configure (1%)
+pub const MAX_KEEPER_REWARD_BPS: u32 = 100;
+
+/// Maximum length of a claim code after trimming
+pub const MAX_CLAIM_CODE_LEN: u32 = 32;
+
+/// Domain tag hashed in front of claim codes
+const CLAIM_CODE_DOMAIN: &[u8] = b"quickex:claim-code:";
+
+/// Maximum number of addresses that may claim a multi-recipient escrow
+pub const MAX_ALLOWED_RECIPIENTS: u32 = 20;
+
+/// Maximum number of watchers registered on a single escrow
+pub const MAX_WATCHERS: u32 = 8;
+
+/// Maximum number of viewers allowed on a single escrow
+pub const MAX_VIEWERS: u32 = 8;
+
+/// Maximum compliance flags on one escrow
+pub const MAX_ESCROW_FLAGS: u32 = 8;
+
+/// Maximum number of escrow ids kept in a single index bucket
+pub const MAX_INDEX_LEN: u32 = 200;
+
+/// Maximum number of escrows created in one batch
+pub const MAX_BATCH_LEN: u32 = 20;
+
+/// Maximum number of escrows read in one `get_escrows` call
+pub const MAX_LOOKUP_LEN: u32 = 100;
+
+/// Order-insensitive hash identifying the counterparty pair (a, b)
+pub fn pair_hash(env: &Env, a: &Address, b: &Address) -> BytesN<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut payload = Bytes::new(env);
+    payload.append(&first.clone().to_xdr(env));
+    payload.append(&second.clone().to_xdr(env));
+    env.crypto().sha256(&payload).into()
+}
+
+/// Return the `offset..offset + limit` window of an id list
+pub fn paginate(env: &Env, ids: &Vec<u64>, offset: u32, limit: u32) -> Vec<u64> {
+    let mut page = Vec::new(env);
+    let end = offset.saturating_add(limit).min(ids.len());
+    for i in offset..end {
+        page.push_back(ids.get_unchecked(i));
+    }
+    page
+}
+
+/// Check that an escrow may move from `current` to `next`
+///
+/// Pending escrows can be released, refunded, expired or disputed; disputed
+/// escrows can only be settled by a release or a refund. Every other move,
+/// including staying in the same state, is rejected; releasing twice fails
+/// with `EscrowAlreadyReleased` whichever path tries it.
+pub(crate) fn transition(current: EscrowStatus, next: EscrowStatus) -> Result<(), QuickexError> {
+    let allowed = matches!(
+        (current, next),
+        (
+            EscrowStatus::Pending,
+            EscrowStatus::Released
+                | EscrowStatus::Refunded
+                | EscrowStatus::Expired
+                | EscrowStatus::Disputed
+        ) | (
+            EscrowStatus::Disputed,
+            EscrowStatus::Released | EscrowStatus::Refunded
+        ) | (
+            EscrowStatus::Funding,
+            EscrowStatus::Pending | EscrowStatus::Refunded | EscrowStatus::Expired
+        )
+    );
+
+    match (current, next) {
+        _ if allowed => Ok(()),
+        (EscrowStatus::Released, EscrowStatus::Released) => {
+            Err(QuickexError::EscrowAlreadyReleased)
+        }
+        _ => Err(QuickexError::InvalidStateTransition),
+    }
+}
+
+/// Fee rate that applies to an escrow, in basis points
+///
+/// Escrows with a fee-exempt sender or recipient pay nothing; otherwise the
+/// escrow's own `fee_bps` applies, falling back to the contract default.
+pub fn effective_fee_bps(env: &Env, escrow: &Escrow) -> u32 {
+    if is_fee_exempt(env, &escrow.from) || is_fee_exempt(env, &escrow.to) {
+        return 0;
+    }
+    escrow
+        .fee_bps
+        .unwrap_or_else(|| fee_bps_for_amount(env, escrow.amount))
+}
+
+/// Fee rate, in basis points, for an escrow of `amount` without its own `fee_bps`
+///
+/// Every size pays the flat default for now; this is where amount tiers
+/// would be looked up.
+pub fn fee_bps_for_amount(env: &Env, _amount: i128) -> u32 {
+    get_default_fee_bps(env)
+}
+
+/// Protocol fee owed on an escrow at release
+///
+/// A fee already charged at creation stands, so a later change to the
+/// default or to exemptions can't make release pay out more than is held.
+pub fn escrow_fee(env: &Env, escrow: &Escrow) -> i128 {
+    if escrow.fee_paid > 0 {
+        return escrow.fee_paid;
+    }
+    escrow.amount * effective_fee_bps(env, escrow) as i128 / MAX_BPS as i128
+}
+
+/// Fee and recipient's net if an escrow were released now
+pub fn quote_release(env: &Env, escrow_id: u64) -> Result<(i128, i128), QuickexError> {
+    let escrow = find_escrow(env, escrow_id)?;
+    let fee = escrow_fee(env, &escrow);
+    Ok((fee, escrow.amount - fee))
+}
+
+/// Who receives what if an escrow settles with `outcome`
+///
+/// Settlement pays out from this same calculation, so a preview always
+/// matches the transfers that follow. `protocol_fee` includes any part
+/// charged at creation.
+pub(crate) fn settlement_breakdown(
+    env: &Env,
+    escrow: &Escrow,
+    outcome: &Resolution,
+) -> SettlementBreakdown {
+    let mut breakdown = SettlementBreakdown {
+        to_recipient: 0,
+        to_sender: 0,
+        protocol_fee: escrow.fee_paid,
+        keeper_reward: 0,
+        dispute_bond: 0,
+        bond_to: None,
+    };
+
+    // A split awarding the recipient nothing is a refund and takes no fee
+    let to_recipient_bps = match *outcome {
+        Resolution::Release => MAX_BPS,
+        Resolution::Refund | Resolution::Split(0) => {
+            breakdown.to_sender = held(escrow);
+            0
+        }
+        Resolution::Split(bps) => bps,
+        Resolution::Sweep => {
+            let (reward_bps, _) = get_keeper_incentive(env);
+            breakdown.keeper_reward = held(escrow) * reward_bps as i128 / MAX_BPS as i128;
+            breakdown.to_sender = held(escrow) - breakdown.keeper_reward;
+            return breakdown;
+        }
+    };
+
+    if to_recipient_bps > 0 {
+        let fee = escrow_fee(env, escrow);
+        let net = escrow.amount - fee;
+        breakdown.protocol_fee = fee;
+        breakdown.to_recipient = net * to_recipient_bps as i128 / MAX_BPS as i128;
+        breakdown.to_sender = net - breakdown.to_recipient;
+    }
+
+    breakdown.bond_to = bond_recipient(escrow, to_recipient_bps);
+    if breakdown.bond_to.is_some() {
+        breakdown.dispute_bond = escrow.dispute_bond;
+    }
+
+    breakdown
+}
+
+/// Preview the payouts if an open escrow settled with `outcome` now
+///
+/// Read-only; nothing is transferred or recorded.
+pub fn preview_settlement(
+    env: &Env,
+    escrow_id: u64,
+    outcome: Resolution,
+) -> Result<SettlementBreakdown, QuickexError> {
+    let escrow = find_escrow(env, escrow_id)?;
+    if !is_open(escrow.status) {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+    if let Resolution::Split(bps) = outcome {
+        admin::validate_bps(bps)?;
+    }
+
+    Ok(settlement_breakdown(env, &escrow, &outcome))
+}
+
+/// Address that receives refunds: the override if one is set, else the sender
+pub fn refund_destination(escrow: &Escrow) -> Address {
+    escrow.refund_to.clone().unwrap_or(escrow.from.clone())
+}
+
+/// Funds the contract holds for an escrow: what was deposited less any fee
+/// already charged at creation
+pub fn held(escrow: &Escrow) -> i128 {
+    escrow.funded - escrow.fee_paid
+}
+
+/// Charge the protocol fee on a newly active escrow when fees are taken on create
+///
+/// The charge is recorded on the escrow so release doesn't take it again and
+/// a refund returns only what is still held.
+fn charge_creation_fee(env: &Env, escrow: &mut Escrow) {
+    if get_fee_timing(env) != FeeTiming::OnCreate {
+        return;
+    }
+
+    let fee = escrow_fee(env, escrow);
+    crate::insurance::accrue_fee(env, &escrow.token, fee);
+    escrow.fee_paid = fee;
+}
+
+/// Move an escrow to `next`, refusing while it is frozen
+///
+/// All escrow mutators go through here rather than setting `status` directly,
+/// which also keeps both parties' activity feeds complete.
+pub(crate) fn advance(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    next: EscrowStatus,
+) -> Result<(), QuickexError> {
+    if escrow.frozen {
+        return Err(QuickexError::EscrowFrozen);
+    }
+    if next == EscrowStatus::Released {
+        check_attestations(env, escrow)?;
+    }
+    transition(escrow.status, next)?;
+    if is_open(escrow.status) && !is_open(next) {
+        adjust_tvl(env, &escrow.token, -held(escrow));
+        unindex_open(env, &escrow.from, escrow_id);
+    }
+    if escrow.status == EscrowStatus::Pending && next == EscrowStatus::Released {
+        reputation::record_completion(env, escrow);
+    }
+    escrow.status = next;
+    activity::record(env, escrow_id, escrow);
+    Ok(())
+}
+
+/// Opaque handle for an escrow id: sha256(id || contract salt)
+///
+/// Returns None until the contract salt has been set.
+pub fn derive_handle(env: &Env, escrow_id: u64) -> Option<BytesN<32>> {
+    let salt = get_handle_salt(env)?;
+
+    let mut payload = Bytes::from_slice(env, &escrow_id.to_be_bytes());
+    payload.append(&salt.into());
+    Some(env.crypto().sha256(&payload).into())
+}
+
+/// Resolve an opaque handle back to the escrow id it stands for
+///
+/// Handles from before a salt rotation keep resolving.
+pub fn resolve_handle(env: &Env, handle: &BytesN<32>) -> Result<u64, QuickexError> {
+    get_escrow_handle(env, handle)
+        .map(|(escrow_id, _)| escrow_id)
+        .ok_or(QuickexError::EscrowNotFound)
+}
+
+/// Replace the handle salt with fresh PRNG output (Admin only)
+///
+/// For when the salt may have leaked. Escrows created from now on get
+/// handles under the new salt; existing handles stay in the reverse index
+/// with the version they were made under, so they still resolve. Returns the
+/// new salt version.
+pub fn rotate_handle_salt(env: &Env, caller: Address) -> Result<u32, QuickexError> {
+    admin::require_admin(env, &caller)?;
+    if get_handle_salt(env).is_none() {
+        return Err(QuickexError::HandleSaltNotSet);
+    }
+
+    let salt = BytesN::from_array(env, &env.prng().gen::<[u8; 32]>());
+    let version = get_handle_salt_version(env) + 1;
+    set_handle_salt(env, &salt);
+    set_handle_salt_version(env, version);
+
+    Ok(version)
+}
+
+/// Refuse a non-admin release of an escrow that carries compliance flags
+pub(crate) fn check_flags(env: &Env, escrow_id: u64, caller: &Address) -> Result<(), QuickexError> {
+    if !get_escrow_flags(env, escrow_id).is_empty() && Some(caller.clone()) != get_admin(env) {
+        return Err(QuickexError::EscrowFlagged);
+    }
+    Ok(())
+}
+
+/// Refuse to fund or release an escrow that lacks either party's
+/// attestation while attestations are required
+///
+/// Checked on tranche deposits and on every move to `Released`. An escrow
+/// funded at creation can't be attested before it exists, so its funds are
+/// held until both parties attach one.
+fn check_attestations(env: &Env, escrow: &Escrow) -> Result<(), QuickexError> {
+    if get_require_attestations(env)
+        && (escrow.from_attestation.is_none() || escrow.to_attestation.is_none())
+    {
+        return Err(QuickexError::AttestationsMissing);
+    }
+    Ok(())
+}
+
+/// Refuse to release a large escrow before its cooling-off delay has passed
+pub(crate) fn check_cooling_off(env: &Env, escrow: &Escrow) -> Result<(), QuickexError> {
+    let (threshold, delay) = get_cooling_off(env);
+    if threshold > 0
+        && escrow.amount > threshold
+        && env.ledger().timestamp() < escrow.created_at.saturating_add(delay)
+    {
+        return Err(QuickexError::CoolingOffActive);
+    }
+    Ok(())
+}
+
+/// Transfer `amount` from `from` into the contract and return what arrived
+///
+/// Fee-on-transfer tokens deliver less than requested, so the contract's own
+/// balance is measured around the transfer and escrows book only what was
+/// actually received.
+fn pull_funds(env: &Env, token: &Address, from: &Address, amount: i128) -> i128 {
+    let token_client = token::Client::new(env, token);
+    let contract = env.current_contract_address();
+
+    let before = token_client.balance(&contract);
+    token_client.transfer(from, &contract, &amount);
+    token_client.balance(&contract) - before
+}
+
+fn index_pair(env: &Env, from: &Address, to: &Address, escrow_id: u64) {
+    let pair = pair_hash(env, from, to);
+    let mut ids = get_pair_escrows(env, &pair);
+    ids.push_back(escrow_id);
+    set_pair_escrows(env, &pair, &ids);
+}
+
+/// Drop an escrow id from the pair index it was filed under
+fn unindex_pair(env: &Env, from: &Address, to: &Address, escrow_id: u64) {
+    let pair = pair_hash(env, from, to);
+    let mut ids = get_pair_escrows(env, &pair);
+    if let Some(i) = ids.first_index_of(escrow_id) {
+        ids.remove(i);
+    }
+    set_pair_escrows(env, &pair, &ids);
+}
+
+/// Make `claimant` the recipient of a multi-recipient escrow, refiling it
+/// under the (sender, claimant) pair so `list_escrows_between` finds it
+fn assign_claimant(env: &Env, escrow_id: u64, escrow: &mut Escrow, claimant: &Address) {
+    unindex_pair(env, &escrow.from, &escrow.to, escrow_id);
+    index_pair(env, &escrow.from, claimant, escrow_id);
+    escrow.to = claimant.clone();
+}
+
+/// File an open escrow under its sender for `refund_all_mine`
+///
+/// Settled escrows drop out as they close, so the list only grows with
+/// escrows the sender is still funding.
+fn index_open(env: &Env, from: &Address, escrow_id: u64) {
+    let mut ids = get_open_escrows(env, from);
+    ids.push_back(escrow_id);
+    set_open_escrows(env, from, &ids);
+}
+
+/// Drop a settled escrow from its sender's open list
+fn unindex_open(env: &Env, from: &Address, escrow_id: u64) {
+    let mut ids = get_open_escrows(env, from);
+    if let Some(i) = ids.first_index_of(escrow_id) {
+        ids.remove(i);
+        set_open_escrows(env, from, &ids);
+    }
+}
+
+/// Copy of a private escrow as outsiders see it, with the amounts zeroed
+///
+/// Public escrows are returned unchanged. The decision uses the `private`
+/// flag captured at creation, not the parties' current settings.
+pub fn redacted(escrow: Escrow) -> Escrow {
+    if !escrow.private {
+        return escrow;
+    }
+
+    Escrow {
+        amount: 0,
+        funded: 0,
+        fee_paid: 0,
+        dispute_bond: 0,
+        ..escrow
+    }
+}
+
+/// Read an escrow as `viewer`, who sees private amounts only if they are a
+/// party or the arbiter
+pub fn view_escrow(env: &Env, escrow_id: u64, viewer: Address) -> Result<Escrow, QuickexError> {
+    let escrow = find_escrow(env, escrow_id)?;
+    viewer.require_auth();
+
+    if has_party_access(&escrow, &viewer) || get_viewers(env, escrow_id).contains(&viewer) {
+        Ok(escrow)
+    } else {
+        Ok(redacted(escrow))
+    }
+}
+
+/// Whether `account` sees the full terms by being on the escrow itself
+fn has_party_access(escrow: &Escrow, account: &Address) -> bool {
+    *account == escrow.from || *account == escrow.to || Some(account.clone()) == escrow.arbiter
+}
+
+/// Load an escrow for a read entry point
+///
+/// A missing record reports `FeatureDisabled` when the escrow module is off,
+/// so clients can tell "never existed" from "can't exist here".
+pub fn find_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, QuickexError> {
+    match get_escrow_by_id(env, escrow_id) {
+        Some(escrow) => Ok(escrow),
+        None if !features::is_enabled(env, Feature::Escrow) => Err(QuickexError::FeatureDisabled),
+        None => Err(QuickexError::EscrowNotFound),
+    }
+}
+
+/// Load several escrows at once, in the order asked, for detail views
+///
+/// Unknown ids come back as `None` and private escrows are redacted as in
+/// `get_escrow`.
+pub fn get_escrows(env: &Env, ids: Vec<u64>) -> Result<Vec<Option<Escrow>>, QuickexError> {
+    if ids.len() > MAX_LOOKUP_LEN {
+        return Err(QuickexError::InvalidBatch);
+    }
+
+    let mut escrows = Vec::new(env);
+    for escrow_id in ids.iter() {
+        escrows.push_back(get_escrow_by_id(env, escrow_id).map(redacted));
+    }
+    Ok(escrows)
+}
+
+/// Load the escrow behind an opaque handle
+pub fn find_escrow_by_handle(env: &Env, handle: &BytesN<32>) -> Result<Escrow, QuickexError> {
+    find_escrow(env, resolve_handle(env, handle)?)
+}
+
+/// Build a pending escrow record without any optional terms
+pub fn new_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+) -> Escrow {
+    Escrow {
+        from,
+        to,
+        token,
+        amount,
+        status: EscrowStatus::Pending,
+        memo,
+        created_at: env.ledger().timestamp(),
+        private: false,
+        arbiter: None,
+        deadline: None,
+        fee_bps: None,
+        dispute_opener: None,
+        dispute_bond: 0,
+        split_bps: None,
+        allowed: Vec::new(env),
+        watcher_count: 0,
+        frozen: false,
+        funded: amount,
+        pull_payment: false,
+        yield_adapter: None,
+        fee_paid: 0,
+        accepted: false,
+        claim_code: None,
+        refund_to: None,
+        interval: None,
+        refund_to_internal: false,
+        deadline_warned: false,
+        from_attestation: None,
+        to_attestation: None,
+        emit_event: true,
+    }
+}
+
+/// Check that `escrow` could be opened now, without writing anything
+///
+/// Returns the deadline the escrow would be stored with, which is capped by
+/// the maximum lifetime when one is configured.
+pub fn validate_escrow(env: &Env, escrow: &Escrow) -> Result<Option<u64>, QuickexError> {
+    features::require_enabled(env, Feature::Escrow)?;
+
+    if escrow.amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    admin::require_min_amount(env, &escrow.token, escrow.amount)?;
+
+    if escrow.memo.len() > MAX_MEMO_LEN {
+        return Err(QuickexError::MemoTooLong);
+    }
+
+    if escrow.fee_bps.is_some_and(|bps| bps > MAX_BPS) {
+        return Err(QuickexError::InvalidFeeBps);
+    }
+
+    let mut deadline = escrow.deadline;
+    let max_lifetime = get_max_escrow_lifetime(env);
+    if max_lifetime > 0 {
+        let latest = escrow.created_at.saturating_add(max_lifetime);
+        match deadline {
+            Some(d) if d > latest => return Err(QuickexError::DeadlineTooFar),
+            Some(_) => {}
+            None => deadline = Some(latest),
+        }
+    }
+
+    if is_paused(env) {
+        return Err(QuickexError::ContractPaused);
+    }
+    admin::require_token_not_paused(env, &escrow.token)?;
+
+    let pair = pair_hash(env, &escrow.from, &escrow.to);
+    if get_pair_escrows(env, &pair).len() >= MAX_INDEX_LEN {
+        return Err(QuickexError::IndexFull);
+    }
+
+    Ok(deadline)
+}
+
+/// Validate, fund and store a new escrow, returning its id
+///
+/// Every creation path goes through here so validation, funding, indexing
+/// and events stay consistent.
+pub fn open_escrow(env: &Env, mut escrow: Escrow) -> Result<u64, QuickexError> {
+    escrow.deadline = validate_escrow(env, &escrow)?;
+
+    escrow.from.require_auth();
+
+    Ok(store_new_escrow(env, escrow))
+}
+
+/// Fund, index and store an escrow that has passed `validate_escrow`
+///
+/// The sender's auth must already have been required.
+fn store_new_escrow(env: &Env, mut escrow: Escrow) -> u64 {
+    let requested = escrow.funded;
+    if requested > 0 {
+        let received = pull_funds(env, &escrow.token, &escrow.from, requested);
+        escrow.amount = received;
+        escrow.funded = received;
+    }
+
+    store_funded_escrow(env, escrow, requested)
+}
+
+/// Index and store an escrow whose funds are already in the contract
+fn store_funded_escrow(env: &Env, mut escrow: Escrow, requested: i128) -> u64 {
+    let escrow_id = increment_escrow_counter(env);
+    index_pair(env, &escrow.from, &escrow.to, escrow_id);
+    if is_open(escrow.status) {
+        index_open(env, &escrow.from, escrow_id);
+    }
+    record_token(env, &escrow.token);
+    activity::record(env, escrow_id, &escrow);
+
+    if escrow.funded != requested {
+        publish_funding_shortfall(env, escrow_id, requested, escrow.funded);
+    }
+
+    if escrow.status == EscrowStatus::Pending {
+        charge_creation_fee(env, &mut escrow);
+        yield_adapter::park(env, &mut escrow);
+        ranking::record(env, &escrow.token, escrow_id, escrow.amount);
+    }
+
+    adjust_tvl(env, &escrow.token, held(&escrow));
+
+    escrow.private = crate::privacy::hides_amounts(env, &escrow.from, &escrow.token);
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    if let Some(handle) = derive_handle(env, escrow_id) {
+        set_escrow_handle(env, &handle, escrow_id, get_handle_salt_version(env));
+    }
+
+    if escrow.emit_event {
+        publish_escrow_created(env, escrow_id, &escrow, get_event_detail(env, &escrow.to));
+    }
+
+    escrow_id
+}
+
+/// Address of the Stellar Asset Contract wrapping native XLM on this network
+///
+/// Native XLM is escrowed like any other token by passing this address. The
+/// address is derived from the network id, so it is valid whether or not the
+/// contract has been deployed yet.
+pub fn native_token(env: &Env) -> Address {
+    // XDR encoding of `Asset::Native`
+    let native = Bytes::from_array(env, &[0; 4]);
+    env.deployer().with_stellar_asset(native).deployed_address()
+}
+
+pub fn create_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+) -> Result<u64, QuickexError> {
+    open_escrow(env, new_escrow(env, from, to, token, amount, memo))
+}
+
+/// Check a batch of escrows from one sender without writing anything
+///
+/// Runs the same checks as `open_escrow` on every item, so a batch that
+/// passes here is accepted by `create_escrow_batch` in the same ledger state.
+pub fn validate_escrow_batch(
+    env: &Env,
+    from: Address,
+    items: Vec<EscrowBatchItem>,
+) -> Result<(), QuickexError> {
+    if items.is_empty() || items.len() > MAX_BATCH_LEN {
+        return Err(QuickexError::InvalidBatch);
+    }
+
+    for item in items.iter() {
+        validate_escrow(env, &batch_escrow(env, &from, item))?;
+    }
+
+    Ok(())
+}
+
+/// Open several escrows from one sender, all or nothing
+pub fn create_escrow_batch(
+    env: &Env,
+    from: Address,
+    items: Vec<EscrowBatchItem>,
+) -> Result<Vec<u64>, QuickexError> {
+    validate_escrow_batch(env, from.clone(), items.clone())?;
+
+    from.require_auth();
+
+    let mut ids = Vec::new(env);
+    for item in items.iter() {
+        let mut escrow = batch_escrow(env, &from, item);
+        // Re-run per item: earlier items in the batch fill the same indexes
+        escrow.deadline = validate_escrow(env, &escrow)?;
+        ids.push_back(store_new_escrow(env, escrow));
+    }
+
+    Ok(ids)
+}
+
+fn batch_escrow(env: &Env, from: &Address, item: EscrowBatchItem) -> Escrow {
+    new_escrow(
+        env,
+        from.clone(),
+        item.to,
+        item.token,
+        item.amount,
+        item.memo,
+    )
+}
+
+/// Open an escrow whose release credits the recipient instead of pushing funds
+///
+/// The recipient collects credited funds with `withdraw_credit`.
+pub fn create_pull_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+) -> Result<u64, QuickexError> {
+    let mut escrow = new_escrow(env, from, to, token, amount, memo);
+    escrow.pull_payment = true;
+    open_escrow(env, escrow)
+}
+
+/// Normalise a human-entered claim code and hash it into a lock
+///
+/// Surrounding ASCII whitespace is trimmed and letters are upper-cased, so
+/// " abc-123 " and "ABC-123" open the same lock. The hash is domain
+/// separated so a code lock never collides with a hash of binary data.
+pub fn hash_claim_code(env: &Env, code: &Bytes) -> Result<BytesN<32>, QuickexError> {
+    let is_space = |b: u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+    let start = code.iter().position(|b| !is_space(b));
+    let end = code.iter().rposition(|b| !is_space(b));
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(QuickexError::InvalidClaimCode);
+    };
+    if end - start >= MAX_CLAIM_CODE_LEN as usize {
+        return Err(QuickexError::InvalidClaimCode);
+    }
+
+    let mut payload = Bytes::from_slice(env, CLAIM_CODE_DOMAIN);
+    for b in code.slice(start as u32..end as u32 + 1).iter() {
+        payload.push_back(b.to_ascii_uppercase());
+    }
+    Ok(env.crypto().sha256(&payload).into())
+}
+
+/// Open an escrow the recipient claims by entering a short code
+///
+/// `code_hash` comes from `hash_claim_code`; the sender passes the code to
+/// the recipient out of band.
+pub fn create_code_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    code_hash: BytesN<32>,
+) -> Result<u64, QuickexError> {
+    let mut escrow = new_escrow(env, from, to, token, amount, Bytes::new(env));
+    escrow.claim_code = Some(code_hash);
+    open_escrow(env, escrow)
+}
+
+/// Pay a code escrow out to its recipient when they enter the right code
+pub fn claim_with_code(
+    env: &Env,
+    claimer: Address,
+    escrow_id: u64,
+    code: Bytes,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if claimer != escrow.to {
+        return Err(QuickexError::Unauthorized);
+    }
+    claimer.require_auth();
+    check_flags(env, escrow_id, &claimer)?;
+
+    if escrow.claim_code != Some(hash_claim_code(env, &code)?) {
+        return Err(QuickexError::InvalidClaimCode);
+    }
+
+    check_cooling_off(env, &escrow)?;
+    circuit_breaker::consume(env, escrow.amount)?;
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Released)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    pay_recipient(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Open an escrow priced in a reference unit, locking `ref_amount * rate` token units
+///
+/// The reference amount and rate are kept on the escrow so UIs can show the
+/// amount the way the sender entered it; settlement only uses the token amount.
+pub fn create_escrow_denominated(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    ref_amount: i128,
+    rate: i128,
+) -> Result<u64, QuickexError> {
+    if ref_amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    if rate <= 0 {
+        return Err(QuickexError::InvalidRate);
+    }
+
+    let amount = ref_amount
+        .checked_mul(rate)
+        .ok_or(QuickexError::AmountOverflow)?;
+
+    let escrow_id = open_escrow(
+        env,
+        new_escrow(env, from, to, token, amount, Bytes::new(env)),
+    )?;
+    set_denomination(env, escrow_id, &Denomination { ref_amount, rate });
+
+    Ok(escrow_id)
+}
+
+/// Open an escrow whose refunds go to `refund_to` instead of the sender
+pub fn create_escrow_with_refund_to(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+    refund_to: Address,
+) -> Result<u64, QuickexError> {
+    let mut escrow = new_escrow(env, from, to, token, amount, memo);
+    escrow.refund_to = Some(refund_to);
+    open_escrow(env, escrow)
+}
+
+/// Open an escrow, optionally without announcing it
+///
+/// With `emit_event` false no `EscrowCreated` event is published and the
+/// creation is left out of the event log, so indexers won't learn of the
+/// escrow until it settles. The token transfer into the contract is still
+/// visible.
+pub fn create_escrow_with_event(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+    emit_event: bool,
+) -> Result<u64, QuickexError> {
+    let mut escrow = new_escrow(env, from, to, token, amount, memo);
+    escrow.emit_event = emit_event;
+    open_escrow(env, escrow)
+}
+
+/// Ledgers an idempotency key is remembered for (about one day)
+pub const IDEMPOTENCY_TTL_LEDGERS: u32 = 17_280;
+
+/// Create an escrow at most once per sender and `idempotency_key`
+///
+/// A wallet retrying a submission that timed out gets back the escrow the
+/// first attempt created, as long as it sends the same parameters; reusing
+/// the key for anything else fails with `IdempotencyConflict`. Keys live in
+/// temporary storage and are forgotten after `IDEMPOTENCY_TTL_LEDGERS`.
+pub fn create_escrow_idempotent(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    memo: Bytes,
+    idempotency_key: BytesN<32>,
+) -> Result<u64, QuickexError> {
+    let mut payload = to.clone().to_xdr(env);
+    payload.append(&token.clone().to_xdr(env));
+    payload.append(&Bytes::from_slice(env, &amount.to_be_bytes()));
+    payload.append(&memo);
+    let params: BytesN<32> = env.crypto().sha256(&payload).into();
+
+    if let Some((escrow_id, stored)) = get_idempotency_key(env, &from, &idempotency_key) {
+        if stored != params {
+            return Err(QuickexError::IdempotencyConflict);
+        }
+        return Ok(escrow_id);
+    }
+
+    let escrow_id = open_escrow(env, new_escrow(env, from.clone(), to, token, amount, memo))?;
+    set_idempotency_key(
+        env,
+        &from,
+        &idempotency_key,
+        &(escrow_id, params),
+        IDEMPOTENCY_TTL_LEDGERS,
+    );
+
+    Ok(escrow_id)
+}
+
+/// Change or clear where an open escrow's refunds go
+///
+/// Only the sender may do this, and only before the escrow is disputed or
+/// settled, so a refund can't be redirected mid-dispute.
+pub fn set_refund_address(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+    refund_to: Option<Address>,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if caller != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    match escrow.status {
+        EscrowStatus::Pending | EscrowStatus::Funding => {}
+        EscrowStatus::Disputed => return Err(QuickexError::RefundAddressLocked),
+        _ => return Err(QuickexError::EscrowAlreadyResolved),
+    }
+
+    escrow.refund_to = refund_to;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Choose whether an open escrow's refunds are credited to the withdrawable
+/// balance instead of transferred
+///
+/// Same rules as `set_refund_address`: sender only, before any dispute.
+pub fn set_refund_to_internal(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+    enabled: bool,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if caller != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    match escrow.status {
+        EscrowStatus::Pending | EscrowStatus::Funding => {}
+        EscrowStatus::Disputed => return Err(QuickexError::RefundAddressLocked),
+        _ => return Err(QuickexError::EscrowAlreadyResolved),
+    }
+
+    escrow.refund_to_internal = enabled;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Open an escrow for `target` that the sender funds in tranches
+///
+/// Nothing is transferred yet; the escrow stays in `Funding` until
+/// `fund_escrow_partial` brings it up to the target.
+pub fn create_tranche_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    target: i128,
+    memo: Bytes,
+) -> Result<u64, QuickexError> {
+    let mut escrow = new_escrow(env, from, to, token, target, memo);
+    escrow.status = EscrowStatus::Funding;
+    escrow.funded = 0;
+    open_escrow(env, escrow)
+}
+
+/// Deposit a tranche into an escrow that is still being funded
+///
+/// The escrow becomes `Pending`, and releasable as usual, once the deposits
+/// reach its target. Returns the amount funded so far, counting only what
+/// actually arrived.
+pub fn fund_escrow_partial(
+    env: &Env,
+    from: Address,
+    escrow_id: u64,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if from != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+
+    let (funded, _) = deposit_tranche(env, from, escrow_id, escrow, amount)?;
+    Ok(funded)
+}
+
+/// Contribute toward an escrow that is still being funded, as one of
+/// several funders
+///
+/// Anyone may contribute; each funder's total is tracked so a refund gives
+/// it back to them, and release pays the recipient the whole target. At most
+/// `MAX_FUNDERS` accounts can contribute. Returns the amount funded so far.
+pub fn fund_escrow_shared(
+    env: &Env,
+    escrow_id: u64,
+    funder: Address,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let mut funders = get_funders(env, escrow_id);
+    let contributed = funders.get(funder.clone()).unwrap_or(0);
+    if contributed == 0 && funders.len() >= MAX_FUNDERS {
+        return Err(QuickexError::TooManyFunders);
+    }
+
+    let (funded, received) = deposit_tranche(env, funder.clone(), escrow_id, escrow, amount)?;
+    funders.set(funder, contributed + received);
+    set_funders(env, escrow_id, &funders);
+
+    Ok(funded)
+}
+
+/// Pull one tranche from `funder` into a `Funding` escrow, returning the
+/// total funded and what this tranche actually delivered
+fn deposit_tranche(
+    env: &Env,
+    funder: Address,
+    escrow_id: u64,
+    mut escrow: Escrow,
+    amount: i128,
+) -> Result<(i128, i128), QuickexError> {
+    if escrow.status != EscrowStatus::Funding {
+        return Err(QuickexError::InvalidStateTransition);
+    }
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    let remaining = escrow.amount - escrow.funded;
+    if amount > remaining {
+        return Err(QuickexError::Overfunded);
+    }
+    // The last deposit may be whatever is left, however small
+    if amount < remaining {
+        admin::require_min_amount(env, &escrow.token, amount)?;
+    }
+
+    if is_paused(env) {
+        return Err(QuickexError::ContractPaused);
+    }
+    admin::require_token_not_paused(env, &escrow.token)?;
+    check_attestations(env, &escrow)?;
+
+    funder.require_auth();
+
+    let held_before = held(&escrow);
+    let received = pull_funds(env, &escrow.token, &funder, amount);
+    if received != amount {
+        publish_funding_shortfall(env, escrow_id, amount, received);
+    }
+
+    escrow.funded += received;
+    if amount == remaining {
+        // A fee-on-transfer token may have delivered less; book what arrived
+        escrow.amount = escrow.funded;
+        advance(env, escrow_id, &mut escrow, EscrowStatus::Pending)?;
+        charge_creation_fee(env, &mut escrow);
+        yield_adapter::park(env, &mut escrow);
+        ranking::record(env, &escrow.token, escrow_id, escrow.amount);
+    }
+    adjust_tvl(env, &escrow.token, held(&escrow) - held_before);
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_escrow_funded(env, escrow_id, received, escrow.funded);
+
+    Ok((escrow.funded, received))
+}
+
+/// Lock funds that the first of several allowed recipients can claim
+///
+/// Until claimed the escrow's `to` is the sender itself.
+pub fn create_multi_recipient_escrow(
+    env: &Env,
+    from: Address,
+    token: Address,
+    amount: i128,
+    allowed: Vec<Address>,
+) -> Result<u64, QuickexError> {
+    if allowed.is_empty() || allowed.len() > MAX_ALLOWED_RECIPIENTS {
+        return Err(QuickexError::InvalidRecipientList);
+    }
+    // No recipient is known until someone claims, so none can attest
+    if get_require_attestations(env) {
+        return Err(QuickexError::AttestationsMissing);
+    }
+
+    let mut escrow = new_escrow(env, from.clone(), from, token, amount, Bytes::new(env));
+    escrow.allowed = allowed.clone();
+    let escrow_id = open_escrow(env, escrow)?;
+
+    for claimant in allowed.iter() {
+        index_claimable(env, &claimant, escrow_id);
+    }
+
+    Ok(escrow_id)
+}
+
+/// File a multi-recipient escrow under a claimant for `claim_all`
+///
+/// A full index is left as is; the claimant can still claim the escrow
+/// directly, and `claim_all` frees slots as it prunes settled ids.
+fn index_claimable(env: &Env, claimant: &Address, escrow_id: u64) {
+    let mut ids = get_claimable_escrows(env, claimant);
+    if ids.contains(escrow_id) || ids.len() >= MAX_INDEX_LEN {
+        return;
+    }
+    ids.push_back(escrow_id);
+    set_claimable_escrows(env, claimant, &ids);
+}
+
+/// Recreate an escrow from a legacy system under its original id (Admin only)
+///
+/// Only ids below `RESERVED_ESCROW_IDS`, which the counter never assigns,
+/// can be imported, and never over an existing record. The admin pays in
+/// what an open escrow holds, so every import is backed by funds; from then
+/// on it is indexed and settles like a created escrow.
+pub fn import_escrow(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    mut escrow: Escrow,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    if escrow_id == 0 || escrow_id >= RESERVED_ESCROW_IDS {
+        return Err(QuickexError::EscrowIdNotImportable);
+    }
+    if get_escrow_by_id(env, escrow_id).is_some() {
+        return Err(QuickexError::EscrowAlreadyExists);
+    }
+    escrow.deadline = validate_escrow(env, &escrow)?;
+    check_import_record(&escrow)?;
+    // Watchers live under their own keys and aren't carried over
+    escrow.watcher_count = 0;
+
+    if is_open(escrow.status) && held(&escrow) > 0 {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(&caller, env.current_contract_address(), &held(&escrow));
+        adjust_tvl(env, &escrow.token, held(&escrow));
+    }
+
+    index_pair(env, &escrow.from, &escrow.to, escrow_id);
+    if is_open(escrow.status) {
+        index_open(env, &escrow.from, escrow_id);
+    }
+    record_token(env, &escrow.token);
+    activity::record(env, escrow_id, &escrow);
+    if escrow.status == EscrowStatus::Pending {
+        ranking::record(env, &escrow.token, escrow_id, escrow.amount);
+        for claimant in escrow.allowed.iter() {
+            index_claimable(env, &claimant, escrow_id);
+        }
+    }
+
+    put_escrow_by_id(env, escrow_id, &escrow);
+    if let Some(handle) = derive_handle(env, escrow_id) {
+        set_escrow_handle(env, &handle, escrow_id, get_handle_salt_version(env));
+    }
+    push_imported_id(env, escrow_id);
+
+    Ok(())
+}
+
+/// Refuse an imported record that refers to funds or state kept outside it
+///
+/// The import only pays in `held(escrow)`, so a record may not claim a
+/// dispute bond, parked principal or a fee taken elsewhere, nor a dispute
+/// or freeze whose details were never recorded here.
+fn check_import_record(escrow: &Escrow) -> Result<(), QuickexError> {
+    let funds_outside = escrow.dispute_bond != 0
+        || escrow.yield_adapter.is_some()
+        || escrow.fee_paid != 0
+        || escrow.funded < 0
+        || escrow.funded > escrow.amount;
+    let state_outside = escrow.dispute_opener.is_some()
+        || escrow.frozen
+        || matches!(escrow.status, EscrowStatus::Disputed | EscrowStatus::Frozen);
+
+    if funds_outside || state_outside {
+        return Err(QuickexError::InvalidImportRecord);
+    }
+    Ok(())
+}
+
+/// Pay a multi-recipient escrow out to the first allowed claimant
+pub fn claim_escrow(env: &Env, claimant: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if !escrow.allowed.contains(&claimant) {
+        return Err(QuickexError::NotAllowedRecipient);
+    }
+    claimant.require_auth();
+
+    check_flags(env, escrow_id, &claimant)?;
+    check_cooling_off(env, &escrow)?;
+    circuit_breaker::consume(env, escrow.amount)?;
+    assign_claimant(env, escrow_id, &mut escrow, &claimant);
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Released)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    pay_recipient(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Settle every escrow in `token` that `recipient` can release without
+/// further approval, up to `limit`, and return the settled ids
+///
+/// Walks the recipient's claimable index. The escrows a recipient can settle
+/// alone are the multi-recipient ones that allow them; a single-recipient
+/// escrow always needs its sender or the admin to release it, so it counts
+/// as needing sender action and is never indexed here. Escrows that can't be
+/// claimed right now (frozen, flagged, cooling off or over the daily cap)
+/// are skipped and stay indexed for a later call. The payouts are summed
+/// into one transfer instead of one per escrow.
+pub fn claim_all(
+    env: &Env,
+    recipient: Address,
+    token: Address,
+    limit: u32,
+) -> Result<Vec<u64>, QuickexError> {
+    recipient.require_auth();
+
+    let mut settled = Vec::new(env);
+    let mut remaining = Vec::new(env);
+    let mut total: i128 = 0;
+
+    for escrow_id in get_claimable_escrows(env, &recipient).iter() {
+        let Some(mut escrow) = get_escrow_by_id(env, escrow_id) else {
+            continue;
+        };
+        // Claimed by someone else or otherwise resolved; drop it from the index
+        if escrow.status != EscrowStatus::Pending || !escrow.allowed.contains(&recipient) {
+            continue;
+        }
+
+        if settled.len() >= limit
+            || escrow.token != token
+            || escrow.frozen
+            || check_flags(env, escrow_id, &recipient).is_err()
+            || check_cooling_off(env, &escrow).is_err()
+            || check_attestations(env, &escrow).is_err()
+            || circuit_breaker::consume(env, escrow.amount).is_err()
+        {
+            remaining.push_back(escrow_id);
+            continue;
+        }
+
+        assign_claimant(env, escrow_id, &mut escrow, &recipient);
+        advance(env, escrow_id, &mut escrow, EscrowStatus::Released)?;
+        put_escrow_by_id(env, escrow_id, &escrow);
+
+        let net = settle_release(env, escrow_id, &escrow);
+        if escrow.pull_payment {
+            pay_to_recipient(env, escrow_id, &escrow, net);
+        } else {
+            total += net;
+        }
+        publish_escrow_released(env, escrow_id, &escrow, get_event_detail(env, &escrow.to));
+
+        settled.push_back(escrow_id);
+    }
+
+    set_claimable_escrows(env, &recipient, &remaining);
+
+    if total > 0 {
+        let token_client = token::Client::new(env, &token);
+        token_client.transfer(&env.current_contract_address(), &recipient, &total);
+    }
+    for escrow_id in settled.iter() {
+        receiver::notify_escrow_event(env, escrow_id, EscrowStatus::Released);
+    }
+
+    Ok(settled)
+}
+
+pub fn release_escrow(env: &Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    // Multi-recipient escrows have no recipient until one claims
+    if !escrow.allowed.is_empty() {
+        return Err(QuickexError::NotAllowedRecipient);
+    }
+
+    if caller != escrow.from && Some(caller.clone()) != get_admin(env) {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    check_flags(env, escrow_id, &caller)?;
+    check_cooling_off(env, &escrow)?;
+    circuit_breaker::consume(env, escrow.amount)?;
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Released)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    pay_recipient(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Open an escrow that opens the next period's escrow each time it is released
+///
+/// The first period is funded from the sender's wallet; each successor is
+/// paid from the sender's withdrawable credit (see `deposit_credit`) and
+/// renewal stops once that credit can't cover another period.
+pub fn create_recurring_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token: Address,
+    amount: i128,
+    interval: u64,
+) -> Result<u64, QuickexError> {
+    if interval == 0 {
+        return Err(QuickexError::InvalidExpiry);
+    }
+
+    let mut escrow = new_escrow(env, from, to, token, amount, Bytes::new(env));
+    escrow.interval = Some(interval);
+    open_escrow(env, escrow)
+}
+
+/// Stop a recurring escrow from renewing when it is released
+pub fn cancel_recurring(env: &Env, from: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if from != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    from.require_auth();
+
+    if escrow.status != EscrowStatus::Pending {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    escrow.interval = None;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Open the next period of a released recurring escrow if the sender's credit covers it
+///
+/// Runs on every release, whether paid in full, split or ruled on in a
+/// dispute; a refund ends the subscription. Anything that would stop the
+/// successor being created (too little credit, a pause, a full pair index)
+/// ends it too instead of failing the release.
+pub(crate) fn renew(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let Some(interval) = escrow.interval else {
+        return;
+    };
+    if escrow.status != EscrowStatus::Released {
+        return;
+    }
+
+    let credit = get_withdrawable(env, &escrow.token, &escrow.from);
+    if credit < escrow.amount {
+        return;
+    }
+
+    let mut next = new_escrow(
+        env,
+        escrow.from.clone(),
+        escrow.to.clone(),
+        escrow.token.clone(),
+        escrow.amount,
+        escrow.memo.clone(),
+    );
+    next.interval = Some(interval);
+    next.refund_to = escrow.refund_to.clone();
+    let Ok(deadline) = validate_escrow(env, &next) else {
+        return;
+    };
+    next.deadline = deadline;
+
+    set_withdrawable(env, &escrow.token, &escrow.from, credit - escrow.amount);
+    let requested = next.funded;
+    let next_id = store_funded_escrow(env, next, requested);
+
+    publish_escrow_renewed(env, escrow_id, next_id);
+}
+
+/// Settle an escrow with a negotiated split between recipient and sender
+///
+/// Needs both parties' auth, or the admin's alone. The amounts must add up
+/// to what the escrow holds; the protocol fee comes out of the recipient's
+/// share. A flagged escrow only pays its recipient when the admin splits it,
+/// and the admin can't split a disputed escrow that has its own arbiter.
+pub fn release_split_amounts(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    to_recipient: i128,
+    to_sender: i128,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if Some(caller.clone()) == get_admin(env) {
+        if escrow.status == EscrowStatus::Disputed {
+            require_resolver(env, &escrow, &caller)?;
+        }
+        caller.require_auth();
+    } else if caller == escrow.from || caller == escrow.to {
+        escrow.from.require_auth();
+        escrow.to.require_auth();
+    } else {
+        return Err(QuickexError::Unauthorized);
+    }
+
+    if to_recipient < 0 || to_sender < 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    if to_recipient.checked_add(to_sender) != Some(held(&escrow)) {
+        return Err(QuickexError::SplitMismatch);
+    }
+    if to_recipient > 0 {
+        check_flags(env, escrow_id, &caller)?;
+        check_cooling_off(env, &escrow)?;
+        circuit_breaker::consume(env, to_recipient)?;
+    }
+
+    let next = if to_recipient > 0 {
+        EscrowStatus::Released
+    } else {
+        EscrowStatus::Refunded
+    };
+    advance(env, escrow_id, &mut escrow, next)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    yield_adapter::recall(env, escrow_id, &escrow);
+    record_settlement(env, &escrow.token, escrow.amount);
+    ranking::remove(env, &escrow.token, escrow_id);
+
+    // A fee already charged at creation is not taken again
+    let fee = match escrow.fee_paid {
+        0 => to_recipient * effective_fee_bps(env, &escrow) as i128 / MAX_BPS as i128,
+        _ => 0,
+    };
+    crate::insurance::accrue_fee(env, &escrow.token, fee);
+
+    pay_to_recipient(env, escrow_id, &escrow, to_recipient - fee);
+    return_funds(env, escrow_id, &escrow, to_sender, false);
+
+    return_bond(env, &escrow);
+
+    publish_escrow_split_released(env, escrow_id, to_recipient - fee, to_sender, fee);
+    receiver::notify_escrow_event(env, escrow_id, escrow.status);
+    renew(env, escrow_id, &escrow);
+
+    Ok(())
+}
+
+/// Pay a released escrow out to its recipient, net of the protocol fee
+pub(crate) fn pay_recipient(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let net = settle_release(env, escrow_id, escrow);
+    pay_to_recipient(env, escrow_id, escrow, net);
+
+    publish_escrow_released(env, escrow_id, escrow, get_event_detail(env, &escrow.to));
+    receiver::notify_escrow_event(env, escrow_id, escrow.status);
+    renew(env, escrow_id, escrow);
+}
+
+/// Book a released escrow's settlement and fee, returning what the
+/// recipient is owed
+fn settle_release(env: &Env, escrow_id: u64, escrow: &Escrow) -> i128 {
+    yield_adapter::recall(env, escrow_id, escrow);
+    record_settlement(env, &escrow.token, escrow.amount);
+    ranking::remove(env, &escrow.token, escrow_id);
+
+    let breakdown = settlement_breakdown(env, escrow, &Resolution::Release);
+    crate::insurance::accrue_fee(env, &escrow.token, breakdown.protocol_fee - escrow.fee_paid);
+
+    breakdown.to_recipient
+}
+
+/// Send `amount` of a settled escrow to its recipient
+///
+/// Pull-payment escrows only credit the recipient's withdrawable balance,
+/// so settlement never depends on the recipient's token account.
+pub(crate) fn pay_to_recipient(env: &Env, escrow_id: u64, escrow: &Escrow, amount: i128) {
+    if amount <= 0 {
+        return;
+    }
+
+    if escrow.pull_payment {
+        let balance = get_withdrawable(env, &escrow.token, &escrow.to) + amount;
+        set_withdrawable(env, &escrow.token, &escrow.to, balance);
+        publish_funds_credited(env, escrow_id, escrow.to.clone(), amount);
+    } else {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(&env.current_contract_address(), &escrow.to, &amount);
+    }
+}
+
+/// Add to an account's withdrawable credit in a token, e.g. to prepay recurring escrows
+///
+/// Returns the new balance. The credit can be taken back with `withdraw_credit`.
+pub fn deposit_credit(
+    env: &Env,
+    owner: Address,
+    token: Address,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+    if is_paused(env) {
+        return Err(QuickexError::ContractPaused);
+    }
+    admin::require_token_not_paused(env, &token)?;
+
+    owner.require_auth();
+
+    let received = pull_funds(env, &token, &owner, amount);
+    let balance = get_withdrawable(env, &token, &owner) + received;
+    set_withdrawable(env, &token, &owner, balance);
+
+    Ok(balance)
+}
+
+/// Pay out everything credited to `recipient` in `token` by pull-payment escrows
+pub fn withdraw_credit(
+    env: &Env,
+    recipient: Address,
+    token: Address,
+) -> Result<i128, QuickexError> {
+    withdraw_credit_to(env, recipient.clone(), token, recipient)
+}
+
+/// Pay out everything credited to `recipient` in `token` to `dest`
+///
+/// Once the recipient has opted into a withdrawal whitelist, `dest` must be
+/// the recipient itself or a destination they approved.
+pub fn withdraw_credit_to(
+    env: &Env,
+    recipient: Address,
+    token: Address,
+    dest: Address,
+) -> Result<i128, QuickexError> {
+    recipient.require_auth();
+
+    if dest != recipient
+        && has_withdraw_whitelist(env, &recipient)
+        && !is_withdraw_destination(env, &recipient, &dest)
+    {
+        return Err(QuickexError::DestinationNotWhitelisted);
+    }
+
+    let balance = get_withdrawable(env, &token, &recipient);
+    if balance <= 0 {
+        return Err(QuickexError::NothingToWithdraw);
+    }
+
+    set_withdrawable(env, &token, &recipient, 0);
+
+    let token_client = token::Client::new(env, &token);
+    token_client.transfer(&env.current_contract_address(), &dest, &balance);
+
+    publish_credit_withdrawn(env, recipient, token, balance);
+
+    Ok(balance)
+}
+
+/// Restrict `owner`'s credit withdrawals to approved destinations, or lift it
+pub fn set_withdraw_whitelist(env: &Env, owner: Address, enabled: bool) {
+    owner.require_auth();
+    crate::storage::set_withdraw_whitelist(env, &owner, enabled);
+}
+
+/// Approve a destination for `owner`'s credit withdrawals
+pub fn add_withdraw_destination(env: &Env, owner: Address, dest: Address) {
+    owner.require_auth();
+    set_withdraw_destination(env, &owner, &dest, true);
+}
+
+/// Revoke a destination approved for `owner`'s credit withdrawals
+pub fn remove_withdraw_destination(env: &Env, owner: Address, dest: Address) {
+    owner.require_auth();
+    set_withdraw_destination(env, &owner, &dest, false);
+}
+
+/// Return everything deposited into a refunded escrow to its sender
+///
+/// That is the full amount, or the tranches received so far when the escrow
+/// is refunded before it was fully funded.
+pub(crate) fn refund_sender(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    refund_sender_less(env, escrow_id, escrow, 0);
+}
+
+/// Refund the sender all held funds except `withheld`, which stays in custody
+/// for the caller to pay out
+fn refund_sender_less(env: &Env, escrow_id: u64, escrow: &Escrow, withheld: i128) {
+    yield_adapter::recall(env, escrow_id, escrow);
+    record_settlement(env, &escrow.token, held(escrow));
+    ranking::remove(env, &escrow.token, escrow_id);
+    let refund = held(escrow) - withheld;
+    return_funds(env, escrow_id, escrow, refund, escrow.refund_to_internal);
+
+    publish_escrow_refunded(env, escrow_id, escrow.from.clone());
+    receiver::notify_escrow_event(env, escrow_id, escrow.status);
+}
+
+/// Give `amount` of an escrow's funds back to whoever put them in
+///
+/// Shared funders each get their pro-rata share of `amount` (their exact
+/// contribution on a full refund), and the rest, including rounding dust,
+/// goes to the sender's refund destination. With `credit` the payouts go
+/// to withdrawable balances instead of being transferred.
+pub(crate) fn return_funds(env: &Env, escrow_id: u64, escrow: &Escrow, amount: i128, credit: bool) {
+    let mut to_sender = amount;
+    for (funder, contribution) in get_funders(env, escrow_id).iter() {
+        let share = contribution * amount / escrow.funded;
+        pay_back(env, escrow_id, escrow, funder, share, credit);
+        to_sender -= share;
+    }
+    pay_back(
+        env,
+        escrow_id,
+        escrow,
+        refund_destination(escrow),
+        to_sender,
+        credit,
+    );
+}
+
+fn pay_back(env: &Env, escrow_id: u64, escrow: &Escrow, dest: Address, amount: i128, credit: bool) {
+    if amount <= 0 {
+        return;
+    }
+
+    if credit {
+        let balance = get_withdrawable(env, &escrow.token, &dest) + amount;
+        set_withdrawable(env, &escrow.token, &dest, balance);
+        publish_funds_credited(env, escrow_id, dest, amount);
+    } else {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(&env.current_contract_address(), &dest, &amount);
+    }
+}
+
+/// Set or clear the contract told when an escrow is released or refunded
+///
+/// Only the sender may change it while the escrow is open, and only to a
+/// contract the admin registered as a receiver, so settlement never calls
+/// arbitrary code.
+pub fn set_notify(
+    env: &Env,
+    from: Address,
+    escrow_id: u64,
+    notify: Option<Address>,
+) -> Result<(), QuickexError> {
+    let escrow = find_escrow(env, escrow_id)?;
+
+    if from != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    from.require_auth();
+
+    if !is_open(escrow.status) {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+    if let Some(contract) = &notify {
+        if !is_receiver(env, contract) {
+            return Err(QuickexError::ReceiverNotRegistered);
+        }
+    }
+
+    set_escrow_notify(env, escrow_id, notify.as_ref());
+
+    Ok(())
+}
+
+pub fn update_escrow_memo(
+    env: &Env,
+    from: Address,
+    escrow_id: u64,
+    memo: Bytes,
+) -> Result<(), QuickexError> {
+    from.require_auth();
+
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if from != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+
+    if escrow.status != EscrowStatus::Pending {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(QuickexError::MemoTooLong);
+    }
+
+    let old_memo = escrow.memo.clone();
+    escrow.memo = memo.clone();
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_escrow_memo_updated(env, escrow_id, old_memo, memo);
+
+    Ok(())
+}
+
+/// Attach the hash of an off-chain note to an escrow, replacing any earlier one
+///
+/// Each party keeps their own note. No event is emitted, so the counterparty
+/// doesn't learn that a note exists.
+pub fn set_private_note(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+    note_hash: BytesN<32>,
+) -> Result<(), QuickexError> {
+    if !is_party(env, escrow_id, caller.clone()) {
+        get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    crate::storage::set_private_note(env, escrow_id, &caller, &note_hash);
+    Ok(())
+}
+
+/// Read back the caller's own note hash on an escrow
+///
+/// Notes are stored per author, so asking for anyone else's note reports
+/// `NoteNotFound` the same as when there is none.
+pub fn get_private_note(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+) -> Result<BytesN<32>, QuickexError> {
+    caller.require_auth();
+    crate::storage::get_private_note(env, escrow_id, &caller).ok_or(QuickexError::NoteNotFound)
+}
+
+/// Attach the hash of a party's KYC attestation to an escrow
+///
+/// The sender and the recipient can each attach one hash; the document
+/// itself stays off-chain. An attached hash can't be replaced.
+pub fn attach_attestation(
+    env: &Env,
+    escrow_id: u64,
+    caller: Address,
+    attestation_hash: BytesN<32>,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let slot = if caller == escrow.from {
+        &mut escrow.from_attestation
+    } else if caller == escrow.to {
+        &mut escrow.to_attestation
+    } else {
+        return Err(QuickexError::Unauthorized);
+    };
+    caller.require_auth();
+
+    if slot.is_some() {
+        return Err(QuickexError::AttestationAlreadyAttached);
+    }
+    *slot = Some(attestation_hash.clone());
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_attestation_attached(env, escrow_id, caller, attestation_hash);
+
+    Ok(())
+}
+
+/// Register a third party as watching an escrow
+///
+/// Watchers get no powers over the escrow; the registration only lets the
+/// parties see who is following it. Needs the watcher's auth and consent
+/// from one of the counterparties.
+pub fn add_watcher(
+    env: &Env,
+    escrow_id: u64,
+    watcher: Address,
+    party: Address,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if party != escrow.from && party != escrow.to {
+        return Err(QuickexError::Unauthorized);
+    }
+    watcher.require_auth();
+    party.require_auth();
+
+    let mut watchers = get_watchers(env, escrow_id);
+    if watchers.contains(&watcher) {
+        return Err(QuickexError::AlreadyWatching);
+    }
+    if watchers.len() >= MAX_WATCHERS {
+        return Err(QuickexError::TooManyWatchers);
+    }
+
+    watchers.push_back(watcher.clone());
+    set_watchers(env, escrow_id, &watchers);
+
+    escrow.watcher_count = watchers.len();
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_watcher_added(env, escrow_id, watcher, escrow.watcher_count);
+
+    Ok(())
+}
+
+/// Let a third party see an escrow's full terms (Sender only)
+///
+/// Slots are capped, so the sender can't list themselves and re-adding an
+/// existing viewer does nothing. A viewer who already sees the terms as
+/// recipient or arbiter is still listed, with `redundant` set on the event.
+pub fn add_viewer(
+    env: &Env,
+    escrow_id: u64,
+    owner: Address,
+    viewer: Address,
+) -> Result<(), QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if owner != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    owner.require_auth();
+
+    if viewer == owner {
+        return Err(QuickexError::SelfViewerNotAllowed);
+    }
+
+    let mut viewers = get_viewers(env, escrow_id);
+    if viewers.contains(&viewer) {
+        return Ok(());
+    }
+    if viewers.len() >= MAX_VIEWERS {
+        return Err(QuickexError::TooManyViewers);
+    }
+
+    let redundant = has_party_access(&escrow, &viewer);
+    viewers.push_back(viewer.clone());
+    set_viewers(env, escrow_id, &viewers);
+
+    publish_viewer_added(env, escrow_id, viewer, viewers.len(), redundant);
+
+    Ok(())
+}
+
+/// Cancel an open escrow and refund its sender (Admin only)
+///
+/// For support interventions; the reason is recorded in the emitted event.
+/// A disputed escrow's bond goes back to whoever posted it.
+pub fn admin_cancel_escrow(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    reason: Symbol,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if !matches!(
+        escrow.status,
+        EscrowStatus::Pending | EscrowStatus::Disputed | EscrowStatus::Funding
+    ) {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Refunded)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    return_bond(env, &escrow);
+    refund_sender(env, escrow_id, &escrow);
+
+    publish_escrow_cancelled(env, escrow_id, reason);
+
+    Ok(())
+}
+
+/// Unwind a sender's open escrows in an emergency, e.g. a compromised key
+///
+/// Walks the sender's open escrows, so the cost follows what is still open
+/// rather than the sender's whole history. Escrows still being funded, or
+/// pending but not yet accepted by the recipient, are cancelled and refunded;
+/// accepted ones are put into dispute so an arbiter decides them. No bond is
+/// taken for those disputes, since a drained sender couldn't post one and a
+/// failed transfer would undo the whole unwind. Frozen escrows are left
+/// alone. Acts on at most `limit` escrows and returns the (cancelled,
+/// disputed) counts.
+pub fn refund_all_mine(env: &Env, sender: Address, limit: u32) -> Result<(u32, u32), QuickexError> {
+    sender.require_auth();
+
+    let (mut cancelled, mut disputed) = (0, 0);
+
+    for escrow_id in get_open_escrows(env, &sender).iter() {
+        if cancelled + disputed >= limit {
+            break;
+        }
+
+        let Some(mut escrow) = get_escrow_by_id(env, escrow_id) else {
+            continue;
+        };
+        if escrow.frozen {
+            continue;
+        }
+
+        match escrow.status {
+            EscrowStatus::Funding => {}
+            EscrowStatus::Pending if !escrow.accepted => {}
+            EscrowStatus::Pending => {
+                start_dispute(env, sender.clone(), escrow_id, &mut escrow, false)?;
+                disputed += 1;
+                continue;
+            }
+            _ => continue,
+        }
+
+        advance(env, escrow_id, &mut escrow, EscrowStatus::Refunded)?;
+        put_escrow_by_id(env, escrow_id, &escrow);
+        refund_sender(env, escrow_id, &escrow);
+        publish_escrow_cancelled(env, escrow_id, symbol_short!("unwind"));
+        cancelled += 1;
+    }
+
+    Ok((cancelled, disputed))
+}
+
+/// Return a pending escrow that outlived the maximum lifetime to its sender
+///
+/// Anyone may call this once `created_at + max_escrow_lifetime_secs` has
+/// passed, so stale escrows can be cleared without either party.
+pub fn force_expire(env: &Env, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let max_lifetime = get_max_escrow_lifetime(env);
+    if max_lifetime == 0
+        || env.ledger().timestamp() < escrow.created_at.saturating_add(max_lifetime)
+    {
+        return Err(QuickexError::EscrowNotExpired);
+    }
+
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Expired)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    refund_sender(env, escrow_id, &escrow);
+
+    publish_escrow_expired(env, escrow_id);
+
+    Ok(())
+}
+
+/// Whether an escrow's deadline had passed `grace` seconds ago
+fn past_deadline(env: &Env, escrow: &Escrow, grace: u64) -> bool {
+    escrow
+        .deadline
+        .is_some_and(|deadline| env.ledger().timestamp() >= deadline.saturating_add(grace))
+}
+
+/// Announce once that a pending escrow's deadline is near
+///
+/// Anyone may call this. Within the admin-set warning window before the
+/// deadline it emits `EscrowDeadlineApproaching` and marks the escrow so
+/// later pokes do nothing. Returns whether the warning was emitted.
+pub fn poke(env: &Env, escrow_id: u64) -> Result<bool, QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let window = get_deadline_warning_window(env);
+    let Some(deadline) = escrow.deadline else {
+        return Ok(false);
+    };
+    let now = env.ledger().timestamp();
+    if escrow.deadline_warned
+        || escrow.status != EscrowStatus::Pending
+        || window == 0
+        || now >= deadline
+        || now < deadline.saturating_sub(window)
+    {
+        return Ok(false);
+    }
+
+    escrow.deadline_warned = true;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_escrow_deadline_approaching(env, escrow_id, escrow.to, deadline);
+
+    Ok(true)
+}
+
+/// Return a pending escrow whose deadline has passed to its sender
+///
+/// Needs the sender's auth and pays no keeper reward.
+pub fn claim_expired(env: &Env, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+    escrow.from.require_auth();
+
+    if !past_deadline(env, &escrow, 0) {
+        return Err(QuickexError::EscrowNotExpired);
+    }
+
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Expired)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    refund_sender(env, escrow_id, &escrow);
+
+    publish_escrow_expired(env, escrow_id);
+
+    Ok(())
+}
+
+/// Return an expired escrow to its sender on their behalf, paying `keeper` a reward
+///
+/// Anyone may call this once the deadline plus the keeper grace period has
+/// passed. The reward is the configured keeper bps of the held funds and
+/// comes out of the sender's refund. Returns the reward paid.
+pub fn sweep_expired(env: &Env, escrow_id: u64, keeper: Address) -> Result<i128, QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let (_, grace) = get_keeper_incentive(env);
+    if !past_deadline(env, &escrow, grace) {
+        return Err(QuickexError::EscrowNotExpired);
+    }
+
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Expired)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    let reward = settlement_breakdown(env, &escrow, &Resolution::Sweep).keeper_reward;
+    refund_sender_less(env, escrow_id, &escrow, reward);
+    if reward > 0 {
+        let token_client = token::Client::new(env, &escrow.token);
+        token_client.transfer(&env.current_contract_address(), &keeper, &reward);
+    }
+
+    publish_escrow_expired(env, escrow_id);
+    publish_escrow_swept(env, escrow_id, keeper, reward);
+
+    Ok(reward)
+}
+
+/// Confirm as recipient that a pending escrow reached the right address
+///
+/// Once accepted, the sender can no longer reassign the recipient.
+pub fn accept_escrow(env: &Env, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if !escrow.allowed.is_empty() {
+        return Err(QuickexError::NotAllowedRecipient);
+    }
+    escrow.to.require_auth();
+
+    if escrow.status != EscrowStatus::Pending {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    escrow.accepted = true;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_escrow_accepted(env, escrow_id, escrow.to);
+
+    Ok(())
+}
+
+/// Point a pending escrow at a corrected recipient before they accept it
+///
+/// Saves a cancel-and-recreate when the sender entered the wrong address.
+/// The pair index moves with the escrow.
+pub fn reassign_recipient(
+    env: &Env,
+    from: Address,
+    escrow_id: u64,
+    new_to: Address,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if from != escrow.from {
+        return Err(QuickexError::Unauthorized);
+    }
+    from.require_auth();
+
+    if !escrow.allowed.is_empty() {
+        return Err(QuickexError::NotAllowedRecipient);
+    }
+    if escrow.accepted || escrow.status != EscrowStatus::Pending {
+        return Err(QuickexError::CannotReassignAfterAcceptance);
+    }
+    if escrow.frozen {
+        return Err(QuickexError::EscrowFrozen);
+    }
+    if get_pair_escrows(env, &pair_hash(env, &from, &new_to)).len() >= MAX_INDEX_LEN {
+        return Err(QuickexError::IndexFull);
+    }
+
+    unindex_pair(env, &from, &escrow.to, escrow_id);
+    index_pair(env, &from, &new_to, escrow_id);
+
+    let old_to = escrow.to;
+    escrow.to = new_to.clone();
+    // The new recipient has to attest for themselves
+    escrow.to_attestation = None;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    publish_recipient_reassigned(env, escrow_id, old_to, new_to);
+
+    Ok(())
+}
+
+/// Tag an escrow for compliance review (Admin only)
+///
+/// A flagged escrow can only be released by the admin until every flag is
+/// cleared. Setting a flag that is already present does nothing.
+pub fn flag_escrow(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    flag: Symbol,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let mut flags = get_escrow_flags(env, escrow_id);
+    if flags.contains(&flag) {
+        return Ok(());
+    }
+    if flags.len() >= MAX_ESCROW_FLAGS {
+        return Err(QuickexError::TooManyFlags);
+    }
+
+    flags.push_back(flag.clone());
+    set_escrow_flags(env, escrow_id, &flags);
+
+    publish_escrow_flagged(env, escrow_id, flag, true);
+
+    Ok(())
+}
+
+/// Clear a compliance flag from an escrow (Admin only)
+pub fn unflag_escrow(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    flag: Symbol,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let mut flags = get_escrow_flags(env, escrow_id);
+    let Some(i) = flags.first_index_of(&flag) else {
+        return Ok(());
+    };
+    flags.remove(i);
+    set_escrow_flags(env, escrow_id, &flags);
+
+    publish_escrow_flagged(env, escrow_id, flag, false);
+
+    Ok(())
+}
+
+/// Freeze a pending escrow on behalf of one party, e.g. on suspected fraud
+///
+/// While frozen the escrow can't be released, refunded, disputed or
+/// expired. Narrower than the contract-wide pause: only this escrow stops.
+pub fn freeze_escrow(env: &Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if caller != escrow.from && caller != escrow.to {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+
+    freeze(env, escrow_id, escrow, Some(&caller))
+}
+
+/// Freeze a pending escrow while the parties renegotiate
+///
+/// Needs both parties' auth. Unlike a single-party freeze, either party can
+/// lift it alone, so neither side can hold the funds hostage.
+pub fn freeze_escrow_mutual(env: &Env, escrow_id: u64) -> Result<(), QuickexError> {
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    escrow.from.require_auth();
+    escrow.to.require_auth();
+
+    freeze(env, escrow_id, escrow, None)
+}
+
+/// Freeze a pending escrow, recording the party who froze it unless both did
+fn freeze(
+    env: &Env,
+    escrow_id: u64,
+    mut escrow: Escrow,
+    frozen_by: Option<&Address>,
+) -> Result<(), QuickexError> {
+    if escrow.frozen {
+        return Err(QuickexError::EscrowFrozen);
+    }
+    if escrow.status != EscrowStatus::Pending {
+        return Err(QuickexError::EscrowAlreadyResolved);
+    }
+
+    escrow.frozen = true;
+    put_escrow_by_id(env, escrow_id, &escrow);
+    set_frozen_by(env, escrow_id, frozen_by);
+
+    publish_escrow_frozen(env, escrow_id, true);
+
+    Ok(())
+}
+
+/// Lift a freeze as the party who set it, as either party after a mutual
+/// freeze, or as the escrow's resolver
+///
+/// The resolver (its arbiter, or the admin when it has none) can step in so
+/// the freezing party can't hold the funds hostage.
+pub fn unfreeze_escrow(env: &Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if !escrow.frozen {
+        return Err(QuickexError::EscrowNotFrozen);
+    }
+    match get_frozen_by(env, escrow_id) {
+        Some(party) if party == caller => caller.require_auth(),
+        None if caller == escrow.from || caller == escrow.to => caller.require_auth(),
+        _ => require_resolver(env, &escrow, &caller)?,
+    }
+
+    escrow.frozen = false;
+    put_escrow_by_id(env, escrow_id, &escrow);
+    set_frozen_by(env, escrow_id, None);
+
+    publish_escrow_frozen(env, escrow_id, false);
+
+    Ok(())
+}
+
+/// Status of an escrow as shown to clients, reporting `Frozen` while frozen
+pub fn escrow_status(env: &Env, escrow_id: u64) -> Result<EscrowStatus, QuickexError> {
+    let escrow = find_escrow(env, escrow_id)?;
+    if escrow.frozen {
+        Ok(EscrowStatus::Frozen)
+    } else {
+        Ok(escrow.status)
+    }
+}
+
+/// Whether `account` is the sender, recipient or arbiter of an escrow
+pub fn is_party(env: &Env, escrow_id: u64, account: Address) -> bool {
+    match get_escrow_by_id(env, escrow_id) {
+        Some(escrow) => {
+            account == escrow.from || account == escrow.to || Some(account) == escrow.arbiter
+        }
+        None => false,
+    }
+}
+
+pub fn list_escrows_between(
+    env: &Env,
+    a: Address,
+    b: Address,
+    offset: u32,
+    limit: u32,
+) -> Vec<u64> {
+    let ids = get_pair_escrows(env, &pair_hash(env, &a, &b));
+    paginate(env, &ids, offset, limit)
+}
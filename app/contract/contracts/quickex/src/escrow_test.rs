@@ -0,0 +1,5122 @@
+#![cfg(test)]
+use crate::{
+    errors::QuickexError,
+    escrow::{transition, RESERVED_ESCROW_IDS},
+    types::{
+        AccountExport, ActivityEntry, ContractConfig, CounterpartyStats, DealStatus, Denomination,
+        EscrowBatchItem, EscrowTemplate, FeatureFlags, FeeTiming, ReserveProof, Resolution,
+        SettlementBreakdown, TermsPreimage,
+    },
+    EscrowStatus, QuickexContract, QuickexContractClient,
+};
+use soroban_sdk::{
+    testutils::{Address as _, Events as _, Ledger},
+    token, vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, Val, Vec,
+};
+
+fn setup<'a>() -> (Env, QuickexContractClient<'a>) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(QuickexContract, ());
+    let client = QuickexContractClient::new(&env, &contract_id);
+    (env, client)
+}
+
+fn create_funded_token(env: &Env, holder: &Address, amount: i128) -> Address {
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(holder, &amount);
+    token
+}
+
+/// The SAC `transfer` event emitted when funds move into or out of escrow
+fn transfer_event(
+    env: &Env,
+    token: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) -> (Address, soroban_sdk::Vec<Val>, Val) {
+    let asset_name = token::StellarAssetClient::new(env, token).name();
+    (
+        token.clone(),
+        (
+            Symbol::new(env, "transfer"),
+            from.clone(),
+            to.clone(),
+            asset_name,
+        )
+            .into_val(env),
+        amount.into_val(env),
+    )
+}
+
+fn memo(env: &Env, text: &str) -> Bytes {
+    Bytes::from_slice(env, text.as_bytes())
+}
+
+#[test]
+fn test_create_escrow() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "invoice-7"));
+    assert!(escrow_id > 0);
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.from, from);
+    assert_eq!(escrow.to, to);
+    assert_eq!(escrow.amount, 1_000);
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    assert_eq!(escrow.memo, memo(&env, "invoice-7"));
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 0);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+}
+
+#[test]
+fn test_release_escrow() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    client.release_escrow(&from, &escrow_id);
+
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyReleased)));
+
+    let result = client.try_release_escrow(&from, &(escrow_id + 1));
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotFound)));
+}
+
+#[test]
+fn test_transition_allows_legal_moves() {
+    use EscrowStatus::*;
+
+    for next in [Released, Refunded, Expired, Disputed] {
+        assert_eq!(transition(Pending, next), Ok(()));
+    }
+    for next in [Released, Refunded] {
+        assert_eq!(transition(Disputed, next), Ok(()));
+    }
+}
+
+#[test]
+fn test_transition_rejects_illegal_moves() {
+    use EscrowStatus::*;
+
+    let all = [
+        Pending, Spent, Expired, Released, Refunded, Disputed, Frozen,
+    ];
+    let legal = [
+        (Pending, Released),
+        (Pending, Refunded),
+        (Pending, Expired),
+        (Pending, Disputed),
+        (Disputed, Released),
+        (Disputed, Refunded),
+    ];
+
+    for current in all {
+        for next in all {
+            if legal.contains(&(current, next)) {
+                continue;
+            }
+            let expected = match (current, next) {
+                (Released, Released) => QuickexError::EscrowAlreadyReleased,
+                _ => QuickexError::InvalidStateTransition,
+            };
+            assert_eq!(transition(current, next), Err(expected));
+        }
+    }
+}
+
+#[test]
+fn test_escrow_counter_matches_created_escrows() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 300);
+
+    assert_eq!(client.get_escrow_counter(), None);
+    assert_eq!(client.get_escrow_count(), 0);
+
+    for created in 1..=3u64 {
+        let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+        assert_eq!(escrow_id, RESERVED_ESCROW_IDS + created);
+        let counter = client.get_escrow_counter().unwrap();
+        assert_eq!(counter, escrow_id);
+        assert_eq!(counter - RESERVED_ESCROW_IDS, client.get_escrow_count());
+        assert_eq!(client.get_escrow_count(), created);
+    }
+    assert_eq!(client.preview_next_escrow_id(), RESERVED_ESCROW_IDS + 4);
+}
+
+#[test]
+fn test_create_escrow_at_expected_id() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let expected_id = client.preview_next_escrow_id();
+    assert_eq!(expected_id, RESERVED_ESCROW_IDS + 1);
+    // Previewing doesn't reserve the id
+    assert_eq!(client.preview_next_escrow_id(), expected_id);
+
+    let escrow_id =
+        client.create_escrow_at(&expected_id, &from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(escrow_id, expected_id);
+    assert_eq!(client.preview_next_escrow_id(), expected_id + 1);
+}
+
+#[test]
+fn test_create_escrow_at_stale_id_fails() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    token::StellarAssetClient::new(&env, &token).mint(&other, &1_000);
+
+    // Two clients preview the same id, one of them wins the race
+    let alice_id = client.preview_next_escrow_id();
+    let bob_id = client.preview_next_escrow_id();
+    assert_eq!(
+        client.create_escrow_at(&alice_id, &from, &to, &token, &1_000, &Bytes::new(&env)),
+        alice_id
+    );
+
+    let result = client.try_create_escrow_at(&bob_id, &other, &to, &token, &500, &Bytes::new(&env));
+    assert_eq!(result, Err(Ok(QuickexError::EscrowIdMismatch)));
+
+    // Re-previewing gives a fresh id that succeeds
+    let fresh_id = client.preview_next_escrow_id();
+    assert_eq!(
+        client.create_escrow_at(&fresh_id, &other, &to, &token, &500, &Bytes::new(&env)),
+        bob_id + 1
+    );
+}
+
+#[test]
+fn test_update_escrow_memo() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "inv-001"));
+    client.update_escrow_memo(&from, &escrow_id, &memo(&env, "inv-010"));
+
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().memo,
+        memo(&env, "inv-010")
+    );
+}
+
+#[test]
+fn test_update_escrow_memo_too_long_fails() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    let long_memo = Bytes::from_array(&env, &[b'x'; 65]);
+
+    let result = client.try_update_escrow_memo(&from, &escrow_id, &long_memo);
+    assert_eq!(result, Err(Ok(QuickexError::MemoTooLong)));
+}
+
+#[test]
+fn test_update_escrow_memo_after_release_fails() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "inv-001"));
+    client.release_escrow(&from, &escrow_id);
+
+    let result = client.try_update_escrow_memo(&from, &escrow_id, &memo(&env, "inv-002"));
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+}
+
+#[test]
+fn test_list_escrows_between_merges_directions() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let token = create_funded_token(&env, &alice, 1_000);
+    token::StellarAssetClient::new(&env, &token).mint(&bob, &1_000);
+
+    let first = client.create_escrow(&alice, &bob, &token, &100, &Bytes::new(&env));
+    let second = client.create_escrow(&bob, &alice, &token, &200, &Bytes::new(&env));
+    client.create_escrow(&alice, &carol, &token, &300, &Bytes::new(&env));
+    let third = client.create_escrow(&alice, &bob, &token, &400, &Bytes::new(&env));
+
+    let ids = client.list_escrows_between(&alice, &bob, &0, &10);
+    assert_eq!(ids, vec![&env, first, second, third]);
+    assert_eq!(client.list_escrows_between(&bob, &alice, &0, &10), ids);
+
+    // Direction is recoverable from the record
+    assert_eq!(client.get_escrow(&second).unwrap().from, bob);
+
+    // Paging
+    assert_eq!(
+        client.list_escrows_between(&alice, &bob, &1, &1),
+        vec![&env, second]
+    );
+    assert_eq!(client.list_escrows_between(&alice, &bob, &5, &10).len(), 0);
+}
+
+#[test]
+fn test_token_privacy_default_hides_amount_in_events() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    client.initialize(&admin);
+
+    let public_token = create_funded_token(&env, &from, 1_000);
+    let sensitive_token = create_funded_token(&env, &from, 1_000);
+    client.set_token_privacy_default(&admin, &sensitive_token, &true);
+    assert!(client.get_token_privacy_default(&sensitive_token));
+    assert!(!client.get_token_privacy_default(&public_token));
+
+    let public_id = client.create_escrow(&from, &to, &public_token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &public_token, &from, &client.address, 1_000),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowCreated"), public_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), to.into_val(&env)),
+                        (Symbol::new(&env, "token"), public_token.into_val(&env)),
+                        (Symbol::new(&env, "amount"), 1_000i128.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    // The sender never enabled account-level privacy, the token default applies
+    let private_id = client.create_escrow(&from, &to, &sensitive_token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &sensitive_token, &from, &client.address, 1_000),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "PrivateEscrowCreated"), private_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), to.into_val(&env)),
+                        (Symbol::new(&env, "token"), sensitive_token.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    assert!(client.get_escrow(&private_id).unwrap().private);
+    assert!(!client.get_escrow(&public_id).unwrap().private);
+}
+
+#[test]
+fn test_private_escrows_by_default_captured_at_creation() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+
+    client.set_private_escrows_by_default(&from, &true);
+    assert!(client.get_private_escrows_by_default(&from));
+    let private_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &from, &client.address, 1_000),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "PrivateEscrowCreated"), private_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), to.into_val(&env)),
+                        (Symbol::new(&env, "token"), token.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    // Turning the setting off only affects escrows created afterwards
+    client.set_private_escrows_by_default(&from, &false);
+    let public_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let outsider = Address::generate(&env);
+    assert_eq!(client.get_escrow(&private_id).unwrap().amount, 0);
+    assert_eq!(
+        client.get_escrow_as(&private_id, &outsider).unwrap().amount,
+        0
+    );
+    assert_eq!(
+        client.get_escrow_as(&private_id, &to).unwrap().amount,
+        1_000
+    );
+    assert_eq!(client.get_escrow(&public_id).unwrap().amount, 1_000);
+
+    // The amount is still stored in clear for settlement
+    client.release_escrow(&from, &private_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_set_token_privacy_default_by_non_admin_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let non_admin = Address::generate(&env);
+    let token = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.set_token_privacy_default(&non_admin, &token, &true);
+}
+
+#[test]
+fn test_template_escrows_keep_their_own_terms() {
+    let (env, client) = setup();
+    let merchant = Address::generate(&env);
+    let supplier = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let token = create_funded_token(&env, &merchant, 1_000);
+    env.ledger().set_timestamp(1_000);
+
+    let template = EscrowTemplate {
+        recipient: supplier.clone(),
+        token: token.clone(),
+        arbiter: Some(arbiter.clone()),
+        deadline_offset: 3_600,
+        fee_bps: Some(50),
+    };
+    let template_id = client.create_template(&merchant, &template);
+    assert_eq!(
+        client.get_template(&merchant, &template_id),
+        Some(template.clone())
+    );
+
+    let first = client.create_from_template(&merchant, &template_id, &100);
+    let second = client.create_from_template(&merchant, &template_id, &200);
+
+    for (escrow_id, amount) in [(first, 100), (second, 200)] {
+        let escrow = client.get_escrow(&escrow_id).unwrap();
+        assert_eq!(escrow.from, merchant);
+        assert_eq!(escrow.to, supplier);
+        assert_eq!(escrow.amount, amount);
+        assert_eq!(escrow.arbiter, Some(arbiter.clone()));
+        assert_eq!(escrow.deadline, Some(4_600));
+        assert_eq!(escrow.fee_bps, Some(50));
+    }
+
+    let other_recipient = Address::generate(&env);
+    client.update_template(
+        &merchant,
+        &template_id,
+        &EscrowTemplate {
+            recipient: other_recipient.clone(),
+            arbiter: None,
+            deadline_offset: 0,
+            fee_bps: None,
+            ..template
+        },
+    );
+
+    // Existing escrows are unaffected, new ones pick up the change
+    let first_escrow = client.get_escrow(&first).unwrap();
+    assert_eq!(first_escrow.to, supplier);
+    assert_eq!(first_escrow.fee_bps, Some(50));
+
+    let third = client.create_from_template(&merchant, &template_id, &300);
+    let third_escrow = client.get_escrow(&third).unwrap();
+    assert_eq!(third_escrow.to, other_recipient);
+    assert_eq!(third_escrow.arbiter, None);
+    assert_eq!(third_escrow.deadline, None);
+}
+
+#[test]
+fn test_template_cap_and_delete() {
+    let (env, client) = setup();
+    let merchant = Address::generate(&env);
+    let template = EscrowTemplate {
+        recipient: Address::generate(&env),
+        token: Address::generate(&env),
+        arbiter: None,
+        deadline_offset: 0,
+        fee_bps: None,
+    };
+
+    for _ in 0..16 {
+        client.create_template(&merchant, &template);
+    }
+    assert_eq!(
+        client.try_create_template(&merchant, &template),
+        Err(Ok(QuickexError::TooManyTemplates))
+    );
+
+    client.delete_template(&merchant, &1);
+    assert_eq!(client.get_template(&merchant, &1), None);
+    assert_eq!(
+        client.try_create_from_template(&merchant, &1, &100),
+        Err(Ok(QuickexError::TemplateNotFound))
+    );
+
+    // Deleting frees a slot; ids are not reused
+    assert_eq!(client.create_template(&merchant, &template), 17);
+}
+
+#[test]
+fn test_event_detail_preference_selects_variant() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let light = Address::generate(&env);
+    let full = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+
+    assert!(client.get_event_detail(&light));
+    client.set_event_detail(&light, &false);
+    assert!(!client.get_event_detail(&light));
+
+    let compact_id = client.create_escrow(&from, &light, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &from, &client.address, 1_000),
+            (
+                client.address.clone(),
+                (
+                    Symbol::new(&env, "CompactEscrowCreated"),
+                    compact_id,
+                    from.clone(),
+                    light.clone()
+                )
+                    .into_val(&env),
+                Map::<Symbol, Val>::new(&env).into_val(&env),
+            ),
+        ]
+    );
+
+    client.release_escrow(&from, &compact_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &light, 1_000),
+            (
+                client.address.clone(),
+                (
+                    Symbol::new(&env, "CompactEscrowReleased"),
+                    compact_id,
+                    from.clone(),
+                    light.clone()
+                )
+                    .into_val(&env),
+                Map::<Symbol, Val>::new(&env).into_val(&env),
+            ),
+        ]
+    );
+
+    // Recipients that never set a preference keep the detailed event
+    let detailed_id = client.create_escrow(&from, &full, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &from, &client.address, 1_000),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowCreated"), detailed_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), full.into_val(&env)),
+                        (Symbol::new(&env, "token"), token.into_val(&env)),
+                        (Symbol::new(&env, "amount"), 1_000i128.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_insurance_pool_accrues_fees_and_pays_claims() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let supplier = Address::generate(&env);
+    let claimant = Address::generate(&env);
+    let token = create_funded_token(&env, &merchant, 20_000);
+    client.initialize(&admin);
+    client.set_insurance_share_bps(&admin, &2_000);
+
+    // 1% fee, a fifth of which goes to the pool
+    let template_id = client.create_template(
+        &merchant,
+        &EscrowTemplate {
+            recipient: supplier.clone(),
+            token: token.clone(),
+            arbiter: None,
+            deadline_offset: 0,
+            fee_bps: Some(100),
+        },
+    );
+    let first = client.create_from_template(&merchant, &template_id, &10_000);
+    let second = client.create_from_template(&merchant, &template_id, &10_000);
+    client.release_escrow(&merchant, &first);
+    client.release_escrow(&merchant, &second);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&supplier), 19_800);
+    assert_eq!(client.get_insurance_pool(&token), 40);
+    assert_eq!(client.get_collected_fees(&token), 160);
+
+    client.pay_insurance_claim(&admin, &first, &claimant, &30);
+    assert_eq!(token_client.balance(&claimant), 30);
+    assert_eq!(client.get_insurance_pool(&token), 10);
+
+    // The pool can never be overdrawn
+    let result = client.try_pay_insurance_claim(&admin, &first, &claimant, &11);
+    assert_eq!(result, Err(Ok(QuickexError::InsufficientInsurancePool)));
+    let result = client.try_pay_insurance_claim(&admin, &first, &claimant, &-5);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+
+    client.pay_insurance_claim(&admin, &second, &claimant, &10);
+    assert_eq!(client.get_insurance_pool(&token), 0);
+    assert_eq!(token_client.balance(&claimant), 40);
+}
+
+#[test]
+fn test_insurance_admin_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_insurance_share_bps(&other, &1_000);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_set_insurance_share_bps(&admin, &10_001);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidFeeBps)));
+    let result = client.try_pay_insurance_claim(&other, &1, &other, &1);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_fee_timing_on_release_is_default() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.get_fee_timing(), FeeTiming::OnRelease);
+
+    let (from, to, _, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    assert_eq!(client.get_collected_fees(&token), 0);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_090);
+}
+
+#[test]
+fn test_fee_timing_on_create_charges_once() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_fee_timing(&admin, &FeeTiming::OnCreate);
+    assert_eq!(client.get_fee_timing(), FeeTiming::OnCreate);
+
+    let (from, to, _, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().fee_paid, 10);
+
+    // Release pays the same net amount without taking the fee again
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_090);
+}
+
+#[test]
+fn test_fee_timing_on_create_refund_does_not_double_charge() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_fee_timing(&admin, &FeeTiming::OnCreate);
+
+    let (from, _, _, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    client.admin_cancel_escrow(&admin, &escrow_id, &Symbol::new(&env, "fraud"));
+
+    // The sender gets back everything but the fee taken at creation, and
+    // the contract holds exactly the collected fee
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 1_090);
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(token_client.balance(&client.address), 10);
+}
+
+#[test]
+fn test_fee_timing_on_create_split_covers_held_funds() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_fee_timing(&admin, &FeeTiming::OnCreate);
+
+    let (from, to, _, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+
+    let result = client.try_release_split_amounts(&from, &escrow_id, &600, &400);
+    assert_eq!(result, Err(Ok(QuickexError::SplitMismatch)));
+
+    client.release_split_amounts(&from, &escrow_id, &600, &390);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&to), 700);
+    assert_eq!(token_client.balance(&from), 490);
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(token_client.balance(&client.address), 10);
+}
+
+#[test]
+fn test_set_fee_timing_admin_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_fee_timing(&other, &FeeTiming::OnCreate);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    assert_eq!(client.get_fee_timing(), FeeTiming::OnRelease);
+}
+
+/// Escrow of `amount` from `from` to `to` judged by `arbiter`, with both
+/// sides holding 100 spare tokens for bonds
+fn setup_arbitrated_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+    amount: i128,
+    fee_bps: Option<u32>,
+) -> (Address, Address, Address, Address, u64) {
+    let from = Address::generate(env);
+    let to = Address::generate(env);
+    let arbiter = Address::generate(env);
+    let token = create_funded_token(env, &from, amount + 100);
+    token::StellarAssetClient::new(env, &token).mint(&to, &100);
+
+    let template_id = client.create_template(
+        &from,
+        &EscrowTemplate {
+            recipient: to.clone(),
+            token: token.clone(),
+            arbiter: Some(arbiter.clone()),
+            deadline_offset: 0,
+            fee_bps,
+        },
+    );
+    let escrow_id = client.create_from_template(&from, &template_id, &amount);
+
+    (from, to, arbiter, token, escrow_id)
+}
+
+#[test]
+fn test_dispute_messages_are_ordered() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, arbiter, _, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+
+    // Nothing can be posted before the dispute is opened
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let result = client.try_post_dispute_message(&escrow_id, &from, &hash);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotDisputed)));
+
+    client.open_dispute(&to, &escrow_id);
+    env.ledger().set_timestamp(100);
+    client.post_dispute_message(&escrow_id, &to, &hash);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "DisputeMessage"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "sender"), to.into_val(&env)),
+                        (Symbol::new(&env, "msg_hash"), hash.into_val(&env)),
+                        (Symbol::new(&env, "index"), 0u32.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    env.ledger().set_timestamp(200);
+    client.post_dispute_message(&escrow_id, &from, &BytesN::from_array(&env, &[2; 32]));
+    env.ledger().set_timestamp(300);
+    client.post_dispute_message(&escrow_id, &arbiter, &BytesN::from_array(&env, &[3; 32]));
+
+    let outsider = Address::generate(&env);
+    let result = client.try_post_dispute_message(&escrow_id, &outsider, &hash);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    let messages = client.get_dispute_messages(&escrow_id, &0, &10);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages.get_unchecked(0).sender, to);
+    assert_eq!(messages.get_unchecked(1).sender, from);
+    assert_eq!(messages.get_unchecked(2).sender, arbiter);
+    assert_eq!(messages.get_unchecked(2).timestamp, 300);
+
+    let page = client.get_dispute_messages(&escrow_id, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(
+        page.get_unchecked(0).msg_hash,
+        BytesN::from_array(&env, &[2; 32])
+    );
+    assert_eq!(client.get_dispute_messages(&escrow_id, &5, &10).len(), 0);
+
+    // Once resolved the record is closed
+    client.resolve_dispute(&arbiter, &escrow_id, &true);
+    let result = client.try_post_dispute_message(&escrow_id, &from, &hash);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotDisputed)));
+    assert_eq!(client.get_dispute_messages(&escrow_id, &0, &10).len(), 3);
+}
+
+#[test]
+fn test_submit_evidence_by_parties() {
+    let (env, client) = setup();
+    let (from, to, arbiter, _, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    let first = BytesN::from_array(&env, &[1; 32]);
+    let second = BytesN::from_array(&env, &[2; 32]);
+
+    // Evidence can be anchored ahead of a dispute
+    client.submit_evidence(&from, &escrow_id, &first);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EvidenceSubmitted"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "evidence_hash"), first.into_val(&env)),
+                        (Symbol::new(&env, "index"), 0u32.into_val(&env)),
+                        (Symbol::new(&env, "submitter"), from.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    client.open_dispute(&to, &escrow_id);
+    client.submit_evidence(&to, &escrow_id, &second);
+    assert_eq!(
+        client.get_evidence(&escrow_id),
+        vec![&env, first.clone(), second]
+    );
+
+    // The arbiter reads evidence but doesn't add to it
+    for outsider in [arbiter.clone(), Address::generate(&env)] {
+        assert_eq!(
+            client.try_submit_evidence(&outsider, &escrow_id, &first),
+            Err(Ok(QuickexError::Unauthorized))
+        );
+    }
+
+    client.resolve_dispute(&arbiter, &escrow_id, &true);
+    assert_eq!(
+        client.try_submit_evidence(&from, &escrow_id, &first),
+        Err(Ok(QuickexError::EscrowAlreadyResolved))
+    );
+    assert_eq!(client.get_evidence(&escrow_id).len(), 2);
+    assert_eq!(client.get_evidence(&99), vec![&env]);
+}
+
+#[test]
+fn test_dispute_messages_are_bounded() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, _, _, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    client.open_dispute(&from, &escrow_id);
+
+    for i in 0..32u8 {
+        client.post_dispute_message(&escrow_id, &to, &BytesN::from_array(&env, &[i; 32]));
+    }
+    let result =
+        client.try_post_dispute_message(&escrow_id, &from, &BytesN::from_array(&env, &[99; 32]));
+    assert_eq!(result, Err(Ok(QuickexError::DisputeLogFull)));
+    assert_eq!(client.get_dispute_messages(&escrow_id, &0, &100).len(), 32);
+}
+
+#[test]
+fn test_dispute_bond_returned_when_opener_wins() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &500);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    let token_client = token::Client::new(&env, &token);
+
+    client.open_dispute(&to, &escrow_id);
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(escrow.dispute_opener, Some(to.clone()));
+    assert_eq!(escrow.dispute_bond, 50);
+    assert_eq!(token_client.balance(&to), 50);
+
+    client.resolve_dispute(&arbiter, &escrow_id, &true);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+    assert_eq!(token_client.balance(&to), 1_100);
+    assert_eq!(token_client.balance(&from), 100);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_dispute_bond_forfeited_when_opener_loses() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &500);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    let token_client = token::Client::new(&env, &token);
+
+    client.open_dispute(&from, &escrow_id);
+    assert_eq!(token_client.balance(&from), 50);
+
+    // Only the arbiter rules once one is set
+    let result = client.try_resolve_dispute(&admin, &escrow_id, &false);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.resolve_dispute(&arbiter, &escrow_id, &true);
+    assert_eq!(token_client.balance(&from), 50);
+    assert_eq!(token_client.balance(&to), 1_150);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_dispute_refused_when_opener_cannot_fund_bond() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &2_000);
+    let (_, to, _, _, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+
+    // A 200 bond is more than the recipient holds
+    assert!(client.try_open_dispute(&to, &escrow_id).is_err());
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    assert_eq!(escrow.dispute_opener, None);
+}
+
+#[test]
+fn test_dispute_without_bond_refunds_sender() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let result = client.try_open_dispute(&Address::generate(&env), &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.open_dispute(&to, &escrow_id);
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().dispute_bond, 0);
+
+    // With no arbiter on the escrow the admin rules
+    client.resolve_dispute(&admin, &escrow_id, &false);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 1_000);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Refunded
+    );
+
+    let result = client.try_resolve_dispute(&admin, &escrow_id, &true);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidStateTransition)));
+}
+
+#[test]
+fn test_multi_recipient_escrow_first_claim_wins() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let courier_a = Address::generate(&env);
+    let courier_b = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 500);
+
+    let escrow_id = client.create_multi_recipient_escrow(
+        &from,
+        &token,
+        &500,
+        &vec![&env, courier_a.clone(), courier_b.clone()],
+    );
+
+    // Nobody to release to until someone claims
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::NotAllowedRecipient)));
+
+    client.claim_escrow(&courier_b, &escrow_id);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&courier_b), 500);
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.to, courier_b);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+
+    // Refiled under the claimant's pair
+    assert_eq!(
+        client.list_escrows_between(&from, &courier_b, &0, &10),
+        vec![&env, escrow_id]
+    );
+    assert_eq!(client.list_escrows_between(&from, &from, &0, &10).len(), 0);
+
+    let result = client.try_claim_escrow(&courier_a, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyReleased)));
+}
+
+#[test]
+fn test_claim_all_settles_claimable_escrows_in_one_transfer() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let courier = Address::generate(&env);
+    let rival = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    let other_token = create_funded_token(&env, &from, 100);
+    let token_client = token::Client::new(&env, &token);
+
+    let first =
+        client.create_multi_recipient_escrow(&from, &token, &300, &vec![&env, courier.clone()]);
+    let second = client.create_multi_recipient_escrow(
+        &from,
+        &token,
+        &400,
+        &vec![&env, courier.clone(), rival.clone()],
+    );
+    let taken = client.create_multi_recipient_escrow(
+        &from,
+        &token,
+        &200,
+        &vec![&env, courier.clone(), rival.clone()],
+    );
+    let other = client.create_multi_recipient_escrow(
+        &from,
+        &other_token,
+        &100,
+        &vec![&env, courier.clone()],
+    );
+    // Needs the sender to release, so claim_all never touches it
+    let direct = client.create_escrow(&from, &courier, &token, &500, &Bytes::new(&env));
+
+    client.claim_escrow(&rival, &taken);
+
+    let settled = client.claim_all(&courier, &token, &10);
+    assert_eq!(settled, vec![&env, first, second]);
+
+    // One payout for both escrows
+    assert_eq!(
+        env.events().all().filter_by_contract(&token),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &courier, 700)
+        ]
+    );
+    assert_eq!(token_client.balance(&courier), 700);
+
+    assert_eq!(client.get_escrow(&first).unwrap().to, courier);
+    assert_eq!(
+        client.get_escrow(&direct).unwrap().status,
+        EscrowStatus::Pending
+    );
+    assert_eq!(
+        client.get_escrow(&other).unwrap().status,
+        EscrowStatus::Pending
+    );
+
+    // Nothing left in this token; the other token is still claimable
+    assert_eq!(client.claim_all(&courier, &token, &10), vec![&env]);
+    assert_eq!(
+        client.claim_all(&courier, &other_token, &10),
+        vec![&env, other]
+    );
+}
+
+#[test]
+fn test_claim_all_respects_limit_and_skips_frozen() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let courier = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 600);
+
+    let frozen =
+        client.create_multi_recipient_escrow(&from, &token, &200, &vec![&env, courier.clone()]);
+    let second =
+        client.create_multi_recipient_escrow(&from, &token, &200, &vec![&env, courier.clone()]);
+    let third =
+        client.create_multi_recipient_escrow(&from, &token, &200, &vec![&env, courier.clone()]);
+    client.freeze_escrow(&from, &frozen);
+
+    assert_eq!(client.claim_all(&courier, &token, &1), vec![&env, second]);
+    assert_eq!(client.claim_all(&courier, &token, &5), vec![&env, third]);
+
+    // The frozen escrow stays claimable once the freeze is lifted
+    client.unfreeze_escrow(&from, &frozen);
+    assert_eq!(client.claim_all(&courier, &token, &5), vec![&env, frozen]);
+    assert_eq!(token::Client::new(&env, &token).balance(&courier), 600);
+}
+
+#[test]
+fn test_multi_recipient_escrow_rejects_other_claimants() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let allowed = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 500);
+
+    let result =
+        client.try_create_multi_recipient_escrow(&from, &token, &500, &soroban_sdk::Vec::new(&env));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidRecipientList)));
+
+    let escrow_id = client.create_multi_recipient_escrow(&from, &token, &500, &vec![&env, allowed]);
+
+    let result = client.try_claim_escrow(&Address::generate(&env), &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::NotAllowedRecipient)));
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Pending
+    );
+}
+
+#[test]
+fn test_add_watchers_with_party_consent() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    let carrier = Address::generate(&env);
+    client.add_watcher(&escrow_id, &carrier, &to);
+
+    // Both the watcher and the consenting party signed
+    let auths = env.auths();
+    assert!(auths.iter().any(|(signer, _)| *signer == carrier));
+    assert!(auths.iter().any(|(signer, _)| *signer == to));
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (
+                    Symbol::new(&env, "WatcherAdded"),
+                    escrow_id,
+                    carrier.clone()
+                )
+                    .into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "watcher_count"), 1u32.into_val(&env))]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        client.list_watchers(&escrow_id),
+        vec![&env, carrier.clone()]
+    );
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().watcher_count, 1);
+
+    let result = client.try_add_watcher(&escrow_id, &carrier, &from);
+    assert_eq!(result, Err(Ok(QuickexError::AlreadyWatching)));
+
+    // Outsiders can't consent on the parties' behalf
+    let result = client.try_add_watcher(&escrow_id, &Address::generate(&env), &carrier);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_watchers_capped_per_escrow() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    for _ in 0..8 {
+        client.add_watcher(&escrow_id, &Address::generate(&env), &from);
+    }
+    let result = client.try_add_watcher(&escrow_id, &Address::generate(&env), &from);
+    assert_eq!(result, Err(Ok(QuickexError::TooManyWatchers)));
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().watcher_count, 8);
+}
+
+#[test]
+fn test_attach_attestation_once_per_party() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    let kyc = BytesN::from_array(&env, &[7; 32]);
+    client.attach_attestation(&escrow_id, &to, &kyc);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (
+                    Symbol::new(&env, "AttestationAttached"),
+                    escrow_id,
+                    to.clone()
+                )
+                    .into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(
+                        Symbol::new(&env, "attestation_hash"),
+                        kyc.clone().into_val(&env)
+                    )]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.to_attestation, Some(kyc.clone()));
+    assert_eq!(escrow.from_attestation, None);
+
+    let result =
+        client.try_attach_attestation(&escrow_id, &to, &BytesN::from_array(&env, &[8; 32]));
+    assert_eq!(result, Err(Ok(QuickexError::AttestationAlreadyAttached)));
+
+    let result = client.try_attach_attestation(&escrow_id, &Address::generate(&env), &kyc);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_required_attestations_gate_funding_and_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_500);
+    client.initialize(&admin);
+    client.set_require_attestations(&admin, &true);
+
+    let tranche_id = client.create_tranche_escrow(&from, &to, &token, &1_000, &memo(&env, "t"));
+    let result = client.try_fund_escrow_partial(&from, &tranche_id, &1_000);
+    assert_eq!(result, Err(Ok(QuickexError::AttestationsMissing)));
+
+    client.attach_attestation(&tranche_id, &from, &BytesN::from_array(&env, &[1; 32]));
+    let result = client.try_fund_escrow_partial(&from, &tranche_id, &1_000);
+    assert_eq!(result, Err(Ok(QuickexError::AttestationsMissing)));
+
+    client.attach_attestation(&tranche_id, &to, &BytesN::from_array(&env, &[2; 32]));
+    client.fund_escrow_partial(&from, &tranche_id, &1_000);
+    client.release_escrow(&from, &tranche_id);
+
+    // Escrows funded at creation are held until both parties attest
+    let escrow_id = client.create_escrow(&from, &to, &token, &500, &Bytes::new(&env));
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::AttestationsMissing)));
+
+    client.set_require_attestations(&admin, &false);
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_500);
+}
+
+#[test]
+fn test_required_attestations_gate_every_payout() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+    client.set_require_attestations(&admin, &true);
+
+    let result =
+        client.try_create_multi_recipient_escrow(&from, &token, &100, &vec![&env, to.clone()]);
+    assert_eq!(result, Err(Ok(QuickexError::AttestationsMissing)));
+
+    // Splits pay the recipient too, even when the admin settles alone
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    let result = client.try_release_split_amounts(&admin, &escrow_id, &600, &400);
+    assert_eq!(result, Err(Ok(QuickexError::AttestationsMissing)));
+
+    // A full refund doesn't need attestations
+    client.release_split_amounts(&admin, &escrow_id, &0, &1_000);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+}
+
+#[test]
+fn test_private_note_readable_only_by_author() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    let waiting = BytesN::from_array(&env, &[1; 32]);
+    client.set_private_note(&escrow_id, &from, &waiting);
+    assert_eq!(client.get_private_note(&escrow_id, &from), waiting);
+
+    // The counterparty can't tell a note exists
+    let result = client.try_get_private_note(&escrow_id, &to);
+    assert_eq!(result, Err(Ok(QuickexError::NoteNotFound)));
+
+    let shipped = BytesN::from_array(&env, &[2; 32]);
+    client.set_private_note(&escrow_id, &from, &shipped);
+    assert_eq!(client.get_private_note(&escrow_id, &from), shipped);
+
+    // Each party keeps their own note
+    client.set_private_note(&escrow_id, &to, &waiting);
+    assert_eq!(client.get_private_note(&escrow_id, &to), waiting);
+    assert_eq!(client.get_private_note(&escrow_id, &from), shipped);
+
+    let result = client.try_set_private_note(&escrow_id, &Address::generate(&env), &shipped);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_viewer_sees_private_escrow_terms() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.set_private_escrows_by_default(&from, &true);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let auditor = Address::generate(&env);
+    assert_eq!(
+        client.get_escrow_as(&escrow_id, &auditor).unwrap().amount,
+        0
+    );
+
+    client.add_viewer(&escrow_id, &from, &auditor);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "ViewerAdded"), escrow_id, auditor.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "redundant"), false.into_val(&env)),
+                        (Symbol::new(&env, "viewer_count"), 1u32.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(
+        client.get_escrow_as(&escrow_id, &auditor).unwrap().amount,
+        1_000
+    );
+}
+
+#[test]
+fn test_add_viewer_rejects_owner_and_outsiders() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    let result = client.try_add_viewer(&escrow_id, &from, &from);
+    assert_eq!(result, Err(Ok(QuickexError::SelfViewerNotAllowed)));
+
+    // Only the sender manages the list
+    let result = client.try_add_viewer(&escrow_id, &to, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    assert!(client.list_viewers(&escrow_id).is_empty());
+}
+
+#[test]
+fn test_add_viewer_flags_existing_party_access() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    // The recipient already sees the terms, so the entry is marked redundant
+    client.add_viewer(&escrow_id, &from, &to);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "ViewerAdded"), escrow_id, to.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "redundant"), true.into_val(&env)),
+                        (Symbol::new(&env, "viewer_count"), 1u32.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(client.list_viewers(&escrow_id), vec![&env, to.clone()]);
+}
+
+#[test]
+fn test_duplicate_viewers_use_no_slot() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    let first = Address::generate(&env);
+    client.add_viewer(&escrow_id, &from, &first);
+    for _ in 0..7 {
+        client.add_viewer(&escrow_id, &from, &Address::generate(&env));
+    }
+    assert_eq!(client.list_viewers(&escrow_id).len(), 8);
+
+    // Re-adding at the cap succeeds quietly instead of failing
+    client.add_viewer(&escrow_id, &from, &first);
+    assert_eq!(env.events().all(), vec![&env]);
+    assert_eq!(client.list_viewers(&escrow_id).len(), 8);
+
+    let result = client.try_add_viewer(&escrow_id, &from, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(QuickexError::TooManyViewers)));
+}
+
+#[test]
+fn test_split_resolution_full_awards() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // All to the sender
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_001, None);
+    client.open_dispute(&from, &escrow_id);
+    client.resolve_dispute_split(&escrow_id, &arbiter, &0);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 1_101);
+    assert_eq!(token_client.balance(&to), 100);
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(escrow.split_bps, Some(0));
+
+    // All to the recipient
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_001, None);
+    client.open_dispute(&from, &escrow_id);
+    client.resolve_dispute_split(&escrow_id, &arbiter, &10_000);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 100);
+    assert_eq!(token_client.balance(&to), 1_101);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+
+    let result = client.try_resolve_dispute_split(&escrow_id, &arbiter, &5_000);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyReleased)));
+}
+
+#[test]
+fn test_split_resolution_takes_fee_then_splits_odd_amount() {
+    let (env, client) = setup();
+    let (from, to, arbiter, token, escrow_id) =
+        setup_arbitrated_escrow(&env, &client, 1_001, Some(100));
+    client.open_dispute(&to, &escrow_id);
+
+    let result = client.try_resolve_dispute_split(&escrow_id, &arbiter, &10_001);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidFeeBps)));
+
+    // Fee of 10 first, then a third of the remaining 991 to the recipient
+    client.resolve_dispute_split(&escrow_id, &arbiter, &3_333);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &to, 330),
+            transfer_event(&env, &token, &client.address, &from, 661),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "DisputeSplit"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "to_recipient"), 330i128.into_val(&env)),
+                        (Symbol::new(&env, "to_sender"), 661i128.into_val(&env)),
+                        (Symbol::new(&env, "fee"), 10i128.into_val(&env)),
+                        (
+                            Symbol::new(&env, "bond_to"),
+                            Some(to.clone()).into_val(&env)
+                        ),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&to), 430);
+    assert_eq!(token_client.balance(&from), 761);
+    assert_eq!(client.get_collected_fees(&token), 10);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().split_bps,
+        Some(3_333)
+    );
+}
+
+#[test]
+fn test_zero_split_refunds_without_fee() {
+    let (env, client) = setup();
+    let (from, to, arbiter, token, escrow_id) =
+        setup_arbitrated_escrow(&env, &client, 1_001, Some(100));
+    client.open_dispute(&to, &escrow_id);
+
+    let preview = client.preview_settlement(&escrow_id, &Resolution::Split(0));
+    assert_eq!(preview.protocol_fee, 0);
+    assert_eq!(preview.to_sender, 1_001);
+
+    // Same payout as resolve_dispute refunding the sender
+    client.resolve_dispute_split(&escrow_id, &arbiter, &0);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&from), 1_101);
+    assert_eq!(token_client.balance(&to), 100);
+    assert_eq!(client.get_collected_fees(&token), 0);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Refunded
+    );
+}
+
+#[test]
+fn test_frozen_escrow_blocks_settlement_until_unfrozen() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+
+    let result = client.try_freeze_escrow(&arbiter, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.freeze_escrow(&to, &escrow_id);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Frozen)
+    );
+
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+    let result = client.try_release_escrow(&admin, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+    let result = client.try_open_dispute(&to, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+    let result = client.try_freeze_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+
+    // The other party can't lift it; the one who froze it can
+    let result = client.try_unfreeze_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.unfreeze_escrow(&to, &escrow_id);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Pending)
+    );
+    let result = client.try_unfreeze_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotFrozen)));
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Released)
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_100);
+}
+
+#[test]
+fn test_mutual_freeze_lifted_by_either_party() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+
+    client.freeze_escrow_mutual(&escrow_id);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(signer, _)| *signer == from));
+    assert!(auths.iter().any(|(signer, _)| *signer == to));
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Frozen)
+    );
+
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+    let result = client.try_freeze_escrow_mutual(&escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+
+    // Either party can lift it on their own; an outsider can't
+    let result = client.try_unfreeze_escrow(&Address::generate(&env), &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.unfreeze_escrow(&to, &escrow_id);
+
+    client.freeze_escrow_mutual(&escrow_id);
+    client.unfreeze_escrow(&arbiter, &escrow_id);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_100);
+}
+
+#[test]
+fn test_arbiter_can_lift_a_party_freeze() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+
+    client.freeze_escrow(&from, &escrow_id);
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFrozen)));
+
+    // With an arbiter set the admin can't step in
+    let result = client.try_unfreeze_escrow(&admin, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.unfreeze_escrow(&arbiter, &escrow_id);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_100);
+}
+
+#[test]
+fn test_admin_cancel_escrow_refunds_with_reason() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 700);
+    client.initialize(&admin);
+    env.ledger().set_timestamp(99);
+    let escrow_id = client.create_escrow(&from, &to, &token, &700, &Bytes::new(&env));
+
+    let reason = Symbol::new(&env, "fraud_report");
+    client.admin_cancel_escrow(&admin, &escrow_id, &reason);
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &from, 700),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowRefunded"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "timestamp"), 99u64.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowCancelled"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "reason"), reason.into_val(&env)),
+                        (Symbol::new(&env, "timestamp"), 99u64.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 700);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Refunded)
+    );
+
+    let result = client.try_admin_cancel_escrow(&admin, &escrow_id, &reason);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+    let result = client.try_admin_cancel_escrow(&from, &escrow_id, &reason);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_max_lifetime_rejects_far_deadlines_and_fills_missing_ones() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let token = create_funded_token(&env, &merchant, 1_000);
+    client.initialize(&admin);
+    client.set_max_escrow_lifetime_secs(&admin, &86_400);
+    env.ledger().set_timestamp(1_000);
+
+    let template = EscrowTemplate {
+        recipient: Address::generate(&env),
+        token: token.clone(),
+        arbiter: None,
+        deadline_offset: 86_401,
+        fee_bps: None,
+    };
+    let too_long = client.create_template(&merchant, &template);
+    let result = client.try_create_from_template(&merchant, &too_long, &100);
+    assert_eq!(result, Err(Ok(QuickexError::DeadlineTooFar)));
+
+    let at_limit = client.create_template(
+        &merchant,
+        &EscrowTemplate {
+            deadline_offset: 86_400,
+            ..template
+        },
+    );
+    let escrow_id = client.create_from_template(&merchant, &at_limit, &100);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().deadline,
+        Some(87_400)
+    );
+
+    let open_ended = client.create_escrow(
+        &merchant,
+        &Address::generate(&env),
+        &token,
+        &100,
+        &Bytes::new(&env),
+    );
+    assert_eq!(
+        client.get_escrow(&open_ended).unwrap().deadline,
+        Some(87_400)
+    );
+}
+
+#[test]
+fn test_force_expire_after_lifetime() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 500);
+    client.initialize(&admin);
+    client.set_max_escrow_lifetime_secs(&admin, &3_600);
+    env.ledger().set_timestamp(10_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &500, &Bytes::new(&env));
+
+    env.ledger().set_timestamp(13_599);
+    let result = client.try_force_expire(&escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotExpired)));
+
+    env.ledger().set_timestamp(13_600);
+    client.force_expire(&escrow_id);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &from, 500),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowRefunded"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "timestamp"), 13_600u64.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowExpired"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "timestamp"), 13_600u64.into_val(&env))]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 500);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Expired)
+    );
+    let result = client.try_force_expire(&escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidStateTransition)));
+}
+
+#[test]
+fn test_release_split_amounts() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    client.release_split_amounts(&to, &escrow_id, &600, &400);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(signer, _)| *signer == from));
+    assert!(auths.iter().any(|(signer, _)| *signer == to));
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&to), 600);
+    assert_eq!(token_client.balance(&from), 400);
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Released)
+    );
+}
+
+#[test]
+fn test_release_split_amounts_mismatched_sum() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let result = client.try_release_split_amounts(&from, &escrow_id, &600, &300);
+    assert_eq!(result, Err(Ok(QuickexError::SplitMismatch)));
+    let result = client.try_release_split_amounts(&from, &escrow_id, &1_100, &-100);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+    let result = client.try_release_split_amounts(&Address::generate(&env), &escrow_id, &500, &500);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    assert_eq!(
+        client.get_escrow_status(&escrow_id),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+#[test]
+fn test_release_split_amounts_leaves_disputes_to_the_arbiter() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+
+    let template = EscrowTemplate {
+        recipient: to.clone(),
+        token: token.clone(),
+        arbiter: Some(Address::generate(&env)),
+        deadline_offset: 3_600,
+        fee_bps: None,
+    };
+    let template_id = client.create_template(&from, &template);
+    let escrow_id = client.create_from_template(&from, &template_id, &1_000);
+    client.open_dispute(&from, &escrow_id);
+
+    let result = client.try_release_split_amounts(&admin, &escrow_id, &600, &400);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    // The parties can still settle it between themselves
+    client.release_split_amounts(&from, &escrow_id, &600, &400);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 600);
+}
+
+#[test]
+fn test_escrow_handles_resolve_and_hide_ordering() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 300);
+    client.initialize(&admin);
+
+    // Handles need the salt first
+    let result = client.try_create_escrow_with_handle(&from, &to, &token, &100, &Bytes::new(&env));
+    assert_eq!(result, Err(Ok(QuickexError::HandleSaltNotSet)));
+
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    client.initialize_handle_salt(&admin, &salt);
+    let result = client.try_initialize_handle_salt(&admin, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::AlreadyInitialized)));
+
+    let first = client.create_escrow_with_handle(&from, &to, &token, &100, &memo(&env, "a"));
+    let second = client.create_escrow_with_handle(&from, &to, &token, &200, &memo(&env, "b"));
+
+    assert_eq!(client.get_escrow_by_handle(&first).unwrap().amount, 100);
+    assert_eq!(client.get_escrow_by_handle(&second).unwrap().amount, 200);
+    assert_eq!(
+        client.get_escrow_status_by_handle(&second),
+        Some(EscrowStatus::Pending)
+    );
+
+    // Consecutive handles don't share a prefix
+    let (a, b) = (first.to_array(), second.to_array());
+    assert_ne!(a[..4], b[..4]);
+
+    client.release_escrow_by_handle(&from, &second);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 200);
+    assert_eq!(
+        client.get_escrow_status_by_handle(&second),
+        Some(EscrowStatus::Released)
+    );
+
+    let unknown = BytesN::from_array(&env, &[1; 32]);
+    assert!(client.get_escrow_by_handle(&unknown).is_none());
+    let result = client.try_release_escrow_by_handle(&from, &unknown);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotFound)));
+}
+
+#[test]
+fn test_rotate_handle_salt_keeps_old_handles() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 300);
+    client.initialize(&admin);
+
+    assert_eq!(
+        client.try_rotate_handle_salt(&admin),
+        Err(Ok(QuickexError::HandleSaltNotSet))
+    );
+
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    client.initialize_handle_salt(&admin, &salt);
+    let before = client.create_escrow_with_handle(&from, &to, &token, &100, &memo(&env, "a"));
+    assert_eq!(client.handle_salt_version(), 0);
+
+    assert_eq!(
+        client.try_rotate_handle_salt(&from),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+    assert_eq!(client.rotate_handle_salt(&admin), 1);
+    assert_eq!(client.handle_salt_version(), 1);
+
+    let after = client.create_escrow_with_handle(&from, &to, &token, &200, &memo(&env, "b"));
+
+    // The new escrow's handle no longer comes from the old salt
+    let mut payload = Bytes::from_slice(&env, &2u64.to_be_bytes());
+    payload.append(&salt.clone().into());
+    let old_salt_handle: BytesN<32> = env.crypto().sha256(&payload).into();
+    assert_ne!(after, old_salt_handle);
+
+    // Both generations resolve
+    assert_eq!(client.get_escrow_by_handle(&before).unwrap().amount, 100);
+    assert_eq!(client.get_escrow_by_handle(&after).unwrap().amount, 200);
+    client.release_escrow_by_handle(&from, &before);
+    assert_eq!(
+        client.get_escrow_status_by_handle(&before),
+        Some(EscrowStatus::Released)
+    );
+}
+
+#[test]
+fn test_aggregate_report_over_two_days() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 600);
+    client.initialize(&admin);
+
+    // Day 10: one release and one cancellation
+    env.ledger().set_sequence_number(10 * 17_280 + 5);
+    let a = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let b = client.create_escrow(&from, &to, &token, &200, &Bytes::new(&env));
+    client.release_escrow(&from, &a);
+    client.admin_cancel_escrow(&admin, &b, &Symbol::new(&env, "support"));
+
+    // Day 11: one release, plus one escrow still pending
+    env.ledger().set_sequence_number(11 * 17_280 + 100);
+    let c = client.create_escrow(&from, &to, &token, &250, &Bytes::new(&env));
+    client.create_escrow(&from, &to, &token, &50, &Bytes::new(&env));
+    client.release_escrow(&from, &c);
+
+    let day_10 = client.get_settlement_bucket(&token, &10);
+    assert_eq!((day_10.settled_count, day_10.volume), (2, 300));
+    let day_11 = client.get_settlement_bucket(&token, &11);
+    assert_eq!((day_11.settled_count, day_11.volume), (1, 250));
+
+    let report = client.get_aggregate_report(&token, &(10 * 17_280), &(12 * 17_280 - 1));
+    assert_eq!(report.from_epoch, 10);
+    assert_eq!(report.to_epoch, 11);
+    assert_eq!(report.settled_count, 3);
+    assert_eq!(report.total_volume, 550);
+
+    let report = client.get_aggregate_report(&token, &(11 * 17_280), &(11 * 17_280));
+    assert_eq!((report.settled_count, report.total_volume), (1, 250));
+
+    let result = client.try_get_aggregate_report(&token, &(11 * 17_280), &(10 * 17_280));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidReportWindow)));
+
+    // Old buckets can be pruned once reported
+    client.prune_settlement_buckets(&admin, &token, &0, &10);
+    let report = client.get_aggregate_report(&token, &(10 * 17_280), &(12 * 17_280 - 1));
+    assert_eq!((report.settled_count, report.total_volume), (1, 250));
+}
+
+#[test]
+fn test_cooling_off_blocks_large_escrows_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 20_000);
+    client.initialize(&admin);
+    client.set_cooling_off(&admin, &10_000, &3_600);
+    env.ledger().set_timestamp(1_000);
+
+    let large = client.create_escrow(&from, &to, &token, &10_001, &Bytes::new(&env));
+    let small = client.create_escrow(&from, &to, &token, &9_999, &Bytes::new(&env));
+
+    let result = client.try_release_escrow(&from, &large);
+    assert_eq!(result, Err(Ok(QuickexError::CoolingOffActive)));
+    client.release_escrow(&from, &small);
+
+    env.ledger().set_timestamp(4_599);
+    let result = client.try_release_escrow(&admin, &large);
+    assert_eq!(result, Err(Ok(QuickexError::CoolingOffActive)));
+
+    env.ledger().set_timestamp(4_600);
+    client.release_escrow(&from, &large);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 20_000);
+}
+
+#[test]
+fn test_cooling_off_applies_to_split_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 20_002);
+    client.initialize(&admin);
+    client.set_cooling_off(&admin, &10_000, &3_600);
+    env.ledger().set_timestamp(1_000);
+
+    let large = client.create_escrow(&from, &to, &token, &10_001, &Bytes::new(&env));
+    let refunded = client.create_escrow(&from, &to, &token, &10_001, &Bytes::new(&env));
+
+    // A 100/0 split is a release and waits out the delay too
+    assert_eq!(
+        client.try_release_split_amounts(&to, &large, &10_001, &0),
+        Err(Ok(QuickexError::CoolingOffActive))
+    );
+    client.release_split_amounts(&to, &refunded, &0, &10_001);
+
+    env.ledger().set_timestamp(4_600);
+    client.release_split_amounts(&to, &large, &10_001, &0);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 10_001);
+}
+
+#[test]
+fn test_escrow_disabled_until_enabled() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    client.initialize_with_features(
+        &admin,
+        &FeatureFlags {
+            escrow_enabled: false,
+            commitments_enabled: true,
+            privacy_enabled: true,
+        },
+    );
+
+    let result = client.try_create_escrow(&from, &to, &token, &500, &memo(&env, "off"));
+    assert_eq!(result, Err(Ok(QuickexError::FeatureDisabled)));
+    assert_eq!(client.get_escrow_count(), 0);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+
+    // False entries never switch a module back off
+    client.enable_features(
+        &admin,
+        &FeatureFlags {
+            escrow_enabled: true,
+            commitments_enabled: false,
+            privacy_enabled: false,
+        },
+    );
+    let flags = client.get_feature_flags();
+    assert!(flags.escrow_enabled && flags.commitments_enabled && flags.privacy_enabled);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &500, &memo(&env, "on"));
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Pending
+    );
+}
+
+#[test]
+fn test_enable_features_requires_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let flags = FeatureFlags {
+        escrow_enabled: false,
+        commitments_enabled: false,
+        privacy_enabled: false,
+    };
+    client.initialize_with_features(&admin, &flags);
+
+    let result = client.try_enable_features(
+        &stranger,
+        &FeatureFlags {
+            escrow_enabled: true,
+            ..flags
+        },
+    );
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    let owner = Address::generate(&env);
+    assert_eq!(
+        client.try_set_privacy(&owner, &true),
+        Err(Ok(QuickexError::FeatureDisabled))
+    );
+    assert_eq!(client.metadata().features, 0);
+}
+
+fn setup_swap(
+    env: &Env,
+    client: &QuickexContractClient,
+    amount_in: i128,
+    num: i128,
+    den: i128,
+) -> (Address, Address, Address, Address, u64) {
+    let from = Address::generate(env);
+    let to = Address::generate(env);
+    let token_in = create_funded_token(env, &from, amount_in);
+    let token_out = create_funded_token(env, &to, 10_000);
+
+    let rate = client.create_rate_commitment(&num, &den, &memo(env, "rate-salt"));
+    let escrow_id = client.create_swap_escrow(&from, &to, &token_in, &token_out, &amount_in, &rate);
+    (from, to, token_in, token_out, escrow_id)
+}
+
+#[test]
+fn test_settle_swap_at_committed_rate() {
+    let (env, client) = setup();
+    let (from, to, token_in, token_out, escrow_id) = setup_swap(&env, &client, 1_000, 3, 2);
+
+    let amount_out = client.settle_swap(&escrow_id, &3, &2, &memo(&env, "rate-salt"));
+    assert_eq!(amount_out, 1_500);
+
+    let token_in = token::Client::new(&env, &token_in);
+    let token_out = token::Client::new(&env, &token_out);
+    assert_eq!(token_in.balance(&to), 1_000);
+    assert_eq!(token_in.balance(&client.address), 0);
+    assert_eq!(token_out.balance(&from), 1_500);
+    assert_eq!(token_out.balance(&to), 8_500);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+
+    let again = client.try_settle_swap(&escrow_id, &3, &2, &memo(&env, "rate-salt"));
+    assert_eq!(again, Err(Ok(QuickexError::EscrowAlreadyReleased)));
+}
+
+#[test]
+fn test_settle_swap_rounds_down() {
+    let (env, client) = setup();
+    let (from, _, _, token_out, escrow_id) = setup_swap(&env, &client, 1_000, 1, 3);
+
+    let amount_out = client.settle_swap(&escrow_id, &1, &3, &memo(&env, "rate-salt"));
+    assert_eq!(amount_out, 333);
+    assert_eq!(token::Client::new(&env, &token_out).balance(&from), 333);
+}
+
+#[test]
+fn test_settle_swap_wrong_rate_fails() {
+    let (env, client) = setup();
+    let (_, to, token_in, _, escrow_id) = setup_swap(&env, &client, 1_000, 3, 2);
+
+    let result = client.try_settle_swap(&escrow_id, &2, &1, &memo(&env, "rate-salt"));
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentMismatch)));
+
+    let result = client.try_settle_swap(&escrow_id, &3, &2, &memo(&env, "other-salt"));
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentMismatch)));
+
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Pending
+    );
+    assert_eq!(token::Client::new(&env, &token_in).balance(&to), 0);
+}
+
+#[test]
+fn test_settle_swap_overflow_fails() {
+    let (env, client) = setup();
+    let (_, _, _, _, escrow_id) = setup_swap(&env, &client, 1_000, i128::MAX, 1);
+
+    let result = client.try_settle_swap(&escrow_id, &i128::MAX, &1, &memo(&env, "rate-salt"));
+    assert_eq!(result, Err(Ok(QuickexError::AmountOverflow)));
+}
+
+/// Tranche escrow of 900 with three funders each holding 1_000
+fn setup_shared_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+) -> (Address, Address, [Address; 3], u64) {
+    let from = Address::generate(env);
+    let to = Address::generate(env);
+    let funders = [
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+    let token = create_funded_token(env, &funders[0], 1_000);
+    for funder in &funders[1..] {
+        token::StellarAssetClient::new(env, &token).mint(funder, &1_000);
+    }
+    let escrow_id = client.create_tranche_escrow(&from, &to, &token, &900, &memo(env, "group"));
+    (to, token, funders, escrow_id)
+}
+
+#[test]
+fn test_shared_funding_releases_total_to_recipient() {
+    let (env, client) = setup();
+    let (to, token, funders, escrow_id) = setup_shared_escrow(&env, &client);
+    let token_client = token::Client::new(&env, &token);
+
+    assert_eq!(
+        client.fund_escrow_shared(&escrow_id, &funders[0], &200),
+        200
+    );
+    assert_eq!(
+        client.fund_escrow_shared(&escrow_id, &funders[1], &300),
+        500
+    );
+    assert_eq!(
+        client.fund_escrow_shared(&escrow_id, &funders[0], &100),
+        600
+    );
+
+    // Past the target is refused; exactly the rest completes funding
+    let result = client.try_fund_escrow_shared(&escrow_id, &funders[2], &301);
+    assert_eq!(result, Err(Ok(QuickexError::Overfunded)));
+    assert_eq!(
+        client.fund_escrow_shared(&escrow_id, &funders[2], &300),
+        900
+    );
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    let contributions = client.get_escrow_funders(&escrow_id);
+    assert_eq!(contributions.get(funders[0].clone()), Some(300));
+    assert_eq!(contributions.get(funders[1].clone()), Some(300));
+    assert_eq!(contributions.get(funders[2].clone()), Some(300));
+
+    client.release_escrow(&escrow.from, &escrow_id);
+    assert_eq!(token_client.balance(&to), 900);
+}
+
+#[test]
+fn test_shared_funding_refund_returns_each_contribution() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (_, token, funders, escrow_id) = setup_shared_escrow(&env, &client);
+    let token_client = token::Client::new(&env, &token);
+
+    client.fund_escrow_shared(&escrow_id, &funders[0], &100);
+    client.fund_escrow_shared(&escrow_id, &funders[1], &250);
+    client.fund_escrow_shared(&escrow_id, &funders[2], &550);
+
+    client.admin_cancel_escrow(&admin, &escrow_id, &Symbol::new(&env, "cancelled"));
+    for funder in &funders {
+        assert_eq!(token_client.balance(funder), 1_000);
+    }
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_shared_funding_caps_funders() {
+    let (env, client) = setup();
+    let (_, token, funders, escrow_id) = setup_shared_escrow(&env, &client);
+    let asset = token::StellarAssetClient::new(&env, &token);
+
+    for _ in 0..16 {
+        let funder = Address::generate(&env);
+        asset.mint(&funder, &1);
+        client.fund_escrow_shared(&escrow_id, &funder, &1);
+    }
+    let result = client.try_fund_escrow_shared(&escrow_id, &funders[0], &1);
+    assert_eq!(result, Err(Ok(QuickexError::TooManyFunders)));
+}
+
+#[test]
+fn test_fund_escrow_in_tranches() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let escrow_id = client.create_tranche_escrow(&from, &to, &token, &1_000, &memo(&env, "t"));
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Funding);
+    assert_eq!(escrow.funded, 0);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    // Not releasable until fully funded
+    let early = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(early, Err(Ok(QuickexError::InvalidStateTransition)));
+
+    assert_eq!(client.fund_escrow_partial(&from, &escrow_id, &400), 400);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Funding
+    );
+
+    assert_eq!(client.fund_escrow_partial(&from, &escrow_id, &600), 1_000);
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    assert_eq!(escrow.funded, 1_000);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token_client.balance(&to), 1_000);
+}
+
+#[test]
+fn test_fund_escrow_partial_overfunded_fails() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+
+    let escrow_id = client.create_tranche_escrow(&from, &to, &token, &1_000, &memo(&env, "t"));
+    client.fund_escrow_partial(&from, &escrow_id, &700);
+
+    let result = client.try_fund_escrow_partial(&from, &escrow_id, &301);
+    assert_eq!(result, Err(Ok(QuickexError::Overfunded)));
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().funded, 700);
+}
+
+#[test]
+fn test_admin_cancel_partially_funded_escrow_refunds_tranches() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_tranche_escrow(&from, &to, &token, &1_000, &memo(&env, "t"));
+    client.fund_escrow_partial(&from, &escrow_id, &250);
+
+    client.admin_cancel_escrow(&admin, &escrow_id, &Symbol::new(&env, "abandoned"));
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Refunded
+    );
+}
+
+fn config(dispute_bond_bps: u32, cooling_off_threshold: i128) -> ContractConfig {
+    ContractConfig {
+        dispute_bond_bps,
+        insurance_share_bps: 2_000,
+        max_escrow_lifetime_secs: 86_400,
+        cooling_off_threshold,
+        cooling_off_delay: 3_600,
+        nullifier_retention_secs: 7 * 86_400,
+        salt_recovery_delay_secs: 86_400,
+    }
+}
+
+#[test]
+fn test_validate_config_matches_set_config() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let before = client.get_config();
+
+    for bad in [config(10_001, 0), config(100, -1)] {
+        let checked = client.try_validate_config(&bad);
+        let applied = client.try_set_config(&admin, &bad);
+        assert!(checked.is_err());
+        assert_eq!(checked, applied);
+    }
+    assert_eq!(client.get_config(), before);
+
+    let good = config(500, 1_000);
+    client.validate_config(&good);
+    assert_eq!(client.get_config(), before);
+
+    client.set_config(&admin, &good);
+    assert_eq!(client.get_config(), good);
+    assert_eq!(client.max_escrow_lifetime_secs(), 86_400);
+}
+
+#[test]
+fn test_validate_escrow_batch_matches_create() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let item = |amount: i128, memo: Bytes| EscrowBatchItem {
+        to: Address::generate(&env),
+        token: token.clone(),
+        amount,
+        memo,
+    };
+
+    let long_memo = Bytes::from_array(&env, &[b'x'; 65]);
+    let bad = vec![&env, item(100, memo(&env, "ok")), item(100, long_memo)];
+    assert_eq!(
+        client.try_validate_escrow_batch(&from, &bad),
+        Err(Ok(QuickexError::MemoTooLong))
+    );
+    assert_eq!(
+        client.try_create_escrow_batch(&from, &bad),
+        Err(Ok(QuickexError::MemoTooLong))
+    );
+    assert_eq!(client.get_escrow_count(), 0);
+
+    let empty = soroban_sdk::Vec::new(&env);
+    assert_eq!(
+        client.try_validate_escrow_batch(&from, &empty),
+        Err(Ok(QuickexError::InvalidBatch))
+    );
+
+    let good = vec![&env, item(300, memo(&env, "a")), item(700, memo(&env, "b"))];
+    client.validate_escrow_batch(&from, &good);
+    assert_eq!(client.get_escrow_count(), 0);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+
+    let ids = client.create_escrow_batch(&from, &good);
+    assert_eq!(
+        ids,
+        vec![&env, RESERVED_ESCROW_IDS + 1, RESERVED_ESCROW_IDS + 2]
+    );
+    assert_eq!(client.get_escrow(&ids.get(1).unwrap()).unwrap().amount, 700);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 0);
+}
+
+#[test]
+fn test_is_party() {
+    let (env, client) = setup();
+    let (from, to, arbiter, _, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    let outsider = Address::generate(&env);
+
+    assert!(client.is_party(&escrow_id, &from));
+    assert!(client.is_party(&escrow_id, &to));
+    assert!(client.is_party(&escrow_id, &arbiter));
+    assert!(!client.is_party(&escrow_id, &outsider));
+    assert!(!client.is_party(&(escrow_id + 1), &from));
+}
+
+/// Token that skims 1% of every transfer, like a fee-on-transfer asset
+mod skim_token {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    pub const SKIM_BPS: i128 = 100;
+
+    #[contract]
+    pub struct SkimToken;
+
+    #[contractimpl]
+    impl SkimToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().persistent().get(&id).unwrap_or(0)
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let skim = amount * SKIM_BPS / 10_000;
+            let from_balance = Self::balance(env.clone(), from.clone());
+            env.storage()
+                .persistent()
+                .set(&from, &(from_balance - amount));
+            Self::mint(env, to, amount - skim);
+        }
+    }
+}
+
+#[test]
+fn test_fee_on_transfer_token_books_received_amount() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = env.register(skim_token::SkimToken, ());
+    let skim = skim_token::SkimTokenClient::new(&env, &token);
+    skim.mint(&from, &1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "FundingShortfall"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "requested"), 1_000i128.into_val(&env)),
+                        (Symbol::new(&env, "received"), 990i128.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowCreated"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), to.into_val(&env)),
+                        (Symbol::new(&env, "token"), token.into_val(&env)),
+                        (Symbol::new(&env, "amount"), 990i128.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.amount, 990);
+    assert_eq!(skim.balance(&client.address), 990);
+
+    // Release pays out exactly what the contract holds
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(skim.balance(&client.address), 0);
+    assert_eq!(skim.balance(&to), 981);
+}
+
+#[test]
+fn test_fee_on_transfer_tranche_counts_received_amount() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = env.register(skim_token::SkimToken, ());
+    skim_token::SkimTokenClient::new(&env, &token).mint(&from, &2_000);
+
+    let escrow_id = client.create_tranche_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(client.fund_escrow_partial(&from, &escrow_id, &500), 495);
+
+    // 505 is still missing from the target
+    let result = client.try_fund_escrow_partial(&from, &escrow_id, &506);
+    assert_eq!(result, Err(Ok(QuickexError::Overfunded)));
+
+    // Paying the full remainder activates the escrow at what actually arrived
+    assert_eq!(client.fund_escrow_partial(&from, &escrow_id, &505), 995);
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Pending);
+    assert_eq!(escrow.amount, 995);
+}
+
+#[test]
+fn test_pull_escrows_credit_then_withdraw_once() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let first = client.create_pull_escrow(&from, &to, &token, &300, &memo(&env, "a"));
+    let second = client.create_pull_escrow(&from, &to, &token, &700, &memo(&env, "b"));
+
+    client.release_escrow(&from, &first);
+    client.release_escrow(&from, &second);
+
+    assert_eq!(token_client.balance(&to), 0);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+    assert_eq!(client.get_withdrawable(&to, &token), 1_000);
+
+    assert_eq!(client.withdraw_credit(&to, &token), 1_000);
+    assert_eq!(token_client.balance(&to), 1_000);
+    assert_eq!(client.get_withdrawable(&to, &token), 0);
+
+    let again = client.try_withdraw_credit(&to, &token);
+    assert_eq!(again, Err(Ok(QuickexError::NothingToWithdraw)));
+}
+
+#[test]
+fn test_withdraw_whitelist_limits_destinations() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let escrow_id = client.create_pull_escrow(&from, &to, &token, &1_000, &memo(&env, "a"));
+    client.release_escrow(&from, &escrow_id);
+
+    let exchange = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.add_withdraw_destination(&to, &exchange);
+    client.set_withdraw_whitelist(&to, &true);
+
+    let result = client.try_withdraw_credit_to(&to, &token, &stranger);
+    assert_eq!(result, Err(Ok(QuickexError::DestinationNotWhitelisted)));
+    assert_eq!(client.get_withdrawable(&to, &token), 1_000);
+
+    assert_eq!(client.withdraw_credit_to(&to, &token, &exchange), 1_000);
+    assert_eq!(token_client.balance(&exchange), 1_000);
+    assert_eq!(client.get_withdrawable(&to, &token), 0);
+}
+
+#[test]
+fn test_withdraw_whitelist_opt_out_and_revoke() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let first = client.create_pull_escrow(&from, &to, &token, &400, &memo(&env, "a"));
+    let second = client.create_pull_escrow(&from, &to, &token, &600, &memo(&env, "b"));
+    client.release_escrow(&from, &first);
+
+    let exchange = Address::generate(&env);
+    client.set_withdraw_whitelist(&to, &true);
+    client.add_withdraw_destination(&to, &exchange);
+    client.remove_withdraw_destination(&to, &exchange);
+
+    let result = client.try_withdraw_credit_to(&to, &token, &exchange);
+    assert_eq!(result, Err(Ok(QuickexError::DestinationNotWhitelisted)));
+
+    // The account itself is always an allowed destination
+    assert_eq!(client.withdraw_credit(&to, &token), 400);
+    assert_eq!(token_client.balance(&to), 400);
+
+    // Opting out lets credit go anywhere again
+    client.release_escrow(&from, &second);
+    client.set_withdraw_whitelist(&to, &false);
+    assert_eq!(client.withdraw_credit_to(&to, &token, &exchange), 600);
+    assert_eq!(token_client.balance(&exchange), 600);
+}
+
+#[test]
+fn test_push_escrow_still_pays_on_release() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 500);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &500, &memo(&env, "push"));
+    client.release_escrow(&from, &escrow_id);
+
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 500);
+    assert_eq!(client.get_withdrawable(&to, &token), 0);
+}
+
+#[test]
+fn test_escrow_created_event_can_be_skipped() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let quiet =
+        client.create_escrow_with_event(&from, &to, &token, &400, &Bytes::new(&env), &false);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        vec![&env]
+    );
+    assert_eq!(client.get_event_log_bounds(), (0, 0));
+    assert!(!client.get_escrow(&quiet).unwrap().emit_event);
+
+    let loud = client.create_escrow_with_event(&from, &to, &token, &600, &Bytes::new(&env), &true);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowCreated"), loud).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "amount"), 600i128.into_val(&env)),
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "to"), to.into_val(&env)),
+                        (Symbol::new(&env, "token"), token.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    assert_eq!(client.get_event_log_bounds(), (0, 1));
+}
+
+#[test]
+fn test_event_log_pages_and_prunes() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    env.ledger().set_timestamp(100);
+
+    let first = client.create_escrow(&from, &to, &token, &400, &memo(&env, "a"));
+    let second = client.create_escrow(&from, &to, &token, &600, &memo(&env, "b"));
+    client.release_escrow(&from, &first);
+    client.admin_cancel_escrow(&admin, &second, &Symbol::new(&env, "support"));
+
+    let kinds = [
+        ("created", first),
+        ("created", second),
+        ("released", first),
+        ("refunded", second),
+        ("cancelled", second),
+    ];
+    assert_eq!(client.get_event_log_bounds(), (0, kinds.len() as u64));
+
+    let page_one = client.get_event_log(&0, &3);
+    let page_two = client.get_event_log(&3, &3);
+    assert_eq!(page_one.len(), 3);
+    assert_eq!(page_two.len(), 2);
+    for (i, (kind, subject)) in kinds.iter().enumerate() {
+        let summary = if i < 3 {
+            page_one.get_unchecked(i as u32)
+        } else {
+            page_two.get_unchecked(i as u32 - 3)
+        };
+        assert_eq!(summary.kind, Symbol::new(&env, kind));
+        assert_eq!(summary.subject, *subject);
+        assert_eq!(summary.timestamp, 100);
+    }
+
+    assert_eq!(client.prune_event_log(&admin, &2), 2);
+    assert_eq!(client.get_event_log_bounds(), (2, 5));
+    let remaining = client.get_event_log(&0, &10);
+    assert_eq!(remaining.len(), 3);
+    assert_eq!(
+        remaining.get_unchecked(0).kind,
+        Symbol::new(&env, "released")
+    );
+}
+
+/// Yield sources for the adapter tests: one that pays a fixed bonus and one
+/// that rejects every deposit
+mod yield_mock {
+    use soroban_sdk::{
+        contract, contractimpl, panic_with_error, symbol_short, token, Address, Env,
+    };
+
+    #[contract]
+    pub struct FixedYieldAdapter;
+
+    #[contractimpl]
+    impl FixedYieldAdapter {
+        pub fn __constructor(env: Env, token: Address, bonus: i128) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("token"), &token);
+            env.storage()
+                .instance()
+                .set(&symbol_short!("bonus"), &bonus);
+        }
+
+        pub fn deposit(env: Env, from: Address, amount: i128) {
+            from.require_auth();
+            Self::token(&env).transfer(&from, env.current_contract_address(), &amount);
+            Self::add_shares(&env, &from, amount);
+        }
+
+        pub fn withdraw(env: Env, to: Address, amount: i128) {
+            to.require_auth();
+            if env.storage().instance().has(&symbol_short!("frozen")) {
+                panic_with_error!(&env, crate::errors::QuickexError::Unauthorized);
+            }
+            Self::add_shares(&env, &to, -amount);
+            Self::token(&env).transfer(&env.current_contract_address(), &to, &amount);
+        }
+
+        pub fn balance_of(env: Env, owner: Address) -> i128 {
+            let shares: i128 = env.storage().persistent().get(&owner).unwrap_or(0);
+            let bonus: i128 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("bonus"))
+                .unwrap();
+            shares + bonus
+        }
+    }
+
+    impl FixedYieldAdapter {
+        pub fn set_frozen(env: &Env, frozen: bool) {
+            match frozen {
+                true => env.storage().instance().set(&symbol_short!("frozen"), &()),
+                false => env.storage().instance().remove(&symbol_short!("frozen")),
+            }
+        }
+
+        fn token(env: &Env) -> token::Client<'_> {
+            let token: Address = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("token"))
+                .unwrap();
+            token::Client::new(env, &token)
+        }
+
+        fn add_shares(env: &Env, owner: &Address, delta: i128) {
+            let shares: i128 = env.storage().persistent().get(owner).unwrap_or(0);
+            env.storage().persistent().set(owner, &(shares + delta));
+        }
+    }
+
+    #[contract]
+    pub struct BrokenYieldAdapter;
+
+    #[contractimpl]
+    impl BrokenYieldAdapter {
+        pub fn deposit(env: Env, _from: Address, _amount: i128) {
+            panic_with_error!(&env, crate::errors::QuickexError::Unauthorized);
+        }
+
+        pub fn withdraw(env: Env, _to: Address, _amount: i128) {
+            panic_with_error!(&env, crate::errors::QuickexError::Unauthorized);
+        }
+
+        pub fn balance_of(_env: Env, _owner: Address) -> i128 {
+            0
+        }
+    }
+}
+
+#[test]
+fn test_yield_adapter_returns_principal_and_harvests_yield() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let adapter = env.register(yield_mock::FixedYieldAdapter, (token.clone(), 50i128));
+    token::StellarAssetClient::new(&env, &token).mint(&adapter, &50);
+    client.set_yield_adapter(&admin, &token, &Some(adapter.clone()));
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "idle"));
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().yield_adapter,
+        Some(adapter.clone())
+    );
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert_eq!(token_client.balance(&adapter), 1_050);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token_client.balance(&to), 1_000);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    assert_eq!(client.harvest_yield(&admin, &token), 50);
+    assert_eq!(client.get_collected_fees(&token), 50);
+    assert_eq!(token_client.balance(&client.address), 50);
+    assert_eq!(token_client.balance(&adapter), 0);
+}
+
+#[test]
+fn test_failing_yield_adapter_falls_back_to_custody() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let adapter = env.register(yield_mock::BrokenYieldAdapter, ());
+    client.set_yield_adapter(&admin, &token, &Some(adapter));
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "safe"));
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().yield_adapter, None);
+    assert_eq!(token_client.balance(&client.address), 1_000);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token_client.balance(&to), 1_000);
+}
+
+#[test]
+fn test_yield_adapter_failing_withdraw_settles_from_custody() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    let token_client = token::Client::new(&env, &token);
+
+    let adapter = env.register(yield_mock::FixedYieldAdapter, (token.clone(), 0i128));
+    client.set_yield_adapter(&admin, &token, &Some(adapter.clone()));
+    let parked = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "parked"));
+    client.set_yield_adapter(&admin, &token, &None);
+    let held = client.create_escrow(&from, &to, &token, &1_000, &memo(&env, "held"));
+    assert_eq!(token_client.balance(&adapter), 1_000);
+
+    // A stuck adapter doesn't block settlement
+    env.as_contract(&adapter, || {
+        yield_mock::FixedYieldAdapter::set_frozen(&env, true)
+    });
+    client.release_escrow(&from, &parked);
+    assert_eq!(token_client.balance(&to), 1_000);
+    assert_eq!(client.get_escrow(&parked).unwrap().yield_adapter, None);
+    assert_eq!(client.get_yield_shortfall(&adapter), 1_000);
+    assert!(!client.get_reserve_proof(&token).solvent);
+
+    // Once the adapter pays again the principal comes back to custody,
+    // not to collected fees
+    env.as_contract(&adapter, || {
+        yield_mock::FixedYieldAdapter::set_frozen(&env, false)
+    });
+    client.set_yield_adapter(&admin, &token, &Some(adapter.clone()));
+    assert_eq!(client.harvest_yield(&admin, &token), 0);
+    assert_eq!(client.get_yield_shortfall(&adapter), 0);
+    assert_eq!(client.get_collected_fees(&token), 0);
+    assert!(client.get_reserve_proof(&token).solvent);
+
+    client.release_escrow(&from, &held);
+    assert_eq!(token_client.balance(&to), 2_000);
+}
+
+#[test]
+fn test_escrow_from_terms_faithful_reveal() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let terms = TermsPreimage {
+        recipient: Address::generate(&env),
+        token: token.clone(),
+        amount: 1_000,
+        deadline: Some(5_000),
+        arbiter: Some(arbiter.clone()),
+        fee_bps: Some(250),
+    };
+    let salt = Bytes::from_array(&env, &[7; 32]);
+
+    let commitment = client.create_terms_commitment(&from, &terms, &salt);
+    let escrow_id = client.create_escrow_from_terms(&from, &commitment, &terms, &salt);
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.from, from);
+    assert_eq!(escrow.to, terms.recipient);
+    assert_eq!(escrow.token, token);
+    assert_eq!(escrow.amount, 1_000);
+    assert_eq!(escrow.deadline, Some(5_000));
+    assert_eq!(escrow.arbiter, Some(arbiter));
+    assert_eq!(escrow.fee_bps, Some(250));
+    assert_eq!(
+        token::Client::new(&env, &token).balance(&client.address),
+        1_000
+    );
+}
+
+#[test]
+fn test_escrow_from_terms_rejects_tampering() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let terms = TermsPreimage {
+        recipient: Address::generate(&env),
+        token: token.clone(),
+        amount: 1_000,
+        deadline: Some(5_000),
+        arbiter: Some(Address::generate(&env)),
+        fee_bps: Some(250),
+    };
+    let salt = Bytes::from_array(&env, &[7; 32]);
+    let commitment = client.create_terms_commitment(&from, &terms, &salt);
+
+    let tampered = [
+        TermsPreimage {
+            recipient: Address::generate(&env),
+            ..terms.clone()
+        },
+        TermsPreimage {
+            token: create_funded_token(&env, &from, 1_000),
+            ..terms.clone()
+        },
+        TermsPreimage {
+            amount: 999,
+            ..terms.clone()
+        },
+        TermsPreimage {
+            deadline: None,
+            ..terms.clone()
+        },
+        TermsPreimage {
+            arbiter: Some(Address::generate(&env)),
+            ..terms.clone()
+        },
+        TermsPreimage {
+            fee_bps: Some(0),
+            ..terms.clone()
+        },
+    ];
+    for fake in tampered {
+        let result = client.try_create_escrow_from_terms(&from, &commitment, &fake, &salt);
+        assert_eq!(result, Err(Ok(QuickexError::TermsMismatch)));
+    }
+
+    // Wrong salt or a different sender doesn't open it either
+    let wrong_salt = Bytes::from_array(&env, &[8; 32]);
+    let result = client.try_create_escrow_from_terms(&from, &commitment, &terms, &wrong_salt);
+    assert_eq!(result, Err(Ok(QuickexError::TermsMismatch)));
+    let other = Address::generate(&env);
+    let result = client.try_create_escrow_from_terms(&other, &commitment, &terms, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::TermsMismatch)));
+
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+}
+
+#[test]
+fn test_remaining_daily_limit_tracks_releases() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 10_000);
+    client.initialize(&admin);
+    assert_eq!(client.remaining_daily_limit(), i128::MAX);
+
+    client.set_daily_outflow_cap(&admin, &1_000);
+    assert_eq!(client.remaining_daily_limit(), 1_000);
+
+    let memo = Bytes::new(&env);
+    let first = client.create_escrow(&from, &to, &token, &600, &memo);
+    let second = client.create_escrow(&from, &to, &token, &500, &memo);
+    let third = client.create_escrow(&from, &to, &token, &400, &memo);
+
+    client.release_escrow(&from, &first);
+    assert_eq!(client.remaining_daily_limit(), 400);
+
+    // A release over the headroom is blocked and uses none of it
+    let result = client.try_release_escrow(&from, &second);
+    assert_eq!(result, Err(Ok(QuickexError::DailyLimitExceeded)));
+    assert_eq!(
+        client.get_escrow(&second).unwrap().status,
+        EscrowStatus::Pending
+    );
+
+    client.release_escrow(&from, &third);
+    assert_eq!(client.remaining_daily_limit(), 0);
+
+    // The limit resets the next day
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 24 * 60 * 60);
+    assert_eq!(client.remaining_daily_limit(), 1_000);
+    client.release_escrow(&from, &second);
+    assert_eq!(client.remaining_daily_limit(), 500);
+}
+
+#[test]
+fn test_daily_limit_covers_splits_and_swaps() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (_, _, _, _, swap_id) = setup_swap(&env, &client, 1_000, 1, 1);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    let first = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    let second = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    client.set_daily_outflow_cap(&admin, &1_200);
+
+    // Only the recipient's share of a split counts towards the cap
+    client.release_split_amounts(&from, &first, &700, &300);
+    assert_eq!(client.remaining_daily_limit(), 500);
+
+    let salt = memo(&env, "rate-salt");
+    assert_eq!(
+        client.try_settle_swap(&swap_id, &1, &1, &salt),
+        Err(Ok(QuickexError::DailyLimitExceeded))
+    );
+    assert_eq!(
+        client.get_escrow_status(&swap_id),
+        Some(EscrowStatus::Pending)
+    );
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 24 * 60 * 60);
+    client.settle_swap(&swap_id, &1, &1, &salt);
+    assert_eq!(client.remaining_daily_limit(), 200);
+
+    assert_eq!(
+        client.try_release_split_amounts(&from, &second, &500, &500),
+        Err(Ok(QuickexError::DailyLimitExceeded))
+    );
+    // A split that pays the recipient nothing is a refund and isn't capped
+    client.release_split_amounts(&from, &second, &0, &1_000);
+    assert_eq!(client.remaining_daily_limit(), 200);
+}
+
+#[test]
+fn test_set_daily_outflow_cap_validation() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_daily_outflow_cap(&other, &1_000);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_set_daily_outflow_cap(&admin, &-1);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+}
+
+/// Admin, sender and a 1_000 escrow whose deadline is at 13_600, with a
+/// 0.5% keeper reward after a 600 second grace period
+fn setup_expiring_escrow(env: &Env, client: &QuickexContractClient) -> (Address, Address, u64) {
+    let admin = Address::generate(env);
+    let from = Address::generate(env);
+    let token = create_funded_token(env, &from, 1_000);
+    client.initialize(&admin);
+    client.set_max_escrow_lifetime_secs(&admin, &3_600);
+    client.set_keeper_incentive(&admin, &50, &600);
+    env.ledger().set_timestamp(10_000);
+    let escrow_id = client.create_escrow(
+        &from,
+        &Address::generate(env),
+        &token,
+        &1_000,
+        &Bytes::new(env),
+    );
+    (from, token, escrow_id)
+}
+
+#[test]
+fn test_sweep_expired_pays_keeper_after_grace() {
+    let (env, client) = setup();
+    let (from, token, escrow_id) = setup_expiring_escrow(&env, &client);
+    let keeper = Address::generate(&env);
+
+    env.ledger().set_timestamp(14_199);
+    let result = client.try_sweep_expired(&escrow_id, &keeper);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotExpired)));
+
+    env.ledger().set_timestamp(14_200);
+    assert_eq!(client.sweep_expired(&escrow_id, &keeper), 5);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            transfer_event(&env, &token, &client.address, &from, 995),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowRefunded"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "from"), from.into_val(&env)),
+                        (Symbol::new(&env, "timestamp"), 14_200u64.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+            transfer_event(&env, &token, &client.address, &keeper, 5),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowExpired"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "timestamp"), 14_200u64.into_val(&env))]
+                )
+                .into_val(&env),
+            ),
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "EscrowSwept"), escrow_id, keeper.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "reward"), 5i128.into_val(&env))]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Expired
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&keeper), 5);
+}
+
+#[test]
+fn test_claim_expired_by_sender_skips_reward() {
+    let (env, client) = setup();
+    let (from, token, escrow_id) = setup_expiring_escrow(&env, &client);
+
+    env.ledger().set_timestamp(13_599);
+    let result = client.try_claim_expired(&escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotExpired)));
+
+    // Within the grace period the sender gets everything back
+    env.ledger().set_timestamp(13_600);
+    client.claim_expired(&escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+
+    env.ledger().set_timestamp(14_200);
+    let result = client.try_sweep_expired(&escrow_id, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidStateTransition)));
+}
+
+#[test]
+fn test_claim_expired_credits_internal_balance_when_set() {
+    let (env, client) = setup();
+    let (from, token, escrow_id) = setup_expiring_escrow(&env, &client);
+    let token_client = token::Client::new(&env, &token);
+
+    client.set_refund_to_internal(&escrow_id, &from, &true);
+
+    env.ledger().set_timestamp(13_600);
+    client.claim_expired(&escrow_id);
+
+    // No transfer: the refund waits in the sender's withdrawable balance
+    assert_eq!(env.events().all().filter_by_contract(&token), vec![&env]);
+    assert_eq!(token_client.balance(&from), 0);
+    assert_eq!(client.get_withdrawable(&from, &token), 1_000);
+
+    assert_eq!(client.withdraw_credit(&from, &token), 1_000);
+    assert_eq!(token_client.balance(&from), 1_000);
+}
+
+#[test]
+fn test_refund_to_internal_toggle_restores_transfer() {
+    let (env, client) = setup();
+    let (from, token, escrow_id) = setup_expiring_escrow(&env, &client);
+
+    let result = client.try_set_refund_to_internal(&escrow_id, &Address::generate(&env), &true);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.set_refund_to_internal(&escrow_id, &from, &true);
+    client.set_refund_to_internal(&escrow_id, &from, &false);
+
+    env.ledger().set_timestamp(13_600);
+    client.claim_expired(&escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+    assert_eq!(client.get_withdrawable(&from, &token), 0);
+}
+
+#[test]
+fn test_poke_warns_once_inside_window() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+    client.set_max_escrow_lifetime_secs(&admin, &3_600);
+    client.set_deadline_warning_window(&admin, &600);
+
+    env.ledger().set_timestamp(10_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    // Deadline is 13_600, so the window opens at 13_000
+    env.ledger().set_timestamp(12_999);
+    assert!(!client.poke(&escrow_id));
+    assert_eq!(env.events().all(), vec![&env]);
+
+    env.ledger().set_timestamp(13_000);
+    assert!(client.poke(&escrow_id));
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (
+                    Symbol::new(&env, "EscrowDeadlineApproaching"),
+                    escrow_id,
+                    to.clone()
+                )
+                    .into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "deadline"), 13_600u64.into_val(&env))]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    assert!(client.get_escrow(&escrow_id).unwrap().deadline_warned);
+
+    env.ledger().set_timestamp(13_500);
+    assert!(!client.poke(&escrow_id));
+    assert_eq!(env.events().all(), vec![&env]);
+}
+
+#[test]
+fn test_poke_is_noop_past_deadline_or_without_window() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    client.initialize(&admin);
+    client.set_max_escrow_lifetime_secs(&admin, &3_600);
+
+    env.ledger().set_timestamp(10_000);
+    let first = client.create_escrow(
+        &from,
+        &Address::generate(&env),
+        &token,
+        &1_000,
+        &Bytes::new(&env),
+    );
+    let second = client.create_escrow(
+        &from,
+        &Address::generate(&env),
+        &token,
+        &1_000,
+        &Bytes::new(&env),
+    );
+
+    // No window configured
+    env.ledger().set_timestamp(13_500);
+    assert!(!client.poke(&first));
+
+    // The deadline itself is outside the window
+    client.set_deadline_warning_window(&admin, &600);
+    env.ledger().set_timestamp(13_600);
+    assert!(!client.poke(&second));
+    assert!(!client.get_escrow(&second).unwrap().deadline_warned);
+
+    let result = client.try_set_deadline_warning_window(&from, &600);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_refund_all_mine_unwinds_mixed_portfolio() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 5_000);
+    let token_client = token::Client::new(&env, &token);
+    client.initialize(&admin);
+
+    let tranche = client.create_tranche_escrow(&from, &to, &token, &1_000, &memo(&env, "t"));
+    client.fund_escrow_partial(&from, &tranche, &300);
+    let unaccepted = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    let accepted = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    client.accept_escrow(&accepted);
+    let released = client.create_escrow(&from, &to, &token, &500, &Bytes::new(&env));
+    client.release_escrow(&from, &released);
+    let frozen = client.create_escrow(&from, &to, &token, &500, &Bytes::new(&env));
+    client.freeze_escrow(&from, &frozen);
+    // Escrows the account only receives are not theirs to unwind
+    let incoming = client.create_escrow(
+        &to,
+        &from,
+        &create_funded_token(&env, &to, 100),
+        &100,
+        &Bytes::new(&env),
+    );
+
+    // The unwind still works while the contract is paused
+    client.set_paused(&admin, &true);
+    assert_eq!(client.refund_all_mine(&from, &10), (2, 1));
+
+    let status = |id: u64| client.get_escrow(&id).unwrap().status;
+    assert_eq!(status(tranche), EscrowStatus::Refunded);
+    assert_eq!(status(unaccepted), EscrowStatus::Refunded);
+    assert_eq!(status(accepted), EscrowStatus::Disputed);
+    assert_eq!(status(released), EscrowStatus::Released);
+    assert_eq!(status(frozen), EscrowStatus::Pending);
+    assert_eq!(status(incoming), EscrowStatus::Pending);
+
+    // 5_000 minted, 500 paid out, 1_000 disputed and 500 frozen still held
+    assert_eq!(token_client.balance(&from), 3_000);
+
+    // Nothing left to act on
+    assert_eq!(client.refund_all_mine(&from, &10), (0, 0));
+}
+
+#[test]
+fn test_refund_all_mine_disputes_without_bond_for_drained_sender() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &1_000);
+
+    let accepted = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    client.accept_escrow(&accepted);
+    let unaccepted = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 0);
+
+    // A bond transfer would fail here and undo the cancellation too
+    assert_eq!(client.refund_all_mine(&from, &10), (1, 1));
+    let escrow = client.get_escrow(&accepted).unwrap();
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(escrow.dispute_bond, 0);
+    assert_eq!(
+        client.get_escrow(&unaccepted).unwrap().status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+}
+
+#[test]
+fn test_refund_all_mine_stops_at_limit() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 300);
+
+    let first = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let second = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let third = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+
+    assert_eq!(client.refund_all_mine(&from, &2), (2, 0));
+    assert_eq!(
+        client.get_escrow(&first).unwrap().status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(
+        client.get_escrow(&second).unwrap().status,
+        EscrowStatus::Refunded
+    );
+    assert_eq!(
+        client.get_escrow(&third).unwrap().status,
+        EscrowStatus::Pending
+    );
+
+    assert_eq!(client.refund_all_mine(&from, &2), (1, 0));
+}
+
+#[test]
+fn test_keeper_incentive_is_capped() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_keeper_incentive(&admin, &101, &600);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidFeeBps)));
+    let result = client.try_set_keeper_incentive(&other, &50, &600);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_reassign_recipient_before_acceptance() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let wrong = Address::generate(&env);
+    let right = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let escrow_id = client.create_escrow(&from, &wrong, &token, &1_000, &Bytes::new(&env));
+
+    let result = client.try_reassign_recipient(&wrong, &escrow_id, &wrong);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.reassign_recipient(&from, &escrow_id, &right);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "RecipientReassigned"), escrow_id).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "old_to"), wrong.into_val(&env)),
+                        (Symbol::new(&env, "new_to"), right.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert_eq!(client.get_escrow(&escrow_id).unwrap().to, right);
+    assert_eq!(client.list_escrows_between(&from, &wrong, &0, &10).len(), 0);
+    assert_eq!(
+        client.list_escrows_between(&from, &right, &0, &10),
+        vec![&env, escrow_id]
+    );
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&right), 1_000);
+}
+
+#[test]
+fn test_reassign_recipient_rejected_after_acceptance() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    client.accept_escrow(&escrow_id);
+    assert!(client.get_escrow(&escrow_id).unwrap().accepted);
+
+    let result = client.try_reassign_recipient(&from, &escrow_id, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(QuickexError::CannotReassignAfterAcceptance)));
+
+    // Settled escrows can't be redirected either
+    let released = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    client.release_escrow(&from, &released);
+    let result = client.try_reassign_recipient(&from, &released, &Address::generate(&env));
+    assert_eq!(result, Err(Ok(QuickexError::CannotReassignAfterAcceptance)));
+}
+
+#[test]
+fn test_code_escrow_claim() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let lock = client.hash_claim_code(&Bytes::from_slice(&env, b"GIFT-42XK"));
+    let escrow_id = client.create_code_escrow(&from, &to, &token, &1_000, &lock);
+
+    let result =
+        client.try_claim_with_code(&to, &escrow_id, &Bytes::from_slice(&env, b"GIFT-42XL"));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidClaimCode)));
+    let result = client.try_claim_with_code(&to, &escrow_id, &Bytes::from_slice(&env, b"   "));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidClaimCode)));
+    let result =
+        client.try_claim_with_code(&from, &escrow_id, &Bytes::from_slice(&env, b"GIFT-42XK"));
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    // Surrounding whitespace and case don't matter
+    client.claim_with_code(&to, &escrow_id, &Bytes::from_slice(&env, b"  gift-42xk\n"));
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+}
+
+#[test]
+fn test_claim_with_code_needs_code_escrow() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let result = client.try_claim_with_code(&to, &escrow_id, &Bytes::from_slice(&env, b"ANY"));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidClaimCode)));
+
+    let result = client.try_hash_claim_code(&Bytes::from_array(&env, &[b'A'; 33]));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidClaimCode)));
+}
+
+#[test]
+fn test_largest_escrows_ordering() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100_000);
+    client.initialize(&admin);
+
+    let memo = Bytes::new(&env);
+    let mut ids = soroban_sdk::Vec::new(&env);
+    for amount in [500i128, 900, 100, 900, 300, 700, 200, 800, 400, 600] {
+        ids.push_back(client.create_escrow(&from, &to, &token, &amount, &memo));
+    }
+    let ranked = client.get_largest_escrows(&admin, &token);
+    assert_eq!(ranked.len(), 10);
+    // Equal amounts rank the older escrow first
+    assert_eq!(ranked.get_unchecked(0), (ids.get_unchecked(1), 900));
+    assert_eq!(ranked.get_unchecked(1), (ids.get_unchecked(3), 900));
+    assert_eq!(ranked.get_unchecked(9), (ids.get_unchecked(2), 100));
+
+    // A larger escrow pushes the smallest out of the top 10; a smaller one isn't reported
+    let big = client.create_escrow(&from, &to, &token, &1_000, &memo);
+    let small = client.create_escrow(&from, &to, &token, &50, &memo);
+    let ranked = client.get_largest_escrows(&admin, &token);
+    assert_eq!(ranked.get_unchecked(0), (big, 1_000));
+    assert_eq!(ranked.get_unchecked(9), (ids.get_unchecked(6), 200));
+    assert!(!ranked.iter().any(|(id, _)| id == small));
+
+    // Settled escrows drop out and the runners-up move up
+    client.release_escrow(&from, &big);
+    client.admin_cancel_escrow(&admin, &ids.get_unchecked(1), &Symbol::new(&env, "risk"));
+    let ranked = client.get_largest_escrows(&admin, &token);
+    assert_eq!(ranked.len(), 10);
+    assert_eq!(ranked.get_unchecked(0), (ids.get_unchecked(3), 900));
+    assert_eq!(ranked.get_unchecked(1), (ids.get_unchecked(7), 800));
+    assert_eq!(ranked.get_unchecked(9), (small, 50));
+}
+
+#[test]
+fn test_largest_escrows_refill_after_settlement() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100_000);
+    client.initialize(&admin);
+
+    let memo = Bytes::new(&env);
+    let mut ids = soroban_sdk::Vec::new(&env);
+    for amount in (1..=11i128).map(|n| n * 100) {
+        ids.push_back(client.create_escrow(&from, &to, &token, &amount, &memo));
+    }
+    let smallest = ids.get_unchecked(0);
+    let largest = ids.get_unchecked(10);
+    let ranked = client.get_largest_escrows(&admin, &token);
+    assert_eq!(ranked.len(), 10);
+    assert!(!ranked.iter().any(|(id, _)| id == smallest));
+
+    // The 100 escrow moves up into the freed slot
+    client.release_escrow(&from, &largest);
+    let ranked = client.get_largest_escrows(&admin, &token);
+    assert_eq!(ranked.len(), 10);
+    assert_eq!(ranked.get_unchecked(0), (ids.get_unchecked(9), 1_000));
+    assert_eq!(ranked.get_unchecked(9), (smallest, 100));
+}
+
+#[test]
+fn test_largest_escrows_admin_or_auditor_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let auditor = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_get_largest_escrows(&other, &token);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_set_auditor(&other, &other, &true);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.set_auditor(&admin, &auditor, &true);
+    assert!(client.is_auditor(&auditor));
+    assert_eq!(client.get_largest_escrows(&auditor, &token).len(), 0);
+
+    client.set_auditor(&admin, &auditor, &false);
+    assert!(!client.is_auditor(&auditor));
+    let result = client.try_get_largest_escrows(&auditor, &token);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_refund_address_override() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 3_000);
+    let token_client = token::Client::new(&env, &token);
+    client.initialize(&admin);
+    let memo = Bytes::new(&env);
+
+    // Set at creation
+    let first = client.create_escrow_with_refund_to(&from, &to, &token, &1_000, &memo, &treasury);
+    client.admin_cancel_escrow(&admin, &first, &Symbol::new(&env, "refund"));
+    assert_eq!(token_client.balance(&treasury), 1_000);
+
+    // Set afterwards, then cleared back to the default
+    let second = client.create_escrow(&from, &to, &token, &1_000, &memo);
+    let result = client.try_set_refund_address(&second, &to, &Some(to.clone()));
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.set_refund_address(&second, &from, &Some(treasury.clone()));
+    assert_eq!(
+        client.get_escrow(&second).unwrap().refund_to,
+        Some(treasury.clone())
+    );
+    client.set_refund_address(&second, &from, &None);
+    client.admin_cancel_escrow(&admin, &second, &Symbol::new(&env, "refund"));
+    assert_eq!(token_client.balance(&from), 2_000);
+    assert_eq!(token_client.balance(&treasury), 1_000);
+}
+
+#[test]
+fn test_refund_address_locked_in_dispute() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    let treasury = Address::generate(&env);
+    client.set_refund_address(&escrow_id, &from, &Some(treasury.clone()));
+
+    client.open_dispute(&to, &escrow_id);
+    let result = client.try_set_refund_address(&escrow_id, &from, &None);
+    assert_eq!(result, Err(Ok(QuickexError::RefundAddressLocked)));
+
+    // The sender's share of a split goes to the address fixed before the dispute
+    client.resolve_dispute_split(&escrow_id, &arbiter, &4_000);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&treasury), 600);
+    assert_eq!(token_client.balance(&from), 100);
+
+    let result = client.try_set_refund_address(&escrow_id, &from, &None);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+}
+
+#[test]
+fn test_create_escrow_denominated() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000_000);
+
+    // 12.50 in cents at 100 base units per cent
+    let escrow_id = client.create_escrow_denominated(&from, &to, &token, &1_250, &100);
+
+    let escrow = client.get_escrow(&escrow_id).unwrap();
+    assert_eq!(escrow.amount, 125_000);
+    assert_eq!(
+        client.get_escrow_denomination(&escrow_id),
+        Some(Denomination {
+            ref_amount: 1_250,
+            rate: 100,
+        })
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 875_000);
+}
+
+#[test]
+fn test_create_escrow_denominated_rejects_bad_input() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let result = client.try_create_escrow_denominated(&from, &to, &token, &10, &0);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidRate)));
+
+    let result = client.try_create_escrow_denominated(&from, &to, &token, &0, &10);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+
+    let result = client.try_create_escrow_denominated(&from, &to, &token, &i128::MAX, &2);
+    assert_eq!(result, Err(Ok(QuickexError::AmountOverflow)));
+}
+
+#[test]
+fn test_plain_escrow_has_no_denomination() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(client.get_escrow_denomination(&escrow_id), None);
+}
+
+#[test]
+fn test_query_escrow_errors() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    // Uninitialized contract: a missing escrow is just missing
+    assert_eq!(
+        client.try_query_escrow(&42).err(),
+        Some(Ok(QuickexError::EscrowNotFound))
+    );
+    assert_eq!(
+        client.try_query_escrow_status(&42).err(),
+        Some(Ok(QuickexError::EscrowNotFound))
+    );
+    assert!(client.get_escrow(&42).is_none());
+
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    assert_eq!(client.query_escrow(&escrow_id).amount, 1_000);
+    assert_eq!(
+        client.query_escrow_status(&escrow_id),
+        EscrowStatus::Pending
+    );
+
+    assert_eq!(
+        client.try_query_yield_adapter(&token).err(),
+        Some(Ok(QuickexError::YieldAdapterNotSet))
+    );
+    assert_eq!(
+        client.try_query_template(&from, &1).err(),
+        Some(Ok(QuickexError::TemplateNotFound))
+    );
+    assert_eq!(
+        client.try_query_registered_commitment(&1).err(),
+        Some(Ok(QuickexError::CommitmentNotFound))
+    );
+}
+
+#[test]
+fn test_query_errors_when_module_disabled() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize_with_features(
+        &admin,
+        &FeatureFlags {
+            escrow_enabled: false,
+            commitments_enabled: false,
+            privacy_enabled: true,
+        },
+    );
+
+    assert_eq!(
+        client.try_query_escrow(&1).err(),
+        Some(Ok(QuickexError::FeatureDisabled))
+    );
+    assert_eq!(
+        client.try_query_template(&admin, &1).err(),
+        Some(Ok(QuickexError::FeatureDisabled))
+    );
+    assert_eq!(
+        client.try_query_registered_commitment(&1).err(),
+        Some(Ok(QuickexError::FeatureDisabled))
+    );
+    assert_eq!(
+        client
+            .try_query_anchored_root(&BytesN::from_array(&env, &[1; 32]))
+            .err(),
+        Some(Ok(QuickexError::FeatureDisabled))
+    );
+    assert!(client.get_escrow(&1).is_none());
+}
+
+#[test]
+fn test_list_escrowed_tokens() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let first = create_funded_token(&env, &from, 1_000);
+    let second = create_funded_token(&env, &from, 1_000);
+    assert_eq!(client.list_escrowed_tokens(), vec![&env]);
+
+    client.create_escrow(&from, &to, &first, &300, &Bytes::new(&env));
+    client.create_escrow(&from, &to, &second, &300, &Bytes::new(&env));
+    client.create_escrow(&from, &to, &first, &300, &Bytes::new(&env));
+    client.deposit(&second, &100, &from, &Bytes::from_array(&env, &[1; 32]));
+
+    assert_eq!(client.list_escrowed_tokens(), vec![&env, first, second]);
+}
+
+#[test]
+fn test_token_pause_blocks_only_that_token() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let exploited = create_funded_token(&env, &from, 10_000);
+    let healthy = create_funded_token(&env, &from, 10_000);
+    client.initialize(&admin);
+
+    let funded = client.create_escrow(&from, &to, &exploited, &1_000, &memo(&env, "a"));
+    let tranche = client.create_tranche_escrow(&from, &to, &exploited, &1_000, &memo(&env, "t"));
+
+    let result = client.try_set_token_paused(&from, &exploited, &true);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.set_token_paused(&admin, &exploited, &true);
+    assert!(client.is_token_paused(&exploited));
+    assert!(!client.is_token_paused(&healthy));
+
+    // No new escrows or funding in the paused token
+    let result = client.try_create_escrow(&from, &to, &exploited, &500, &memo(&env, "b"));
+    assert_eq!(result, Err(Ok(QuickexError::TokenPaused)));
+    let result = client.try_fund_escrow_partial(&from, &tranche, &400);
+    assert_eq!(result, Err(Ok(QuickexError::TokenPaused)));
+
+    // Other tokens and settlement of funded escrows still work
+    client.create_escrow(&from, &to, &healthy, &500, &memo(&env, "c"));
+    client.release_escrow(&from, &funded);
+    assert_eq!(token::Client::new(&env, &exploited).balance(&to), 1_000);
+
+    client.set_token_paused(&admin, &exploited, &false);
+    client.create_escrow(&from, &to, &exploited, &500, &memo(&env, "d"));
+}
+
+#[test]
+fn test_recurring_escrow_renews_from_credit() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 5_000);
+    let token_client = token::Client::new(&env, &token);
+
+    assert_eq!(client.deposit_credit(&from, &token, &2_500), 2_500);
+    let first = client.create_recurring_escrow(&from, &to, &token, &1_000, &2_592_000);
+    assert_eq!(client.get_escrow(&first).unwrap().interval, Some(2_592_000));
+
+    // Each release opens the next period from credit until it runs short
+    client.release_escrow(&from, &first);
+    let second = first + 1;
+    let next = client.get_escrow(&second).unwrap();
+    assert_eq!(next.status, EscrowStatus::Pending);
+    assert_eq!(next.amount, 1_000);
+    assert_eq!(next.interval, Some(2_592_000));
+    assert_eq!(client.get_withdrawable(&from, &token), 1_500);
+
+    client.release_escrow(&from, &second);
+    client.release_escrow(&from, &(second + 1));
+    assert!(client.get_escrow(&(second + 2)).is_none());
+    assert_eq!(client.get_withdrawable(&from, &token), 500);
+    assert_eq!(token_client.balance(&to), 3_000);
+}
+
+#[test]
+fn test_recurring_escrow_renews_on_split_and_dispute_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 5_000);
+    client.initialize(&admin);
+
+    client.deposit_credit(&from, &token, &3_000);
+    let first = client.create_recurring_escrow(&from, &to, &token, &1_000, &86_400);
+
+    client.release_split_amounts(&from, &first, &600, &400);
+    let second = first + 1;
+    assert_eq!(
+        client.get_escrow_status(&second),
+        Some(EscrowStatus::Pending)
+    );
+
+    client.open_dispute(&from, &second);
+    client.resolve_dispute(&admin, &second, &true);
+    let third = second + 1;
+    assert_eq!(
+        client.get_escrow_status(&third),
+        Some(EscrowStatus::Pending)
+    );
+
+    // A refund ends the subscription
+    client.open_dispute(&from, &third);
+    client.resolve_dispute(&admin, &third, &false);
+    assert!(client.get_escrow(&(third + 1)).is_none());
+    assert_eq!(client.get_withdrawable(&from, &token), 1_000);
+}
+
+#[test]
+fn test_cancel_recurring_stops_renewal() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 5_000);
+
+    client.deposit_credit(&from, &token, &3_000);
+    let escrow_id = client.create_recurring_escrow(&from, &to, &token, &1_000, &86_400);
+
+    let result = client.try_cancel_recurring(&to, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.cancel_recurring(&from, &escrow_id);
+
+    client.release_escrow(&from, &escrow_id);
+    assert!(client.get_escrow(&(escrow_id + 1)).is_none());
+    assert_eq!(client.get_withdrawable(&from, &token), 3_000);
+
+    let result = client.try_cancel_recurring(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+}
+
+#[test]
+fn test_activity_feed_filters_by_ledger_and_pages() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    // 40 creations span two stored pages of 32
+    env.ledger().set_sequence_number(100);
+    for _ in 0..40 {
+        client.create_escrow(&from, &to, &token, &10, &Bytes::new(&env));
+    }
+
+    let first = RESERVED_ESCROW_IDS + 1;
+    env.ledger().set_sequence_number(200);
+    client.release_escrow(&from, &first);
+    env.ledger().set_sequence_number(300);
+    client.release_escrow(&from, &(first + 1));
+
+    let recent = client.get_activity(&from, &200, &0, &10);
+    assert_eq!(
+        recent,
+        vec![
+            &env,
+            ActivityEntry {
+                escrow_id: first,
+                status: EscrowStatus::Released,
+                ledger: 200,
+            },
+            ActivityEntry {
+                escrow_id: first + 1,
+                status: EscrowStatus::Released,
+                ledger: 300,
+            },
+        ]
+    );
+    assert_eq!(client.get_activity(&to, &201, &0, &10).len(), 1);
+
+    // Paging across the page boundary
+    let page = client.get_activity(&to, &0, &30, &5);
+    assert_eq!(page.len(), 5);
+    for (i, entry) in page.iter().enumerate() {
+        assert_eq!(entry.escrow_id, first + 30 + i as u64);
+        assert_eq!(entry.status, EscrowStatus::Pending);
+        assert_eq!(entry.ledger, 100);
+    }
+    assert_eq!(client.get_activity(&from, &0, &40, &10).len(), 2);
+    assert_eq!(client.get_activity(&from, &301, &0, &10).len(), 0);
+    assert_eq!(
+        client
+            .get_activity(&Address::generate(&env), &0, &0, &10)
+            .len(),
+        0
+    );
+}
+
+#[test]
+fn test_activity_cursor_matches_full_read_while_feed_grows() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    for _ in 0..40 {
+        client.create_escrow(
+            &from,
+            &Address::generate(&env),
+            &token,
+            &10,
+            &Bytes::new(&env),
+        );
+    }
+
+    // A creation between calls appends to the feed without shifting what
+    // the cursor has already covered
+    let (first, cursor) = client.get_activity_by_cursor(&from, &0);
+    assert_eq!(first.len(), 32);
+    assert_eq!(cursor, Some(1));
+    client.create_escrow(
+        &from,
+        &Address::generate(&env),
+        &token,
+        &10,
+        &Bytes::new(&env),
+    );
+
+    let mut entries = first;
+    let mut cursor = cursor;
+    while let Some(next) = cursor {
+        let (page, after) = client.get_activity_by_cursor(&from, &next);
+        entries.append(&page);
+        cursor = after;
+    }
+    assert_eq!(entries, client.get_activity(&from, &0, &0, &100));
+    assert_eq!(entries.len(), 41);
+    for (i, entry) in entries.iter().enumerate() {
+        assert_eq!(entry.escrow_id, RESERVED_ESCROW_IDS + i as u64 + 1);
+    }
+
+    let (empty, cursor) = client.get_activity_by_cursor(&Address::generate(&env), &0);
+    assert!(empty.is_empty());
+    assert_eq!(cursor, None);
+}
+
+#[test]
+fn test_owner_commitments_cursor_lists_every_deposit() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let token = create_funded_token(&env, &owner, 1_000);
+    let mut deposited = Vec::new(&env);
+    for i in 0..33u8 {
+        deposited.push_back(client.deposit(
+            &token,
+            &10,
+            &owner,
+            &Bytes::from_array(&env, &[i; 32]),
+        ));
+    }
+
+    let (first, cursor) = client.list_owner_commitments(&owner, &0);
+    assert_eq!(first.len(), 32);
+    assert_eq!(cursor, Some(1));
+    let (second, cursor) = client.list_owner_commitments(&owner, &1);
+    assert_eq!(cursor, None);
+
+    let mut listed = first;
+    listed.append(&second);
+    assert_eq!(listed, deposited);
+}
+
+#[test]
+fn test_quote_escrow_release_applies_fee_rules() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // Global default applies to escrows without their own rate
+    let (_, _, _, _, default_id) = setup_arbitrated_escrow(&env, &client, 10_000, None);
+    assert_eq!(client.quote_escrow_release(&default_id), (0, 10_000));
+    client.set_default_fee_bps(&admin, &100);
+    assert_eq!(client.quote_escrow_release(&default_id), (100, 9_900));
+
+    // A per-escrow override beats the default
+    let (from, to, _, token, override_id) =
+        setup_arbitrated_escrow(&env, &client, 10_000, Some(250));
+    assert_eq!(client.quote_escrow_release(&override_id), (250, 9_750));
+
+    // An exempt party pays nothing, and release matches the quote
+    client.set_fee_exempt(&admin, &to, &true);
+    assert!(client.is_fee_exempt(&to));
+    assert_eq!(client.quote_escrow_release(&override_id), (0, 10_000));
+    let before = token::Client::new(&env, &token).balance(&to);
+    client.release_escrow(&from, &override_id);
+    assert_eq!(
+        token::Client::new(&env, &token).balance(&to),
+        before + 10_000
+    );
+
+    let result = client.try_set_default_fee_bps(&admin, &10_001);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidFeeBps)));
+    let result = client.try_quote_escrow_release(&99);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowNotFound)));
+}
+
+#[test]
+fn test_effective_fee_bps_follows_default_rate() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.effective_fee_bps(&1_000), 0);
+
+    client.set_default_fee_bps(&admin, &75);
+    assert_eq!(client.effective_fee_bps(&1), 75);
+    assert_eq!(client.effective_fee_bps(&1_000_000_000), 75);
+
+    client.set_default_fee_bps(&admin, &120);
+    assert_eq!(client.effective_fee_bps(&1_000), 120);
+}
+
+#[test]
+fn test_flagged_escrow_needs_admin_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let high_risk = Symbol::new(&env, "high_risk");
+    let sanctions = Symbol::new(&env, "sanctions_hit");
+    let result = client.try_flag_escrow(&from, &escrow_id, &high_risk);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.flag_escrow(&admin, &escrow_id, &high_risk);
+    client.flag_escrow(&admin, &escrow_id, &sanctions);
+    client.flag_escrow(&admin, &escrow_id, &high_risk);
+    assert_eq!(
+        client.get_escrow_flags(&escrow_id),
+        vec![&env, high_risk.clone(), sanctions.clone()]
+    );
+
+    // The sender can't release while flagged, the admin can
+    let result = client.try_release_escrow(&from, &escrow_id);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowFlagged)));
+
+    client.unflag_escrow(&admin, &escrow_id, &sanctions);
+    assert_eq!(client.get_escrow_flags(&escrow_id), vec![&env, high_risk]);
+    client.release_escrow(&admin, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+}
+
+#[test]
+fn test_flagged_escrow_split_needs_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 2_000);
+    client.initialize(&admin);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    let refunded = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+    client.flag_escrow(&admin, &escrow_id, &Symbol::new(&env, "high_risk"));
+    client.flag_escrow(&admin, &refunded, &Symbol::new(&env, "high_risk"));
+
+    // Both parties agreeing can't move a flagged escrow to the recipient
+    assert_eq!(
+        client.try_release_split_amounts(&to, &escrow_id, &1_000, &0),
+        Err(Ok(QuickexError::EscrowFlagged))
+    );
+    assert_eq!(
+        client.try_release_split_amounts(&from, &escrow_id, &1, &999),
+        Err(Ok(QuickexError::EscrowFlagged))
+    );
+
+    // Returning everything to the sender is still allowed
+    client.release_split_amounts(&from, &refunded, &0, &1_000);
+    assert_eq!(token::Client::new(&env, &token).balance(&from), 1_000);
+
+    client.release_split_amounts(&admin, &escrow_id, &1_000, &0);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+}
+
+#[test]
+fn test_unflagged_escrow_releases_normally() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(&env));
+
+    let review = Symbol::new(&env, "review");
+    client.flag_escrow(&admin, &escrow_id, &review);
+    client.unflag_escrow(&admin, &escrow_id, &review);
+    assert_eq!(client.get_escrow_flags(&escrow_id).len(), 0);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+}
+
+#[test]
+fn test_tvl_tracks_open_escrows() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let released = client.create_escrow(&from, &to, &token, &300, &Bytes::new(&env));
+    client.create_escrow(&from, &to, &token, &200, &Bytes::new(&env));
+    let tranche = client.create_tranche_escrow(&from, &to, &token, &400, &memo(&env, "t"));
+    client.fund_escrow_partial(&from, &tranche, &150);
+    assert_eq!(client.get_tvl(&token), 650);
+
+    client.release_escrow(&from, &released);
+    assert_eq!(client.get_tvl(&token), 350);
+}
+
+#[test]
+fn test_recompute_tvl_repairs_corrupted_value() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let other = create_funded_token(&env, &from, 1_000);
+    client.initialize(&admin);
+
+    client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    client.create_escrow(&from, &to, &other, &500, &Bytes::new(&env));
+    let released = client.create_escrow(&from, &to, &token, &200, &Bytes::new(&env));
+    client.release_escrow(&from, &released);
+    client.create_escrow(&from, &to, &token, &300, &Bytes::new(&env));
+
+    env.as_contract(&client.address, || {
+        crate::storage::set_tvl(&env, &token, 9_999);
+    });
+    assert_eq!(client.get_tvl(&token), 9_999);
+
+    // A scan must start at id 1 and continue where the last page ended
+    assert_eq!(
+        client.try_recompute_tvl(&admin, &token, &3, &2),
+        Err(Ok(QuickexError::TvlScanOutOfOrder))
+    );
+    assert_eq!(client.recompute_tvl(&admin, &token, &1, &2), 100);
+    assert_eq!(client.get_tvl(&token), 9_999);
+    assert_eq!(client.recompute_tvl(&admin, &token, &3, &2), 400);
+    assert_eq!(client.get_tvl(&token), 400);
+    assert_eq!(client.get_tvl(&other), 500);
+}
+
+#[test]
+fn test_recompute_tvl_admin_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let token = Address::generate(&env);
+    client.initialize(&admin);
+
+    assert_eq!(
+        client.try_recompute_tvl(&Address::generate(&env), &token, &1, &10),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_reserve_proof_flags_shortfall() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_fee_timing(&admin, &FeeTiming::OnCreate);
+    env.ledger().set_sequence_number(500);
+
+    let (_, _, _, token, _) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    let proof = client.get_reserve_proof(&token);
+    assert_eq!(
+        proof,
+        ReserveProof {
+            token: token.clone(),
+            tvl: 990,
+            fees_accrued: 10,
+            credits: 0,
+            bonds: 0,
+            deposits: 0,
+            balance: 1_000,
+            parked: 0,
+            solvent: true,
+            ledger: 500,
+        }
+    );
+    assert_eq!(env.events().all(), vec![&env]);
+
+    // Owe more than the contract holds
+    env.as_contract(&client.address, || {
+        crate::storage::set_tvl(&env, &token, 1_500);
+    });
+    let proof = client.get_reserve_proof(&token);
+    assert!(!proof.solvent);
+    assert_eq!(proof.tvl, 1_500);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "ReserveShortfall"), token.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "held"), 1_000i128.into_val(&env)),
+                        (Symbol::new(&env, "owed"), 1_510i128.into_val(&env)),
+                        (Symbol::new(&env, "ledger"), 500u32.into_val(&env)),
+                    ]
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_reserve_proof_counts_credits_bonds_and_deposits() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &500);
+
+    let (from, to, arbiter, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, None);
+    client.open_dispute(&from, &escrow_id);
+    let salt = Bytes::from_slice(&env, b"reserve");
+    let commitment = client.deposit(&token, &40, &from, &salt);
+    client.deposit_credit(&to, &token, &60);
+
+    let proof = client.get_reserve_proof(&token);
+    assert_eq!(
+        (proof.tvl, proof.bonds, proof.deposits, proof.credits),
+        (1_000, 50, 40, 60)
+    );
+    assert_eq!(proof.balance, 1_150);
+    assert!(proof.solvent);
+
+    client.resolve_dispute(&arbiter, &escrow_id, &true);
+    client.withdraw(&token, &40, &commitment, &from, &salt);
+    let proof = client.get_reserve_proof(&token);
+    assert_eq!(
+        (proof.tvl, proof.bonds, proof.deposits, proof.credits),
+        (0, 0, 0, 60)
+    );
+    assert!(proof.solvent);
+
+    // Losing the funds behind an unpaid credit is a shortfall
+    env.as_contract(&client.address, || {
+        token::Client::new(&env, &token).transfer(&client.address, &admin, &60);
+    });
+    let proof = client.get_reserve_proof(&token);
+    assert_eq!(proof.balance, 0);
+    assert!(!proof.solvent);
+
+    token::StellarAssetClient::new(&env, &token).mint(&client.address, &60);
+    client.withdraw_credit(&to, &token);
+    let proof = client.get_reserve_proof(&token);
+    assert_eq!((proof.credits, proof.balance), (0, 0));
+    assert!(proof.solvent);
+}
+
+#[test]
+fn test_import_escrow_only_below_reserved_ids() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    client.initialize(&admin);
+
+    let created = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    client.release_escrow(&from, &created);
+    let record = client.get_escrow(&created).unwrap();
+
+    assert_eq!(
+        client.try_import_escrow(&from, &7, &record),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+    for escrow_id in [0, RESERVED_ESCROW_IDS, created] {
+        assert_eq!(
+            client.try_import_escrow(&admin, &escrow_id, &record),
+            Err(Ok(QuickexError::EscrowIdNotImportable))
+        );
+    }
+
+    client.import_escrow(&admin, &7, &record);
+    assert_eq!(client.get_escrow_status(&7), Some(EscrowStatus::Released));
+    assert_eq!(
+        client.try_import_escrow(&admin, &7, &record),
+        Err(Ok(QuickexError::EscrowAlreadyExists))
+    );
+
+    // Imports don't move the counter
+    assert_eq!(client.get_escrow_count(), 1);
+    assert_eq!(client.preview_next_escrow_id(), created + 1);
+}
+
+#[test]
+fn test_migrate_from_unversioned_storage() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    client.initialize(&admin);
+    assert_eq!(client.get_storage_version(), 1);
+    assert_eq!(
+        client.try_migrate(&admin, &1),
+        Err(Ok(QuickexError::UnknownMigration))
+    );
+
+    // Lay storage out as a build from before versioning did: one escrow at
+    // id 1 and no stored version
+    let created = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    env.as_contract(&client.address, || {
+        use crate::storage::{self, DataKey};
+        let record = storage::get_escrow_by_id(&env, created).unwrap();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::EscrowById(created));
+        storage::put_escrow_by_id(&env, 1, &record);
+        env.storage()
+            .persistent()
+            .set(&DataKey::EscrowCounter, &1u64);
+        env.storage().instance().remove(&DataKey::StorageVersion);
+    });
+    assert_eq!(client.get_storage_version(), 0);
+    assert_eq!(client.get_escrow_count(), 1);
+    // TVL can be repaired before migrating
+    assert_eq!(client.recompute_tvl(&admin, &token, &1, &10), 100);
+
+    assert_eq!(
+        client.try_migrate(&Address::generate(&env), &0),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_migrate(&admin, &1),
+        Err(Ok(QuickexError::StorageVersionMismatch))
+    );
+
+    assert_eq!(client.migrate(&admin, &0), 1);
+    assert_eq!(client.get_storage_version(), 1);
+    assert_eq!(client.recompute_tvl(&admin, &token, &1, &10), 100);
+    assert_eq!(client.get_escrow_count(), 0);
+    assert_eq!(client.preview_next_escrow_id(), RESERVED_ESCROW_IDS + 1);
+
+    // The legacy escrow still settles
+    client.release_escrow(&from, &1);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 100);
+    assert_eq!(
+        client.try_migrate(&admin, &1),
+        Err(Ok(QuickexError::UnknownMigration))
+    );
+}
+
+#[test]
+fn test_imported_escrow_settles_normally() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100);
+    client.initialize(&admin);
+
+    let created = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let record = client.get_escrow(&created).unwrap();
+
+    // The admin pays in what the imported escrow holds
+    client.import_escrow(&admin, &42, &record);
+    assert_eq!(token::Client::new(&env, &token).balance(&admin), 0);
+    assert_eq!(client.get_tvl(&token), 200);
+    assert_eq!(
+        client.list_escrows_between(&from, &to, &0, &10),
+        vec![&env, created, 42]
+    );
+
+    // A recount visits imported escrows, however sparse, before created ones
+    env.as_contract(&client.address, || {
+        crate::storage::set_tvl(&env, &token, 0);
+    });
+    assert_eq!(client.recompute_tvl(&admin, &token, &1, &2), 200);
+    assert_eq!(client.get_tvl(&token), 200);
+
+    client.release_escrow(&from, &42);
+    assert_eq!(client.get_escrow_status(&42), Some(EscrowStatus::Released));
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 100);
+    assert_eq!(client.get_tvl(&token), 100);
+
+    assert_eq!(client.preview_next_escrow_id(), created + 1);
+}
+
+#[test]
+fn test_import_escrow_rejects_state_outside_the_record() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &100);
+    client.initialize(&admin);
+
+    let created = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let record = client.get_escrow(&created).unwrap();
+
+    let mut bonded = record.clone();
+    bonded.dispute_bond = 50;
+    let mut parked = record.clone();
+    parked.yield_adapter = Some(Address::generate(&env));
+    let mut prepaid = record.clone();
+    prepaid.fee_paid = 10;
+    let mut overfunded = record.clone();
+    overfunded.funded = 200;
+    let mut disputed = record.clone();
+    disputed.status = EscrowStatus::Disputed;
+    disputed.dispute_opener = Some(from.clone());
+    let mut opener = record.clone();
+    opener.dispute_opener = Some(from.clone());
+    let mut frozen = record.clone();
+    frozen.frozen = true;
+    for bad in [
+        bonded, parked, prepaid, overfunded, disputed, opener, frozen,
+    ] {
+        assert_eq!(
+            client.try_import_escrow(&admin, &7, &bad),
+            Err(Ok(QuickexError::InvalidImportRecord))
+        );
+    }
+
+    // Records go through the same validation as created escrows
+    let mut empty = record.clone();
+    empty.amount = 0;
+    empty.funded = 0;
+    assert_eq!(
+        client.try_import_escrow(&admin, &7, &empty),
+        Err(Ok(QuickexError::InvalidAmount))
+    );
+    let mut long_memo = record.clone();
+    long_memo.memo = Bytes::from_array(&env, &[b'x'; 512]);
+    assert_eq!(
+        client.try_import_escrow(&admin, &7, &long_memo),
+        Err(Ok(QuickexError::MemoTooLong))
+    );
+    let mut bad_fee = record.clone();
+    bad_fee.fee_bps = Some(10_001);
+    assert_eq!(
+        client.try_import_escrow(&admin, &7, &bad_fee),
+        Err(Ok(QuickexError::InvalidFeeBps))
+    );
+    assert!(client.get_escrow(&7).is_none());
+    assert_eq!(token::Client::new(&env, &token).balance(&admin), 100);
+
+    // Watchers aren't carried over
+    let mut watched = record;
+    watched.watcher_count = 3;
+    client.import_escrow(&admin, &7, &watched);
+    assert_eq!(client.get_escrow(&7).unwrap().watcher_count, 0);
+}
+
+fn expected_digest(env: &Env, export: &AccountExport) -> BytesN<32> {
+    let mut unsealed = export.clone();
+    unsealed.digest = BytesN::from_array(env, &[0; 32]);
+    env.crypto().sha256(&unsealed.to_xdr(env)).into()
+}
+
+#[test]
+fn test_export_account_lists_open_positions() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token = create_funded_token(&env, &owner, 1_000);
+    let incoming_token = create_funded_token(&env, &other, 1_000);
+
+    let sent = client.create_escrow(&owner, &other, &token, &100, &Bytes::new(&env));
+    let released = client.create_escrow(&owner, &other, &token, &100, &Bytes::new(&env));
+    client.release_escrow(&owner, &released);
+    let disputed = client.create_escrow(&owner, &other, &token, &100, &Bytes::new(&env));
+    client.accept_escrow(&disputed);
+    client.open_dispute(&owner, &disputed);
+    let received = client.create_escrow(&other, &owner, &incoming_token, &100, &Bytes::new(&env));
+
+    let salt = Bytes::from_slice(&env, b"kept");
+    let kept = client.deposit(&token, &50, &owner, &salt);
+    let spent_salt = Bytes::from_slice(&env, b"spent");
+    let spent = client.deposit(&token, &50, &owner, &spent_salt);
+    client.withdraw(&token, &50, &spent, &owner, &spent_salt);
+
+    client.set_privacy(&owner, &true);
+
+    let export = client.export_account(&owner);
+    assert_eq!(export.owner, owner);
+    assert_eq!(export.sent, vec![&env, sent, disputed]);
+    assert_eq!(export.received, vec![&env, received]);
+    assert_eq!(export.commitments, vec![&env, kept]);
+    assert!(export.privacy_enabled);
+    assert!(!export.has_more);
+    assert_eq!(export.digest, expected_digest(&env, &export));
+
+    // Any tampering shows up in the digest
+    let mut tampered = export.clone();
+    tampered.sent.pop_back();
+    assert_ne!(expected_digest(&env, &tampered), export.digest);
+}
+
+#[test]
+fn test_export_account_pages_cover_full_export() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token = create_funded_token(&env, &owner, 10_000);
+
+    // Each escrow logs two entries, so the history spans several pages
+    for _ in 0..20 {
+        let id = client.create_escrow(&owner, &other, &token, &10, &Bytes::new(&env));
+        client.accept_escrow(&id);
+        client.open_dispute(&owner, &id);
+    }
+
+    let full = client.export_account(&owner);
+    assert_eq!(full.sent.len(), 20);
+
+    let mut sent = soroban_sdk::Vec::new(&env);
+    let mut page = 0;
+    loop {
+        let export = client.export_account_page(&owner, &page);
+        assert_eq!(export.page, page);
+        assert_eq!(export.digest, expected_digest(&env, &export));
+        sent.append(&export.sent);
+        if !export.has_more {
+            break;
+        }
+        page += 1;
+    }
+    assert_eq!(page, 1);
+    assert_eq!(sent, full.sent);
+}
+
+/// Create a classic account holding `balance` stroops of native XLM
+fn create_native_account(env: &Env, seed: u8, balance: i64) -> Address {
+    extern crate std;
+    use soroban_sdk::{
+        xdr::{
+            AccountEntry, AccountEntryExt, AccountId, LedgerEntry, LedgerEntryData, LedgerEntryExt,
+            LedgerKey, LedgerKeyAccount, PublicKey, ScAddress, SequenceNumber, Thresholds, Uint256,
+        },
+        TryFromVal,
+    };
+    use std::rc::Rc;
+
+    let account_id = AccountId(PublicKey::PublicKeyTypeEd25519(Uint256([seed; 32])));
+    let key = LedgerKey::Account(LedgerKeyAccount {
+        account_id: account_id.clone(),
+    });
+    let entry = LedgerEntry {
+        data: LedgerEntryData::Account(AccountEntry {
+            account_id: account_id.clone(),
+            balance,
+            flags: 0,
+            home_domain: Default::default(),
+            inflation_dest: None,
+            num_sub_entries: 0,
+            seq_num: SequenceNumber(0),
+            thresholds: Thresholds([1; 4]),
+            signers: Default::default(),
+            ext: AccountEntryExt::V0,
+        }),
+        last_modified_ledger_seq: 0,
+        ext: LedgerEntryExt::V0,
+    };
+    env.host()
+        .add_ledger_entry(&Rc::new(key), &Rc::new(entry), None)
+        .unwrap();
+
+    Address::try_from_val(env, &ScAddress::Account(account_id)).unwrap()
+}
+
+#[test]
+fn test_escrow_native_xlm() {
+    let (env, client) = setup();
+    let native = env
+        .deployer()
+        .with_stellar_asset(Bytes::from_array(&env, &[0; 4]))
+        .deploy();
+    assert_eq!(client.native_token(), native);
+
+    let from = create_native_account(&env, 1, 50_000_000);
+    let to = create_native_account(&env, 2, 10_000_000);
+    let xlm = token::Client::new(&env, &native);
+
+    let escrow_id = client.create_escrow(&from, &to, &native, &20_000_000, &Bytes::new(&env));
+    assert_eq!(xlm.balance(&from), 30_000_000);
+    assert_eq!(xlm.balance(&client.address), 20_000_000);
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(xlm.balance(&to), 30_000_000);
+    assert_eq!(xlm.balance(&client.address), 0);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+}
+
+#[test]
+fn test_token_min_amounts_are_enforced_per_token() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let stellar = create_funded_token(&env, &from, 1_000_000);
+    let bridged = create_funded_token(&env, &from, 1_000_000);
+    client.initialize(&admin);
+
+    client.set_token_min_amount(&admin, &stellar, &100);
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "TokenMinAmountSet"), stellar.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "decimals"), 7u32.into_val(&env)),
+                        (Symbol::new(&env, "min"), 100i128.into_val(&env)),
+                    ],
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+    client.set_token_min_amount(&admin, &bridged, &10_000);
+    assert_eq!(client.get_token_min_amount(&stellar), 100);
+
+    assert_eq!(
+        client.try_create_escrow(&from, &to, &stellar, &99, &Bytes::new(&env)),
+        Err(Ok(QuickexError::AmountBelowMinimum))
+    );
+    client.create_escrow(&from, &to, &stellar, &100, &Bytes::new(&env));
+
+    // Above the other token's minimum is not enough here
+    assert_eq!(
+        client.try_create_escrow(&from, &to, &bridged, &100, &Bytes::new(&env)),
+        Err(Ok(QuickexError::AmountBelowMinimum))
+    );
+    client.create_escrow(&from, &to, &bridged, &10_000, &Bytes::new(&env));
+
+    // Partial deposits must meet the minimum, except the one that completes funding
+    let tranche = client.create_tranche_escrow(&from, &to, &stellar, &250, &memo(&env, "t"));
+    assert_eq!(
+        client.try_fund_escrow_partial(&from, &tranche, &50),
+        Err(Ok(QuickexError::AmountBelowMinimum))
+    );
+    client.fund_escrow_partial(&from, &tranche, &200);
+    assert_eq!(client.fund_escrow_partial(&from, &tranche, &50), 250);
+
+    client.set_token_min_amount(&admin, &stellar, &0);
+    client.create_escrow(&from, &to, &stellar, &1, &Bytes::new(&env));
+    assert_eq!(
+        client.try_set_token_min_amount(&from, &stellar, &10),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_get_escrows_preserves_order_and_unknown_ids() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+
+    let first = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    let second = client.create_escrow(&from, &to, &token, &200, &Bytes::new(&env));
+    client.release_escrow(&from, &first);
+
+    let escrows = client.get_escrows(&vec![&env, second, 99, first]);
+    assert_eq!(escrows.len(), 3);
+
+    let found = escrows.get(0).unwrap().unwrap();
+    assert_eq!(found.amount, 200);
+    assert_eq!(found.status, EscrowStatus::Pending);
+    assert!(escrows.get(1).unwrap().is_none());
+    let found = escrows.get(2).unwrap().unwrap();
+    assert_eq!(found.amount, 100);
+    assert_eq!(found.status, EscrowStatus::Released);
+
+    let mut too_many = soroban_sdk::Vec::new(&env);
+    for id in 0..101 {
+        too_many.push_back(id);
+    }
+    assert!(matches!(
+        client.try_get_escrows(&too_many),
+        Err(Ok(QuickexError::InvalidBatch))
+    ));
+}
+
+#[test]
+fn test_counterparty_stats_track_completion_and_dispute_loss() {
+    let (env, client) = setup();
+    let (from, to, arbiter, token, disputed) = setup_arbitrated_escrow(&env, &client, 500, None);
+    let clean = client.create_escrow(&from, &to, &token, &50, &Bytes::new(&env));
+    client.release_escrow(&from, &clean);
+
+    client.accept_escrow(&disputed);
+    client.open_dispute(&to, &disputed);
+    // Refunding the sender rules against the recipient
+    client.resolve_dispute(&arbiter, &disputed, &false);
+
+    let sender = client.get_counterparty_stats(&from);
+    assert_eq!(sender.completed_as_sender, 1);
+    assert_eq!(sender.completed_as_recipient, 0);
+    assert_eq!(sender.disputes_against, 1);
+    assert_eq!(sender.disputes_lost, 0);
+
+    let recipient = client.get_counterparty_stats(&to);
+    assert_eq!(recipient.completed_as_sender, 0);
+    assert_eq!(recipient.completed_as_recipient, 1);
+    assert_eq!(recipient.disputes_against, 0);
+    assert_eq!(recipient.disputes_lost, 1);
+    assert!(!recipient.opted_out);
+}
+
+#[test]
+fn test_counterparty_stats_opt_out_requires_privacy() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 100);
+    let escrow_id = client.create_escrow(&from, &to, &token, &100, &Bytes::new(&env));
+    client.release_escrow(&from, &escrow_id);
+
+    assert_eq!(
+        client.try_set_counterparty_stats_opt_out(&from, &true),
+        Err(Ok(QuickexError::PrivacyNotEnabled))
+    );
+
+    client.set_privacy(&from, &true);
+    client.set_counterparty_stats_opt_out(&from, &true);
+    assert_eq!(
+        client.get_counterparty_stats(&from),
+        CounterpartyStats {
+            opted_out: true,
+            ..Default::default()
+        }
+    );
+
+    // The opt-out lapses with privacy, and nothing was lost meanwhile
+    client.set_privacy(&from, &false);
+    let stats = client.get_counterparty_stats(&from);
+    assert!(!stats.opted_out);
+    assert_eq!(stats.completed_as_sender, 1);
+}
+
+#[test]
+fn test_create_escrow_idempotent_retry_returns_same_escrow() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let token_client = token::Client::new(&env, &token);
+    let key = BytesN::from_array(&env, &[7; 32]);
+
+    let escrow_id =
+        client.create_escrow_idempotent(&from, &to, &token, &300, &memo(&env, "inv"), &key);
+    let retried =
+        client.create_escrow_idempotent(&from, &to, &token, &300, &memo(&env, "inv"), &key);
+    assert_eq!(retried, escrow_id);
+    assert_eq!(client.get_escrow_count(), 1);
+    assert_eq!(token_client.balance(&from), 700);
+
+    // The same key with other parameters is a client bug, not a retry
+    assert_eq!(
+        client.try_create_escrow_idempotent(&from, &to, &token, &301, &memo(&env, "inv"), &key),
+        Err(Ok(QuickexError::IdempotencyConflict))
+    );
+
+    // Keys are per sender
+    let other = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&other, &300);
+    let second =
+        client.create_escrow_idempotent(&other, &to, &token, &300, &memo(&env, "inv"), &key);
+    assert_ne!(second, escrow_id);
+}
+
+#[test]
+fn test_create_escrow_idempotent_key_expires() {
+    let (env, client) = setup();
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+    let token = create_funded_token(&env, &from, 1_000);
+    let key = BytesN::from_array(&env, &[9; 32]);
+
+    let first = client.create_escrow_idempotent(&from, &to, &token, &100, &Bytes::new(&env), &key);
+
+    env.ledger()
+        .with_mut(|l| l.sequence_number += crate::escrow::IDEMPOTENCY_TTL_LEDGERS + 1);
+
+    let second = client.create_escrow_idempotent(&from, &to, &token, &100, &Bytes::new(&env), &key);
+    assert_ne!(second, first);
+}
+
+#[test]
+fn test_preview_settlement_matches_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let (from, to, _, token, escrow_id) = setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    let token_client = token::Client::new(&env, &token);
+
+    let refund = client.preview_settlement(&escrow_id, &Resolution::Refund);
+    assert_eq!(refund.to_sender, 1_000);
+    assert_eq!(refund.to_recipient, 0);
+    assert_eq!(refund.protocol_fee, 0);
+
+    let preview = client.preview_settlement(&escrow_id, &Resolution::Release);
+    assert_eq!(
+        preview,
+        SettlementBreakdown {
+            to_recipient: 990,
+            to_sender: 0,
+            protocol_fee: 10,
+            keeper_reward: 0,
+            dispute_bond: 0,
+            bond_to: None,
+        }
+    );
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token_client.balance(&to), 100 + preview.to_recipient);
+    assert_eq!(client.get_collected_fees(&token), preview.protocol_fee);
+
+    let result = client.try_preview_settlement(&escrow_id, &Resolution::Release);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+}
+
+#[test]
+fn test_preview_settlement_matches_dispute_split() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_dispute_bond_bps(&admin, &500);
+    let (from, to, arbiter, token, escrow_id) =
+        setup_arbitrated_escrow(&env, &client, 1_000, Some(100));
+    let token_client = token::Client::new(&env, &token);
+    client.open_dispute(&to, &escrow_id);
+
+    let result = client.try_preview_settlement(&escrow_id, &Resolution::Split(10_001));
+    assert_eq!(result, Err(Ok(QuickexError::InvalidFeeBps)));
+
+    let preview = client.preview_settlement(&escrow_id, &Resolution::Split(2_500));
+    assert_eq!(
+        preview,
+        SettlementBreakdown {
+            to_recipient: 247,
+            to_sender: 743,
+            protocol_fee: 10,
+            keeper_reward: 0,
+            dispute_bond: 50,
+            bond_to: Some(to.clone()),
+        }
+    );
+
+    // A full award to the sender forfeits the recipient's bond
+    let forfeit = client.preview_settlement(&escrow_id, &Resolution::Split(0));
+    assert_eq!(forfeit.bond_to, Some(from.clone()));
+
+    client.resolve_dispute_split(&escrow_id, &arbiter, &2_500);
+    assert_eq!(
+        token_client.balance(&to),
+        50 + preview.to_recipient + preview.dispute_bond
+    );
+    assert_eq!(token_client.balance(&from), 100 + preview.to_sender);
+    assert_eq!(client.get_collected_fees(&token), preview.protocol_fee);
+}
+
+#[test]
+fn test_preview_settlement_matches_keeper_sweep() {
+    let (env, client) = setup();
+    let (from, token, escrow_id) = setup_expiring_escrow(&env, &client);
+    let keeper = Address::generate(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    env.ledger().set_timestamp(14_200);
+    let preview = client.preview_settlement(&escrow_id, &Resolution::Sweep);
+    assert_eq!(preview.keeper_reward, 5);
+    assert_eq!(preview.to_sender, 995);
+
+    assert_eq!(
+        client.sweep_expired(&escrow_id, &keeper),
+        preview.keeper_reward
+    );
+    assert_eq!(token_client.balance(&keeper), preview.keeper_reward);
+    assert_eq!(token_client.balance(&from), preview.to_sender);
+}
+
+#[test]
+fn test_deal_aggregate_status_follows_linked_escrows() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let owner = Address::generate(&env);
+    let token = create_funded_token(&env, &owner, 10_000);
+    let escrow_to =
+        |to: &Address| client.create_escrow(&owner, to, &token, &1_000, &Bytes::new(&env));
+    let goods = escrow_to(&Address::generate(&env));
+    let shipping = escrow_to(&Address::generate(&env));
+    let insurance = escrow_to(&Address::generate(&env));
+
+    let deal_id = client.create_deal(&owner);
+    assert_eq!(client.get_deal(&deal_id).unwrap().status, DealStatus::Open);
+    for escrow_id in [goods, shipping, insurance] {
+        client.link_escrow_to_deal(&deal_id, &escrow_id, &owner);
+    }
+    let deal = client.get_deal(&deal_id).unwrap();
+    assert_eq!(deal.owner, owner);
+    assert_eq!(deal.escrows, vec![&env, goods, shipping, insurance]);
+
+    // Settled differently, the deal stays open until the last one settles
+    client.release_escrow(&owner, &goods);
+    client.admin_cancel_escrow(&admin, &shipping, &Symbol::new(&env, "lost"));
+    assert_eq!(client.get_deal(&deal_id).unwrap().status, DealStatus::Open);
+    client.release_escrow(&owner, &insurance);
+    assert_eq!(
+        client.get_deal(&deal_id).unwrap().status,
+        DealStatus::Failed
+    );
+
+    // A deal whose escrows were all released is complete
+    let released = escrow_to(&Address::generate(&env));
+    let complete_id = client.create_deal(&owner);
+    client.link_escrow_to_deal(&complete_id, &released, &owner);
+    client.release_escrow(&owner, &released);
+    assert_eq!(
+        client.get_deal(&complete_id).unwrap().status,
+        DealStatus::Complete
+    );
+    assert_eq!(client.get_deal(&99), None);
+}
+
+#[test]
+fn test_link_escrow_to_deal_checks_caller_and_cap() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token = create_funded_token(&env, &owner, 20_000);
+    let foreign_token = create_funded_token(&env, &other, 1_000);
+    let deal_id = client.create_deal(&owner);
+
+    let result = client.try_link_escrow_to_deal(&7, &1, &owner);
+    assert_eq!(result, Err(Ok(QuickexError::DealNotFound)));
+
+    // The owner must be a party of the escrow, and a party must own the deal
+    let foreign = client.create_escrow(
+        &other,
+        &Address::generate(&env),
+        &foreign_token,
+        &1_000,
+        &Bytes::new(&env),
+    );
+    let result = client.try_link_escrow_to_deal(&deal_id, &foreign, &owner);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_link_escrow_to_deal(&deal_id, &foreign, &other);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    for _ in 0..16 {
+        let escrow_id = client.create_escrow(
+            &owner,
+            &Address::generate(&env),
+            &token,
+            &1_000,
+            &Bytes::new(&env),
+        );
+        client.link_escrow_to_deal(&deal_id, &escrow_id, &owner);
+    }
+    let result = client.try_link_escrow_to_deal(&deal_id, &(foreign + 1), &owner);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyLinked)));
+
+    let extra = client.create_escrow(
+        &owner,
+        &Address::generate(&env),
+        &token,
+        &1_000,
+        &Bytes::new(&env),
+    );
+    let result = client.try_link_escrow_to_deal(&deal_id, &extra, &owner);
+    assert_eq!(result, Err(Ok(QuickexError::TooManyDealEscrows)));
+}
@@ -0,0 +1,68 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::storage::{
+    get_event_log_bounds, get_event_log_entry, remove_event_log_entry, set_event_log_bounds,
+    set_event_log_entry,
+};
+use crate::types::EventSummary;
+use soroban_sdk::{Address, Env, Symbol, Vec};
+
+/// Maximum number of summaries returned by one `get_event_log` call
+pub const MAX_EVENT_LOG_PAGE: u32 = 100;
+
+/// Maximum number of summaries removed by one prune
+pub const MAX_EVENT_LOG_PRUNE: u64 = 500;
+
+/// Append a summary of a key escrow event to the on-chain log
+pub fn append(env: &Env, kind: Symbol, subject: u64) {
+    let (first, next) = get_event_log_bounds(env);
+    let summary = EventSummary {
+        kind,
+        subject,
+        timestamp: env.ledger().timestamp(),
+    };
+    set_event_log_entry(env, next, &summary);
+    set_event_log_bounds(env, first, next + 1);
+}
+
+/// Read up to `limit` summaries starting at sequence number `start`
+///
+/// Pruned entries are skipped, so a cold-start indexer can begin at 0.
+pub fn page(env: &Env, start: u64, limit: u32) -> Vec<EventSummary> {
+    let (first, next) = get_event_log_bounds(env);
+    let from = start.max(first);
+    let to = from
+        .saturating_add(limit.min(MAX_EVENT_LOG_PAGE) as u64)
+        .min(next);
+
+    let mut summaries = Vec::new(env);
+    for seq in from..to {
+        if let Some(summary) = get_event_log_entry(env, seq) {
+            summaries.push_back(summary);
+        }
+    }
+    summaries
+}
+
+/// Drop the oldest summaries below sequence number `before` (Admin only)
+///
+/// At most `MAX_EVENT_LOG_PRUNE` entries go per call. Returns how many were
+/// removed; sequence numbers of the remaining entries are unchanged.
+pub fn prune(env: &Env, caller: Address, before: u64) -> Result<u64, QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let (first, next) = get_event_log_bounds(env);
+    let until = before
+        .min(next)
+        .min(first.saturating_add(MAX_EVENT_LOG_PRUNE));
+    if until <= first {
+        return Ok(0);
+    }
+
+    for seq in first..until {
+        remove_event_log_entry(env, seq);
+    }
+    set_event_log_bounds(env, until, next);
+
+    Ok(until - first)
+}
@@ -1,4 +1,6 @@
-use soroban_sdk::{contractevent, Address, BytesN, Env};
+use crate::event_log;
+use crate::types::{Escrow, ReserveProof};
+use soroban_sdk::{contractevent, symbol_short, Address, Bytes, BytesN, Env, Symbol};
 
 #[contractevent(topics = ["PrivacyToggled"])]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -26,6 +28,13 @@ pub struct DepositToggledEvent {
     pub amount: i128,
 }
 
+#[contractevent(topics = ["PrivateDeposit"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateDepositEvent {
+    pub commitment: BytesN<32>,
+    pub token: Address,
+}
+
 pub(crate) fn publish_privacy_toggled(env: &Env, owner: Address, enabled: bool, timestamp: u64) {
     PrivacyToggledEvent {
         owner,
@@ -35,6 +44,40 @@ pub(crate) fn publish_privacy_toggled(env: &Env, owner: Address, enabled: bool,
     .publish(env);
 }
 
+#[contractevent(topics = ["DataForgotten"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DataForgottenEvent {
+    #[topic]
+    pub owner: Address,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_data_forgotten(env: &Env, owner: Address) {
+    DataForgottenEvent {
+        owner,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["TokenMinAmountSet"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMinAmountSetEvent {
+    #[topic]
+    pub token: Address,
+    pub min: i128,
+    pub decimals: u32,
+}
+
+pub(crate) fn publish_token_min_amount_set(env: &Env, token: Address, min: i128, decimals: u32) {
+    TokenMinAmountSetEvent {
+        token,
+        min,
+        decimals,
+    }
+    .publish(env);
+}
+
 #[allow(dead_code)]
 #[contractevent(topics = ["ContractPaused"])]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -48,6 +91,36 @@ pub(crate) fn publish_contract_paused(env: &Env, paused: bool, timestamp: u64) {
     ContractPausedEvent { paused, timestamp }.publish(env);
 }
 
+#[contractevent(topics = ["TokenPaused"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenPausedEvent {
+    #[topic]
+    pub token: Address,
+    pub paused: bool,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_token_paused(env: &Env, token: Address, paused: bool) {
+    TokenPausedEvent {
+        token,
+        paused,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["AuditorSet"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditorSetEvent {
+    #[topic]
+    pub auditor: Address,
+    pub enabled: bool,
+}
+
+pub(crate) fn publish_auditor_set(env: &Env, auditor: Address, enabled: bool) {
+    AuditorSetEvent { auditor, enabled }.publish(env);
+}
+
 #[allow(dead_code)]
 #[contractevent(topics = ["AdminChanged"])]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -98,6 +171,101 @@ pub(crate) fn publish_contract_upgraded(
     .publish(env);
 }
 
+#[contractevent(topics = ["YieldRecallFailed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct YieldRecallFailedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub adapter: Address,
+    pub amount: i128,
+}
+
+pub(crate) fn publish_yield_recall_failed(
+    env: &Env,
+    escrow_id: u64,
+    adapter: &Address,
+    amount: i128,
+) {
+    YieldRecallFailedEvent {
+        escrow_id,
+        adapter: adapter.clone(),
+        amount,
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["StorageMigrated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageMigratedEvent {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub admin: Address,
+}
+
+pub(crate) fn publish_storage_migrated(
+    env: &Env,
+    from_version: u32,
+    to_version: u32,
+    admin: &Address,
+) {
+    StorageMigratedEvent {
+        from_version,
+        to_version,
+        admin: admin.clone(),
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["MaxProofLenSet"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaxProofLenSetEvent {
+    pub max_len: u32,
+    pub admin: Address,
+}
+
+pub(crate) fn publish_max_proof_len_set(env: &Env, max_len: u32, admin: &Address) {
+    MaxProofLenSetEvent {
+        max_len,
+        admin: admin.clone(),
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["TestModeChanged"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestModeChangedEvent {
+    pub enabled: bool,
+    pub admin: Address,
+}
+
+pub(crate) fn publish_test_mode_changed(env: &Env, enabled: bool, admin: &Address) {
+    TestModeChangedEvent {
+        enabled,
+        admin: admin.clone(),
+    }
+    .publish(env);
+}
+
+#[contractevent(topics = ["ReserveShortfall"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveShortfallEvent {
+    #[topic]
+    pub token: Address,
+    pub held: i128,
+    pub owed: i128,
+    pub ledger: u32,
+}
+
+pub(crate) fn publish_reserve_shortfall(env: &Env, proof: &ReserveProof) {
+    ReserveShortfallEvent {
+        token: proof.token.clone(),
+        held: proof.balance + proof.parked,
+        owed: proof.tvl + proof.fees_accrued + proof.credits + proof.bonds + proof.deposits,
+        ledger: proof.ledger,
+    }
+    .publish(env);
+}
+
 pub(crate) fn publish_withdraw_toggled(env: &Env, to: Address, commitment: BytesN<32>) {
     WithdrawToggledEvent {
         to,
@@ -107,7 +275,18 @@ pub(crate) fn publish_withdraw_toggled(env: &Env, to: Address, commitment: Bytes
     .publish(env);
 }
 
-pub(crate) fn publish_deposit(env: &Env, commitment: BytesN<32>, token: Address, amount: i128) {
+pub(crate) fn publish_deposit(
+    env: &Env,
+    commitment: BytesN<32>,
+    token: Address,
+    amount: i128,
+    private: bool,
+) {
+    if private {
+        PrivateDepositEvent { commitment, token }.publish(env);
+        return;
+    }
+
     DepositToggledEvent {
         commitment,
         token,
@@ -115,3 +294,784 @@ pub(crate) fn publish_deposit(env: &Env, commitment: BytesN<32>, token: Address,
     }
     .publish(env);
 }
+
+#[contractevent(topics = ["EscrowCreated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCreatedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["PrivateEscrowCreated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateEscrowCreatedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub from: Address,
+    pub to: Address,
+    pub token: Address,
+}
+
+#[contractevent(topics = ["EscrowReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowReleasedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub to: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["PrivateEscrowReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrivateEscrowReleasedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub to: Address,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["CompactEscrowCreated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompactEscrowCreatedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub to: Address,
+}
+
+#[contractevent(topics = ["CompactEscrowReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompactEscrowReleasedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub from: Address,
+    #[topic]
+    pub to: Address,
+}
+
+#[contractevent(topics = ["EscrowRefunded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowRefundedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub from: Address,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["DisputeOpened"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeOpenedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub opener: Address,
+    pub bond: i128,
+}
+
+#[contractevent(topics = ["DisputeResolved"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeResolvedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub released: bool,
+    pub bond_to: Option<Address>,
+}
+
+#[contractevent(topics = ["DisputeSplit"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeSplitEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub to_recipient: i128,
+    pub to_sender: i128,
+    pub fee: i128,
+    pub bond_to: Option<Address>,
+}
+
+#[contractevent(topics = ["DisputeMessage"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeMessageEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub sender: Address,
+    pub msg_hash: BytesN<32>,
+    pub index: u32,
+}
+
+#[contractevent(topics = ["EvidenceSubmitted"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvidenceSubmittedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub submitter: Address,
+    pub evidence_hash: BytesN<32>,
+    pub index: u32,
+}
+
+#[contractevent(topics = ["EscrowCancelled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowCancelledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub reason: Symbol,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["EscrowExpired"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowExpiredEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub timestamp: u64,
+}
+
+#[contractevent(topics = ["EscrowDeadlineApproaching"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowDeadlineApproachingEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub to: Address,
+    pub deadline: u64,
+}
+
+#[contractevent(topics = ["AttestationAttached"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttestationAttachedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub party: Address,
+    pub attestation_hash: BytesN<32>,
+}
+
+#[contractevent(topics = ["EscrowSplitReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowSplitReleasedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub to_recipient: i128,
+    pub to_sender: i128,
+    pub fee: i128,
+}
+
+#[contractevent(topics = ["EscrowFunded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowFundedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub amount: i128,
+    pub funded: i128,
+}
+
+#[contractevent(topics = ["FundingShortfall"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundingShortfallEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub requested: i128,
+    pub received: i128,
+}
+
+#[contractevent(topics = ["FundsCredited"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundsCreditedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["CreditWithdrawn"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreditWithdrawnEvent {
+    #[topic]
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["EscrowSwept"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowSweptEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub keeper: Address,
+    pub reward: i128,
+}
+
+#[contractevent(topics = ["EscrowAccepted"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowAcceptedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub recipient: Address,
+}
+
+#[contractevent(topics = ["RecipientReassigned"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecipientReassignedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub old_to: Address,
+    pub new_to: Address,
+}
+
+#[contractevent(topics = ["EscrowRenewed"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowRenewedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub next_id: u64,
+}
+
+#[contractevent(topics = ["SwapSettled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapSettledEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub token_out: Address,
+    pub amount_out: i128,
+}
+
+#[contractevent(topics = ["AuctionFinalized"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuctionFinalizedEvent {
+    #[topic]
+    pub auction_id: u64,
+    pub winner: Option<Address>,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["EscrowFrozen"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowFrozenEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub frozen: bool,
+}
+
+#[contractevent(topics = ["EscrowFlagged"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowFlaggedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub flag: Symbol,
+    pub flagged: bool,
+}
+
+#[contractevent(topics = ["WatcherAdded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WatcherAddedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub watcher: Address,
+    pub watcher_count: u32,
+}
+
+#[contractevent(topics = ["ViewerAdded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ViewerAddedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    #[topic]
+    pub viewer: Address,
+    pub viewer_count: u32,
+    pub redundant: bool,
+}
+
+#[contractevent(topics = ["SaltRecoveryRequested"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaltRecoveryRequestedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub recovery: Address,
+    pub available_at: u64,
+}
+
+#[contractevent(topics = ["SaltRecoveryCancelled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaltRecoveryCancelledEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+}
+
+#[contractevent(topics = ["SaltBlobReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaltBlobReleasedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub recovery: Address,
+    pub blob: Bytes,
+}
+
+#[contractevent(topics = ["InsuranceClaimPaid"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceClaimPaidEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["EscrowMemoUpdated"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowMemoUpdatedEvent {
+    #[topic]
+    pub escrow_id: u64,
+    pub old_memo: Bytes,
+    pub new_memo: Bytes,
+}
+
+/// Publish the creation event in the variant the escrow's settings call for:
+/// compact if the recipient opted out of detailed events, private if the
+/// amount is hidden, full otherwise
+pub(crate) fn publish_escrow_created(env: &Env, escrow_id: u64, escrow: &Escrow, detailed: bool) {
+    event_log::append(env, symbol_short!("created"), escrow_id);
+
+    if !detailed {
+        CompactEscrowCreatedEvent {
+            escrow_id,
+            from: escrow.from.clone(),
+            to: escrow.to.clone(),
+        }
+        .publish(env);
+        return;
+    }
+
+    if escrow.private {
+        PrivateEscrowCreatedEvent {
+            escrow_id,
+            from: escrow.from.clone(),
+            to: escrow.to.clone(),
+            token: escrow.token.clone(),
+        }
+        .publish(env);
+        return;
+    }
+
+    EscrowCreatedEvent {
+        escrow_id,
+        from: escrow.from.clone(),
+        to: escrow.to.clone(),
+        token: escrow.token.clone(),
+        amount: escrow.amount,
+    }
+    .publish(env);
+}
+
+/// Publish the release event in the variant the escrow's settings call for
+pub(crate) fn publish_escrow_released(env: &Env, escrow_id: u64, escrow: &Escrow, detailed: bool) {
+    event_log::append(env, symbol_short!("released"), escrow_id);
+
+    if !detailed {
+        CompactEscrowReleasedEvent {
+            escrow_id,
+            from: escrow.from.clone(),
+            to: escrow.to.clone(),
+        }
+        .publish(env);
+        return;
+    }
+
+    let timestamp = env.ledger().timestamp();
+    if escrow.private {
+        PrivateEscrowReleasedEvent {
+            escrow_id,
+            to: escrow.to.clone(),
+            timestamp,
+        }
+        .publish(env);
+        return;
+    }
+
+    EscrowReleasedEvent {
+        escrow_id,
+        to: escrow.to.clone(),
+        amount: escrow.amount,
+        timestamp,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_memo_updated(
+    env: &Env,
+    escrow_id: u64,
+    old_memo: Bytes,
+    new_memo: Bytes,
+) {
+    EscrowMemoUpdatedEvent {
+        escrow_id,
+        old_memo,
+        new_memo,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_refunded(env: &Env, escrow_id: u64, from: Address) {
+    event_log::append(env, symbol_short!("refunded"), escrow_id);
+
+    EscrowRefundedEvent {
+        escrow_id,
+        from,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_dispute_opened(env: &Env, escrow_id: u64, opener: Address, bond: i128) {
+    event_log::append(env, symbol_short!("disputed"), escrow_id);
+
+    DisputeOpenedEvent {
+        escrow_id,
+        opener,
+        bond,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_dispute_resolved(
+    env: &Env,
+    escrow_id: u64,
+    released: bool,
+    bond_to: Option<Address>,
+) {
+    event_log::append(env, symbol_short!("resolved"), escrow_id);
+
+    DisputeResolvedEvent {
+        escrow_id,
+        released,
+        bond_to,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_dispute_split(
+    env: &Env,
+    escrow_id: u64,
+    to_recipient: i128,
+    to_sender: i128,
+    fee: i128,
+    bond_to: Option<Address>,
+) {
+    event_log::append(env, symbol_short!("resolved"), escrow_id);
+
+    DisputeSplitEvent {
+        escrow_id,
+        to_recipient,
+        to_sender,
+        fee,
+        bond_to,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_evidence_submitted(
+    env: &Env,
+    escrow_id: u64,
+    submitter: Address,
+    evidence_hash: BytesN<32>,
+    index: u32,
+) {
+    EvidenceSubmittedEvent {
+        escrow_id,
+        submitter,
+        evidence_hash,
+        index,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_dispute_message(
+    env: &Env,
+    escrow_id: u64,
+    sender: Address,
+    msg_hash: BytesN<32>,
+    index: u32,
+) {
+    DisputeMessageEvent {
+        escrow_id,
+        sender,
+        msg_hash,
+        index,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_insurance_claim_paid(
+    env: &Env,
+    escrow_id: u64,
+    to: Address,
+    token: Address,
+    amount: i128,
+) {
+    InsuranceClaimPaidEvent {
+        escrow_id,
+        to,
+        token,
+        amount,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_cancelled(env: &Env, escrow_id: u64, reason: Symbol) {
+    event_log::append(env, symbol_short!("cancelled"), escrow_id);
+
+    EscrowCancelledEvent {
+        escrow_id,
+        reason,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_deadline_approaching(
+    env: &Env,
+    escrow_id: u64,
+    to: Address,
+    deadline: u64,
+) {
+    EscrowDeadlineApproachingEvent {
+        escrow_id,
+        to,
+        deadline,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_attestation_attached(
+    env: &Env,
+    escrow_id: u64,
+    party: Address,
+    attestation_hash: BytesN<32>,
+) {
+    AttestationAttachedEvent {
+        escrow_id,
+        party,
+        attestation_hash,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_expired(env: &Env, escrow_id: u64) {
+    event_log::append(env, symbol_short!("expired"), escrow_id);
+
+    EscrowExpiredEvent {
+        escrow_id,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_split_released(
+    env: &Env,
+    escrow_id: u64,
+    to_recipient: i128,
+    to_sender: i128,
+    fee: i128,
+) {
+    event_log::append(env, symbol_short!("split"), escrow_id);
+
+    EscrowSplitReleasedEvent {
+        escrow_id,
+        to_recipient,
+        to_sender,
+        fee,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_funded(env: &Env, escrow_id: u64, amount: i128, funded: i128) {
+    EscrowFundedEvent {
+        escrow_id,
+        amount,
+        funded,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_funding_shortfall(
+    env: &Env,
+    escrow_id: u64,
+    requested: i128,
+    received: i128,
+) {
+    FundingShortfallEvent {
+        escrow_id,
+        requested,
+        received,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_funds_credited(env: &Env, escrow_id: u64, recipient: Address, amount: i128) {
+    FundsCreditedEvent {
+        escrow_id,
+        recipient,
+        amount,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_swept(env: &Env, escrow_id: u64, keeper: Address, reward: i128) {
+    EscrowSweptEvent {
+        escrow_id,
+        keeper,
+        reward,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_accepted(env: &Env, escrow_id: u64, recipient: Address) {
+    EscrowAcceptedEvent {
+        escrow_id,
+        recipient,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_recipient_reassigned(
+    env: &Env,
+    escrow_id: u64,
+    old_to: Address,
+    new_to: Address,
+) {
+    RecipientReassignedEvent {
+        escrow_id,
+        old_to,
+        new_to,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_renewed(env: &Env, escrow_id: u64, next_id: u64) {
+    EscrowRenewedEvent { escrow_id, next_id }.publish(env);
+}
+
+pub(crate) fn publish_credit_withdrawn(
+    env: &Env,
+    recipient: Address,
+    token: Address,
+    amount: i128,
+) {
+    CreditWithdrawnEvent {
+        recipient,
+        token,
+        amount,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_auction_finalized(
+    env: &Env,
+    auction_id: u64,
+    winner: Option<Address>,
+    amount: i128,
+) {
+    AuctionFinalizedEvent {
+        auction_id,
+        winner,
+        amount,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_swap_settled(
+    env: &Env,
+    escrow_id: u64,
+    token_out: Address,
+    amount_out: i128,
+) {
+    SwapSettledEvent {
+        escrow_id,
+        token_out,
+        amount_out,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_escrow_frozen(env: &Env, escrow_id: u64, frozen: bool) {
+    EscrowFrozenEvent { escrow_id, frozen }.publish(env);
+}
+
+pub(crate) fn publish_escrow_flagged(env: &Env, escrow_id: u64, flag: Symbol, flagged: bool) {
+    EscrowFlaggedEvent {
+        escrow_id,
+        flag,
+        flagged,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_watcher_added(
+    env: &Env,
+    escrow_id: u64,
+    watcher: Address,
+    watcher_count: u32,
+) {
+    WatcherAddedEvent {
+        escrow_id,
+        watcher,
+        watcher_count,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_viewer_added(
+    env: &Env,
+    escrow_id: u64,
+    viewer: Address,
+    viewer_count: u32,
+    redundant: bool,
+) {
+    ViewerAddedEvent {
+        escrow_id,
+        viewer,
+        viewer_count,
+        redundant,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_salt_recovery_requested(
+    env: &Env,
+    commitment: BytesN<32>,
+    recovery: Address,
+    available_at: u64,
+) {
+    SaltRecoveryRequestedEvent {
+        commitment,
+        recovery,
+        available_at,
+    }
+    .publish(env);
+}
+
+pub(crate) fn publish_salt_recovery_cancelled(env: &Env, commitment: BytesN<32>) {
+    SaltRecoveryCancelledEvent { commitment }.publish(env);
+}
+
+pub(crate) fn publish_salt_blob_released(
+    env: &Env,
+    commitment: BytesN<32>,
+    recovery: Address,
+    blob: Bytes,
+) {
+    SaltBlobReleasedEvent {
+        commitment,
+        recovery,
+        blob,
+    }
+    .publish(env);
+}
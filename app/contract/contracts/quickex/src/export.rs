@@ -0,0 +1,114 @@
+use crate::activity::ACTIVITY_PAGE_SIZE;
+use crate::privacy::get_privacy;
+use crate::reporting::is_open;
+use crate::storage::{
+    get_activity_count, get_activity_page, get_escrow, get_escrow_by_id, get_owner_deposit_count,
+    get_owner_deposit_page, get_privacy_level, get_private_escrows_by_default,
+};
+use crate::types::{AccountExport, EscrowStatus};
+use soroban_sdk::{xdr::ToXdr, Address, BytesN, Env, Vec};
+
+/// Commitment deposits covered by one export page
+pub const EXPORT_PAGE_SIZE: u32 = ACTIVITY_PAGE_SIZE;
+
+/// Export every open position of `owner` in one go
+pub fn export_account(env: &Env, owner: Address) -> AccountExport {
+    let mut export = empty_export(env, owner, 0);
+    let mut page = 0;
+    while collect_page(env, &mut export, page) {
+        page += 1;
+    }
+    seal(env, export)
+}
+
+/// Export the positions found on one page of `owner`'s history
+///
+/// Page `n` covers the account's `n`th activity page and its `n`th batch of
+/// `EXPORT_PAGE_SIZE` commitment deposits; keep reading while `has_more`.
+/// Each escrow is listed on exactly one page, so the pages together match
+/// `export_account`.
+pub fn export_account_page(env: &Env, owner: Address, page: u32) -> AccountExport {
+    let mut export = empty_export(env, owner, page);
+    export.has_more = collect_page(env, &mut export, page);
+    seal(env, export)
+}
+
+fn empty_export(env: &Env, owner: Address, page: u32) -> AccountExport {
+    AccountExport {
+        privacy_enabled: get_privacy(env, owner.clone()),
+        privacy_level: get_privacy_level(env, &owner),
+        private_escrows_by_default: get_private_escrows_by_default(env, &owner),
+        owner,
+        page,
+        has_more: false,
+        sent: Vec::new(env),
+        received: Vec::new(env),
+        commitments: Vec::new(env),
+        digest: BytesN::from_array(env, &[0; 32]),
+    }
+}
+
+/// Append the open positions on `page` and return whether later pages exist
+fn collect_page(env: &Env, export: &mut AccountExport, page: u32) -> bool {
+    let owner = export.owner.clone();
+
+    // Statuses never repeat for an escrow, so only its latest feed entry
+    // matches its current status and it is listed once
+    for entry in get_activity_page(env, &owner, page).iter() {
+        let Some(escrow) = get_escrow_by_id(env, entry.escrow_id) else {
+            continue;
+        };
+        if escrow.status != entry.status || !is_open(escrow.status) {
+            continue;
+        }
+        if escrow.from == owner {
+            export.sent.push_back(entry.escrow_id);
+        }
+        if escrow.to == owner {
+            export.received.push_back(entry.escrow_id);
+        }
+    }
+
+    for commitment in get_owner_deposit_page(env, &owner, page).iter() {
+        let outstanding = get_escrow(env, &commitment.clone().into())
+            .is_some_and(|entry| entry.status == EscrowStatus::Pending);
+        if outstanding {
+            export.commitments.push_back(commitment);
+        }
+    }
+
+    let next = page.saturating_add(1);
+    get_activity_count(env, &owner) > next.saturating_mul(ACTIVITY_PAGE_SIZE)
+        || get_owner_deposit_count(env, &owner) > next.saturating_mul(EXPORT_PAGE_SIZE)
+}
+
+/// Read one page of the commitments `owner` has deposited under, oldest first
+///
+/// The cursor is the page index; pass the returned cursor back for the next
+/// page until it is `None`. Each call loads a single storage page, and new
+/// deposits only ever append, so a cursor stays valid while the list grows.
+pub fn owner_commitments(
+    env: &Env,
+    owner: &Address,
+    cursor: u32,
+) -> (Vec<BytesN<32>>, Option<u32>) {
+    let next = cursor.saturating_add(1);
+    let more = get_owner_deposit_count(env, owner) > next.saturating_mul(EXPORT_PAGE_SIZE);
+    (
+        get_owner_deposit_page(env, owner, cursor),
+        more.then_some(next),
+    )
+}
+
+/// Fill in the digest over the export's canonical XDR encoding
+fn seal(env: &Env, mut export: AccountExport) -> AccountExport {
+    export.digest = export_digest(env, &export);
+    export
+}
+
+/// sha256 of `export`'s XDR with its digest zeroed
+fn export_digest(env: &Env, export: &AccountExport) -> BytesN<32> {
+    let mut unsealed = export.clone();
+    unsealed.digest = BytesN::from_array(env, &[0; 32]);
+    env.crypto().sha256(&unsealed.to_xdr(env)).into()
+}
@@ -0,0 +1,53 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::storage::{get_feature_flags, set_feature_flags};
+use crate::types::FeatureFlags;
+use soroban_sdk::{Address, Env};
+
+/// A module that a deployment can leave switched off
+#[derive(Clone, Copy)]
+pub enum Feature {
+    Escrow,
+    Commitments,
+    Privacy,
+}
+
+/// Whether `feature` is enabled on this deployment
+pub fn is_enabled(env: &Env, feature: Feature) -> bool {
+    let flags = get_feature_flags(env);
+    match feature {
+        Feature::Escrow => flags.escrow_enabled,
+        Feature::Commitments => flags.commitments_enabled,
+        Feature::Privacy => flags.privacy_enabled,
+    }
+}
+
+/// Fail with `FeatureDisabled` unless `feature` is enabled
+///
+/// Call before any storage write so a disabled module leaves no state behind.
+pub fn require_enabled(env: &Env, feature: Feature) -> Result<(), QuickexError> {
+    if !is_enabled(env, feature) {
+        return Err(QuickexError::FeatureDisabled);
+    }
+    Ok(())
+}
+
+/// Turn on the modules set in `flags` (Admin only)
+///
+/// Flags left false keep their current value: a module can never be switched
+/// off again, so state created while it was on is never stranded.
+pub fn enable(env: &Env, caller: Address, flags: FeatureFlags) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let current = get_feature_flags(env);
+    set_feature_flags(
+        env,
+        &FeatureFlags {
+            escrow_enabled: current.escrow_enabled || flags.escrow_enabled,
+            commitments_enabled: current.commitments_enabled || flags.commitments_enabled,
+            privacy_enabled: current.privacy_enabled || flags.privacy_enabled,
+        },
+    );
+
+    Ok(())
+}
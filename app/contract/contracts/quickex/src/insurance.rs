@@ -0,0 +1,61 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::escrow::MAX_BPS;
+use crate::events::publish_insurance_claim_paid;
+use crate::storage::{
+    get_collected_fees, get_escrow_by_id, get_insurance_pool, get_insurance_share_bps,
+    set_collected_fees, set_insurance_pool, set_insurance_share_bps,
+};
+use soroban_sdk::{token, Address, Env};
+
+/// Set the share of protocol fees diverted into the insurance pool (Admin only)
+pub fn set_share_bps(env: &Env, caller: Address, share_bps: u32) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    admin::validate_bps(share_bps)?;
+
+    set_insurance_share_bps(env, share_bps);
+    Ok(())
+}
+
+/// Split a collected fee between the insurance pool and the protocol balance
+pub fn accrue_fee(env: &Env, token: &Address, fee: i128) {
+    if fee <= 0 {
+        return;
+    }
+
+    let insured = fee * get_insurance_share_bps(env) as i128 / MAX_BPS as i128;
+    set_insurance_pool(env, token, get_insurance_pool(env, token) + insured);
+    set_collected_fees(env, token, get_collected_fees(env, token) + fee - insured);
+}
+
+/// Pay a claim for an escrow out of the pool of its token (Admin only)
+///
+/// The pool balance is tracked per token and a claim can never exceed it.
+pub fn pay_claim(
+    env: &Env,
+    caller: Address,
+    escrow_id: u64,
+    to: Address,
+    amount: i128,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    let escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    let pool = get_insurance_pool(env, &escrow.token);
+    if amount > pool {
+        return Err(QuickexError::InsufficientInsurancePool);
+    }
+    set_insurance_pool(env, &escrow.token, pool - amount);
+
+    let token_client = token::Client::new(env, &escrow.token);
+    token_client.transfer(&env.current_contract_address(), &to, &amount);
+
+    publish_insurance_claim_paid(env, escrow_id, to, escrow.token, amount);
+
+    Ok(())
+}
@@ -1,18 +1,46 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, token, Address, Bytes, BytesN, Env, Vec};
+use soroban_sdk::{contract, contractimpl, token, Address, Bytes, BytesN, Env, Map, Symbol, Vec};
 
+mod activity;
 mod admin;
+mod auction;
+mod circuit_breaker;
 mod commitment;
+mod deal;
+mod dispute;
 mod errors;
+mod escrow;
+mod event_log;
 mod events;
+mod export;
+mod features;
+mod insurance;
+mod merkle;
+mod metadata;
+mod migration;
 mod privacy;
+mod ranking;
+mod receiver;
+mod reporting;
+mod reputation;
+mod salt_escrow;
 mod storage;
+mod swap;
+mod template;
 mod types;
+mod yield_adapter;
 
 use errors::QuickexError;
 use events::publish_withdraw_toggled;
+use features::Feature;
 use storage::*;
-use types::{EscrowEntry, EscrowStatus};
+use types::{
+    AccountExport, ActivityEntry, AggregateReport, AnchoredRoot, Auction, CommitmentParams,
+    CommitmentProofBundle, CommitmentRecord, ContractConfig, CounterpartyStats, DealSummary,
+    Denomination, DisputeMessage, Escrow, EscrowBatchItem, EscrowEntry, EscrowStatus,
+    EscrowTemplate, EventSummary, FeatureFlags, FeeTiming, Metadata, MixedCommitment, ReserveProof,
+    Resolution, SettlementBreakdown, SettlementBucket, TermsPreimage,
+};
 
 /// Main contract structure
 #[contract]
@@ -21,9 +49,13 @@ pub struct QuickexContract;
 #[contractimpl]
 impl QuickexContract {
     /// Withdraw funds by proving commitment ownership
+    ///
+    /// Deposits made under an asset-bound (v2) commitment only pay out when
+    /// `token` matches the token that was deposited; token-less (v1)
+    /// commitments are still accepted.
     pub fn withdraw(
         env: Env,
-        _token: &Address,
+        token: &Address,
         amount: i128,
         _commitment: BytesN<32>,
         to: Address,
@@ -36,10 +68,22 @@ impl QuickexContract {
 
         to.require_auth();
 
-        let commitment = commitment::create_amount_commitment(&env, to.clone(), amount, salt)?;
-
-        let entry: EscrowEntry =
-            get_escrow(&env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+        let asset_commitment = commitment::create_asset_amount_commitment(
+            &env,
+            to.clone(),
+            token.clone(),
+            amount,
+            salt.clone(),
+        )?;
+        let (commitment, entry) = match get_escrow(&env, &asset_commitment.clone().into()) {
+            Some(entry) => {
+                if entry.token != *token {
+                    return Err(QuickexError::TokenMismatch);
+                }
+                (asset_commitment, entry)
+            }
+            None => commitment::find_amount_deposit(&env, to.clone(), amount, salt)?,
+        };
 
         if entry.status != EscrowStatus::Pending {
             return Err(QuickexError::AlreadySpent);
@@ -52,144 +96,3569 @@ impl QuickexContract {
         let mut updated_entry = entry.clone();
         updated_entry.status = EscrowStatus::Spent;
         put_escrow(&env, &commitment.clone().into(), &updated_entry);
+        increment_spent_commitment_count(&env);
 
         let token_client = token::Client::new(&env, &entry.token);
         token_client.transfer(&env.current_contract_address(), &to, &amount);
+        reporting::adjust_owed(&env, DataKey::DepositsHeld(entry.token.clone()), -amount);
+
+        publish_withdraw_toggled(&env, to.clone(), commitment.clone());
+
+        // Callback runs last so a misbehaving receiver only ever sees final state
+        if !receiver::notify_released(&env, &to, &commitment, &entry.token, amount) {
+            set_callback_failed(&env, &commitment);
+        }
+
+        Ok(true)
+    }
+
+    /// Set how long spent commitments are kept before they can be pruned (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `seconds` - Retention period counted from the deposit
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_nullifier_retention(
+        env: Env,
+        caller: Address,
+        seconds: u64,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_nullifier_retention(&env, seconds);
+        Ok(())
+    }
+
+    /// Get how many commitments have been withdrawn
+    ///
+    /// Counts every nullifier ever recorded; pruning spent entries doesn't
+    /// lower it.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u64` - Number of commitments spent
+    pub fn spent_commitment_count(env: Env) -> u64 {
+        get_spent_commitment_count(&env)
+    }
+
+    /// Remove spent commitment entries past their retention period (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `ids` - Commitments to prune; ineligible ones are skipped
+    ///
+    /// # Returns
+    /// * `Result<u32, QuickexError>` - Number of entries removed
+    pub fn prune_nullifiers(
+        env: Env,
+        caller: Address,
+        ids: Vec<BytesN<32>>,
+    ) -> Result<u32, QuickexError> {
+        commitment::prune_nullifiers(&env, caller, ids)
+    }
+
+    /// Register a contract to receive release callbacks (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `contract` - The receiver contract implementing `on_escrow_released`
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn register_receiver(
+        env: Env,
+        caller: Address,
+        contract: Address,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_receiver(&env, &contract);
+        Ok(())
+    }
+
+    /// Stop release and settlement callbacks to a contract (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `contract` - The receiver contract to drop
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn clear_receiver(
+        env: Env,
+        caller: Address,
+        contract: Address,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        remove_receiver(&env, &contract);
+        Ok(())
+    }
+
+    /// Check whether a contract is registered for release callbacks
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `contract` - The contract address to query
+    ///
+    /// # Returns
+    /// * `bool` - True if the contract is a registered receiver
+    pub fn is_receiver(env: Env, contract: Address) -> bool {
+        is_receiver(&env, &contract)
+    }
+
+    /// Check whether the release callback for a withdrawn commitment trapped
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The withdrawn commitment
+    ///
+    /// # Returns
+    /// * `bool` - True if a registered receiver's `on_escrow_released` call failed
+    pub fn has_callback_failed(env: Env, commitment: BytesN<32>) -> bool {
+        has_callback_failed(&env, &commitment)
+    }
+
+    /// Set or clear the contract notified when an escrow is released or refunded
+    ///
+    /// On settlement the contract gets `on_escrow_event(escrow_id, status)`.
+    /// The call is a try-invoke, so a failing callback doesn't block the payout.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The escrow's sender
+    /// * `escrow_id` - The escrow
+    /// * `notify` - A registered receiver contract, or None to clear it
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the sender
+    /// * `EscrowAlreadyResolved` - The escrow has already settled
+    /// * `ReceiverNotRegistered` - The contract is not a registered receiver
+    pub fn set_escrow_notify(
+        env: Env,
+        from: Address,
+        escrow_id: u64,
+        notify: Option<Address>,
+    ) -> Result<(), QuickexError> {
+        escrow::set_notify(&env, from, escrow_id, notify)
+    }
 
-        publish_withdraw_toggled(&env, to, commitment);
+    /// Get the contract notified when an escrow settles, if any
+    pub fn get_escrow_notify(env: Env, escrow_id: u64) -> Option<Address> {
+        get_escrow_notify(&env, escrow_id)
+    }
 
+    pub fn enable_privacy(
+        env: Env,
+        account: Address,
+        privacy_level: u32,
+    ) -> Result<bool, QuickexError> {
+        features::require_enabled(&env, Feature::Privacy)?;
+        set_privacy_level(&env, &account, privacy_level);
+        add_privacy_history(&env, &account, privacy_level);
         Ok(true)
     }
 
-    pub fn enable_privacy(env: Env, account: Address, privacy_level: u32) -> bool {
-        set_privacy_level(&env, &account, privacy_level);
-        add_privacy_history(&env, &account, privacy_level);
-        true
+    pub fn privacy_status(env: Env, account: Address) -> Option<u32> {
+        get_privacy_level(&env, &account)
+    }
+
+    pub fn privacy_history(env: Env, account: Address) -> Vec<u32> {
+        get_privacy_history(&env, &account)
+    }
+
+    /// Enable or disable privacy for an account
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account address to configure
+    /// * `enabled` - True to enable privacy, False to disable
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_privacy(env: Env, owner: Address, enabled: bool) -> Result<(), QuickexError> {
+        privacy::set_privacy(&env, owner, enabled)
+    }
+
+    /// Enable privacy for an account until a given time
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account address to configure
+    /// * `valid_until` - Ledger timestamp at which privacy lapses
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if the time has already passed
+    pub fn set_privacy_until(
+        env: Env,
+        owner: Address,
+        valid_until: u64,
+    ) -> Result<(), QuickexError> {
+        privacy::set_privacy_until(&env, owner, valid_until)
+    }
+
+    /// Make every escrow an account creates from now on private
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account address to configure (must authorize)
+    /// * `enabled` - True to create private escrows by default
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_private_escrows_by_default(
+        env: Env,
+        owner: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        privacy::set_private_escrows_by_default(&env, owner, enabled)
+    }
+
+    /// Check whether an account creates private escrows by default
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account address to query
+    ///
+    /// # Returns
+    /// * `bool` - True if new escrows from the account are private
+    pub fn get_private_escrows_by_default(env: Env, owner: Address) -> bool {
+        get_private_escrows_by_default(&env, &owner)
+    }
+
+    /// Check the current privacy status of an account
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account address to query
+    ///
+    /// # Returns
+    /// * `bool` - Current privacy status (true = enabled)
+    pub fn get_privacy(env: Env, owner: Address) -> bool {
+        privacy::get_privacy(&env, owner)
+    }
+
+    /// Reset privacy to the default (disabled) for a list of accounts (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `accounts` - Accounts whose privacy settings are cleared
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn admin_reset_privacy(
+        env: Env,
+        caller: Address,
+        accounts: Vec<Address>,
+    ) -> Result<(), QuickexError> {
+        privacy::admin_reset_privacy(&env, caller, accounts)
+    }
+
+    /// Delete the caller's stored privacy settings and history
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account whose data is removed (must authorize)
+    pub fn forget_me(env: Env, owner: Address) {
+        privacy::forget_me(&env, owner)
+    }
+
+    /// Turn the deterministic commitment test mode on or off (Admin only)
+    ///
+    /// INSECURE: while on, `create_amount_commitment` ignores the salt so
+    /// integration tests get reproducible hashes. Refused on the public network.
+    /// Each commitment keeps the mode it was deposited or registered under, so
+    /// switching back doesn't strand test deposits. Emits `TestModeChanged`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `enabled` - True to turn test mode on
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `TestModeForbidden` on mainnet
+    pub fn set_test_mode(env: Env, caller: Address, enabled: bool) -> Result<(), QuickexError> {
+        commitment::set_test_mode(&env, caller, enabled)
+    }
+
+    /// Check whether the deterministic commitment test mode is on
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `bool` - True if commitments currently ignore the salt
+    pub fn is_test_mode(env: Env) -> bool {
+        is_test_mode(&env)
+    }
+
+    /// Set whether escrows in a token hide amounts in events by default (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token address
+    /// * `enabled` - True to hide amounts even for senders without account-level privacy
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_token_privacy_default(
+        env: Env,
+        caller: Address,
+        token: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_token_privacy_default(&env, &token, enabled);
+        Ok(())
+    }
+
+    /// Set the smallest amount accepted for escrows in a token (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token address
+    /// * `min` - Minimum escrow amount and partial-funding deposit, in the token's base units; 0 removes it
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_token_min_amount(
+        env: Env,
+        caller: Address,
+        token: Address,
+        min: i128,
+    ) -> Result<(), QuickexError> {
+        admin::set_token_min_amount(&env, caller, token, min)
+    }
+
+    /// Get the smallest amount accepted for escrows in a token (0 = any positive amount)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `i128` - The minimum in the token's base units
+    pub fn get_token_min_amount(env: Env, token: Address) -> i128 {
+        get_token_min_amount(&env, &token)
+    }
+
+    /// Check whether escrows in a token hide amounts in events by default
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `bool` - True if the token's privacy default is enabled
+    pub fn get_token_privacy_default(env: Env, token: Address) -> bool {
+        get_token_privacy_default(&env, &token)
+    }
+
+    /// Deposit funds and create an escrow entry
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    /// * `amount` - The amount to deposit
+    /// * `owner` - The owner of the funds
+    /// * `salt` - Random salt for privacy
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
+    pub fn deposit(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        features::require_enabled(&env, Feature::Commitments)?;
+
+        if amount <= 0 {
+            return Err(QuickexError::InvalidAmount);
+        }
+        admin::require_token_not_paused(&env, &token)?;
+
+        owner.require_auth();
+
+        let commitment = commitment::create_amount_commitment(&env, owner.clone(), amount, salt)?;
+        commitment::record_mode(&env, &commitment);
+
+        let entry = EscrowEntry {
+            token: token.clone(),
+            amount,
+            owner: owner.clone(),
+            status: EscrowStatus::Pending,
+            created_at: env.ledger().timestamp(),
+        };
+
+        put_escrow(&env, &commitment.clone().into(), &entry);
+        push_owner_deposit(&env, &owner, &commitment);
+        reporting::record_token(&env, &token);
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&owner, env.current_contract_address(), &amount);
+        reporting::adjust_owed(&env, DataKey::DepositsHeld(token), amount);
+
+        Ok(commitment)
+    }
+
+    /// Create a commitment for a hidden amount
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to commit
+    /// * `salt` - Random salt for privacy
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
+    pub fn create_amount_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_amount_commitment(&env, owner, amount, salt)
+    }
+
+    /// Compute an amount commitment without touching storage; anyone may call
+    ///
+    /// Same hash as `create_amount_commitment`. Only the registry and deposit
+    /// entry points store commitments, and those require the owner's auth so
+    /// nobody can fill another account's registry.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner bound into the commitment
+    /// * `amount` - The amount to commit
+    /// * `salt` - Random salt for privacy
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
+    pub fn compute_amount_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_amount_commitment(&env, owner, amount, salt)
+    }
+
+    /// Encode a commitment as lowercase hex for display and logging
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash
+    ///
+    /// # Returns
+    /// * `Bytes` - 64 ASCII hex characters
+    pub fn commitment_to_hex(env: Env, commitment: BytesN<32>) -> Bytes {
+        commitment::commitment_to_hex(&env, &commitment)
+    }
+
+    /// Recompute the commitments for a batch of openings
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the funds
+    /// * `amounts` - The committed amounts
+    /// * `salts` - The salts, paired with `amounts` by position
+    ///
+    /// # Returns
+    /// * `Result<Vec<BytesN<32>>, QuickexError>` - The commitments in input order, or `InvalidBatch` if the lengths differ
+    pub fn recompute_commitments_batch(
+        env: Env,
+        owner: Address,
+        amounts: Vec<i128>,
+        salts: Vec<Bytes>,
+    ) -> Result<Vec<BytesN<32>>, QuickexError> {
+        commitment::recompute_batch(&env, owner, amounts, salts)
+    }
+
+    /// Create a commitment binding both the token and the hidden amount (scheme v2)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the funds
+    /// * `token` - The token the amount is denominated in
+    /// * `amount` - The amount to commit
+    /// * `salt` - Random salt for privacy
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
+    pub fn create_asset_amount_commitment(
+        env: Env,
+        owner: Address,
+        token: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_asset_amount_commitment(&env, owner, token, amount, salt)
+    }
+
+    /// Create a commitment bound to the current ledger time (scheme v3)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to commit
+    /// * `salt` - Random salt for privacy
+    ///
+    /// # Returns
+    /// * `Result<(BytesN<32>, u64), QuickexError>` - The commitment hash and the timestamp it includes
+    pub fn create_timestamped_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<(BytesN<32>, u64), QuickexError> {
+        commitment::create_timestamped_commitment(&env, owner, amount, salt)
+    }
+
+    /// Verify a scheme v3 commitment with the timestamp it was created at
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash to verify
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The committed amount
+    /// * `created_at` - The timestamp returned when the commitment was created
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `bool` - True if the opening and timestamp match
+    pub fn verify_timestamped_commitment(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        created_at: u64,
+        salt: Bytes,
+    ) -> bool {
+        commitment::verify_timestamped_commitment(&env, commitment, owner, amount, created_at, salt)
+    }
+
+    /// Create a commitment with on-chain randomness mixed into the salt
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to commit
+    /// * `salt` - Client salt, combined with contract PRNG output
+    ///
+    /// # Returns
+    /// * `Result<MixedCommitment, QuickexError>` - The commitment and the effective salt to store
+    pub fn create_amount_commitment_mixed(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<MixedCommitment, QuickexError> {
+        commitment::create_mixed_commitment(&env, owner, amount, salt)
+    }
+
+    /// Commit to full escrow terms (recipient, token, amount, deadline, arbiter, fee)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The sender who will fund the escrow
+    /// * `terms` - The terms to commit to
+    /// * `salt` - Random salt to keep the terms hidden
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The terms commitment hash
+    pub fn create_terms_commitment(
+        env: Env,
+        owner: Address,
+        terms: TermsPreimage,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_terms_commitment(&env, owner, terms, salt)
+    }
+
+    /// Reveal committed terms and open an escrow with exactly those terms
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The sender who made the commitment (must authorize)
+    /// * `commitment` - The terms commitment hash
+    /// * `terms` - The revealed terms
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id, or `TermsMismatch` if the reveal is wrong
+    pub fn create_escrow_from_terms(
+        env: Env,
+        owner: Address,
+        commitment: BytesN<32>,
+        terms: TermsPreimage,
+        salt: Bytes,
+    ) -> Result<u64, QuickexError> {
+        commitment::create_escrow_from_terms(&env, owner, commitment, terms, salt)
+    }
+
+    /// Verify a commitment matches the provided values
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash to verify
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to verify
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `bool` - True if valid
+    pub fn verify_amount_commitment(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> bool {
+        commitment::verify_amount_commitment(&env, commitment, owner, amount, salt)
+    }
+
+    /// Verify a commitment and return the recomputed hash alongside the result
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash to verify
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to verify
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `Result<(bool, BytesN<32>), QuickexError>` - Whether it matched, and the hash the inputs produce
+    pub fn verify_and_diff(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<(bool, BytesN<32>), QuickexError> {
+        commitment::verify_and_diff(&env, commitment, owner, amount, salt)
+    }
+
+    /// Deposit an encrypted salt for a commitment with a trusted recovery party
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The commitment owner (must authorize)
+    /// * `commitment` - The commitment the salt belongs to
+    /// * `blob` - Encrypted salt, opaque to the contract, at most 256 bytes
+    /// * `recovery` - The address allowed to recover the blob
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn store_salt_blob(
+        env: Env,
+        owner: Address,
+        commitment: BytesN<32>,
+        blob: Bytes,
+        recovery: Address,
+    ) -> Result<(), QuickexError> {
+        salt_escrow::store_salt_blob(&env, owner, commitment, blob, recovery)
+    }
+
+    /// Start the recovery delay for an escrowed salt as the recovery party
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment whose salt is being recovered
+    /// * `recovery` - The designated recovery address (must authorize)
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn request_salt_recovery(
+        env: Env,
+        commitment: BytesN<32>,
+        recovery: Address,
+    ) -> Result<(), QuickexError> {
+        salt_escrow::request_recovery(&env, commitment, recovery)
+    }
+
+    /// Cancel a pending salt recovery as the owner
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The commitment owner (must authorize)
+    /// * `commitment` - The commitment whose recovery is cancelled
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn cancel_salt_recovery(
+        env: Env,
+        owner: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        salt_escrow::cancel_recovery(&env, owner, commitment)
+    }
+
+    /// Release an escrowed salt blob to the recovery party after the delay
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `hash` - The commitment whose salt is released
+    /// * `recovery` - The designated recovery address (must authorize)
+    ///
+    /// # Returns
+    /// * `Result<Bytes, QuickexError>` - The encrypted blob, also emitted in `SaltBlobReleased`
+    pub fn release_salt_blob(
+        env: Env,
+        hash: BytesN<32>,
+        recovery: Address,
+    ) -> Result<Bytes, QuickexError> {
+        salt_escrow::release_salt_blob(&env, hash, recovery)
+    }
+
+    /// Set the delay between a salt recovery request and release (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `seconds` - The recovery delay
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_salt_recovery_delay(
+        env: Env,
+        caller: Address,
+        seconds: u64,
+    ) -> Result<(), QuickexError> {
+        salt_escrow::set_delay(&env, caller, seconds)
+    }
+
+    /// Get the parameters of the commitment hashing scheme
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `CommitmentParams` - Algorithm, rounds, domain tag, format version and salt cap
+    pub fn commitment_params(env: Env) -> CommitmentParams {
+        commitment::commitment_params(&env)
+    }
+
+    /// Get the parameters of the asset-bound (v2) commitment scheme
+    ///
+    /// Same as `commitment_params` but version 2, whose preimage has
+    /// `xdr(token)` after the owner.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `CommitmentParams` - Algorithm, rounds, domain tag, format version and salt cap
+    pub fn asset_commitment_params(env: Env) -> CommitmentParams {
+        commitment::asset_commitment_params(&env)
+    }
+
+    /// Get the parameters of the timestamped (v3) commitment scheme
+    ///
+    /// Same as `commitment_params` but version 3, whose preimage has the
+    /// big-endian `created_at` after the amount.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `CommitmentParams` - Algorithm, rounds, domain tag, format version and salt cap
+    pub fn timestamped_commitment_params(env: Env) -> CommitmentParams {
+        commitment::timestamped_commitment_params(&env)
+    }
+
+    /// Register a commitment in the on-chain registry
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the commitment (must authorize)
+    /// * `commitment` - The commitment hash to register
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The registry id assigned to the commitment
+    pub fn register_commitment(
+        env: Env,
+        owner: Address,
+        commitment: BytesN<32>,
+    ) -> Result<u64, QuickexError> {
+        commitment::register_commitment(&env, owner, commitment)
+    }
+
+    /// Register an asset-bound (v2) commitment with the token it binds
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the commitment (must authorize)
+    /// * `token` - The token bound into the commitment
+    /// * `commitment` - The commitment hash to register
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The registry id assigned to the commitment
+    pub fn register_asset_commitment(
+        env: Env,
+        owner: Address,
+        token: Address,
+        commitment: BytesN<32>,
+    ) -> Result<u64, QuickexError> {
+        commitment::register_asset_commitment(&env, owner, token, commitment)
+    }
+
+    /// Register a batch of commitments and anchor their Merkle root
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner of the commitments (must authorize)
+    /// * `leaves` - Between 1 and 64 commitment hashes
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The anchored root for later inclusion proofs
+    pub fn register_commitments_anchored(
+        env: Env,
+        owner: Address,
+        leaves: Vec<BytesN<32>>,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::register_anchored(&env, owner, leaves)
+    }
+
+    /// Get the record of an anchored commitment root
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `root` - The Merkle root
+    ///
+    /// # Returns
+    /// * `Option<AnchoredRoot>` - Owner, leaf count and ledger, if the root was anchored
+    pub fn get_anchored_root(env: Env, root: BytesN<32>) -> Option<AnchoredRoot> {
+        commitment::find_anchored(&env, &root).ok()
+    }
+
+    /// `get_anchored_root` that says why a root is missing
+    ///
+    /// # Returns
+    /// * `Result<AnchoredRoot, QuickexError>` - `CommitmentNotFound`, or `FeatureDisabled` if commitments are off
+    pub fn query_anchored_root(env: Env, root: BytesN<32>) -> Result<AnchoredRoot, QuickexError> {
+        commitment::find_anchored(&env, &root)
+    }
+
+    /// Get the latest anchored root recorded at or before a ledger
+    ///
+    /// With an inclusion proof against the returned root, this shows that a
+    /// commitment existed by that ledger. Only the last 256 roots are kept.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `ledger` - The ledger sequence to look back from
+    ///
+    /// # Returns
+    /// * `Option<(BytesN<32>, u32)>` - The root and the ledger it was anchored in
+    pub fn get_root_at_or_before(env: Env, ledger: u32) -> Option<(BytesN<32>, u32)> {
+        commitment::root_at_or_before(&env, ledger)
+    }
+
+    /// Check that a commitment is included under an anchored root
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `root` - The anchored Merkle root
+    /// * `leaf` - The commitment hash
+    /// * `proof` - Sibling hashes from the leaf level up
+    ///
+    /// # Returns
+    /// * `Result<bool, QuickexError>` - True if the proof is valid and the root was anchored; `ProofTooLong` above the configured maximum
+    pub fn verify_inclusion(
+        env: Env,
+        root: BytesN<32>,
+        leaf: BytesN<32>,
+        proof: Vec<BytesN<32>>,
+    ) -> Result<bool, QuickexError> {
+        commitment::verify_anchored(&env, root, leaf, proof)
+    }
+
+    /// Set the most sibling hashes an inclusion proof may carry (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `max_len` - Proof length cap; 32 by default, enough for 2^32 leaves
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    ///
+    /// # Errors
+    /// * `InvalidProofLen` - `max_len` is 0, which would refuse every proof
+    pub fn set_max_proof_len(env: Env, caller: Address, max_len: u32) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        if max_len == 0 {
+            return Err(QuickexError::InvalidProofLen);
+        }
+        set_max_proof_len(&env, max_len);
+        events::publish_max_proof_len_set(&env, max_len, &caller);
+        Ok(())
+    }
+
+    /// Get the most sibling hashes an inclusion proof may carry
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u32` - The proof length cap
+    pub fn get_max_proof_len(env: Env) -> u32 {
+        get_max_proof_len(&env)
+    }
+
+    /// Get a registered commitment by its registry id
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `id` - The registry id
+    ///
+    /// # Returns
+    /// * `Option<CommitmentRecord>` - The registry record if the id is known
+    pub fn get_registered_commitment(env: Env, id: u64) -> Option<CommitmentRecord> {
+        commitment::find_registered(&env, id).ok()
+    }
+
+    /// Export a registered commitment's public parameters for off-chain verifiers
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash
+    ///
+    /// # Returns
+    /// * `Result<CommitmentProofBundle, QuickexError>` - Scheme, owner, ledger, anchoring and status; `CommitmentNotFound` if unregistered
+    pub fn export_commitment_proof(
+        env: Env,
+        commitment: BytesN<32>,
+    ) -> Result<CommitmentProofBundle, QuickexError> {
+        commitment::export_proof(&env, commitment)
+    }
+
+    /// `get_registered_commitment` that says why a record is missing
+    ///
+    /// # Returns
+    /// * `Result<CommitmentRecord, QuickexError>` - `CommitmentNotFound`, or `FeatureDisabled` if commitments are off
+    pub fn query_registered_commitment(
+        env: Env,
+        id: u64,
+    ) -> Result<CommitmentRecord, QuickexError> {
+        commitment::find_registered(&env, id)
+    }
+
+    /// Verify an opening against the commitment registered under `id`
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `id` - The registry id of the commitment
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to verify
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `bool` - True if valid, false if the opening mismatches or the id is unknown
+    pub fn verify_registered(env: Env, id: u64, owner: Address, amount: i128, salt: Bytes) -> bool {
+        commitment::verify_registered(&env, id, owner, amount, salt)
+    }
+
+    /// Verify an opening of a commitment that must be in the on-chain registry
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - The commitment hash
+    /// * `owner` - The owner of the funds
+    /// * `amount` - The amount to verify
+    /// * `salt` - The salt used for the commitment
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - `CommitmentNotRegistered` for unknown hashes, `CommitmentMismatch` for a wrong opening
+    pub fn verify_and_require_registered(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<(), QuickexError> {
+        commitment::verify_and_require_registered(&env, commitment, owner, amount, salt)
+    }
+
+    /// Create an escrow, transferring the funds from the sender into the contract
+    ///
+    /// Native XLM is escrowed by passing `native_token()` as `token`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - Sender address (must authorize)
+    /// * `to` - Recipient address
+    /// * `token` - The token address
+    /// * `amount` - Amount to escrow
+    /// * `memo` - Free-form reference, at most `MAX_MEMO_LEN` bytes
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The escrow id
+    pub fn create_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo: Bytes,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_escrow(&env, from, to, token, amount, memo)
+    }
+
+    /// Get the Stellar Asset Contract address of native XLM on this network
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `Address` - The token address to escrow native XLM with
+    pub fn native_token(env: Env) -> Address {
+        escrow::native_token(&env)
+    }
+
+    /// Check a batch of escrows without creating anything
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender who would fund every escrow
+    /// * `items` - Recipient, token, amount and memo of each escrow
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if `create_escrow_batch` would accept the batch now
+    pub fn validate_escrow_batch(
+        env: Env,
+        from: Address,
+        items: Vec<EscrowBatchItem>,
+    ) -> Result<(), QuickexError> {
+        escrow::validate_escrow_batch(&env, from, items)
+    }
+
+    /// Create several escrows from one sender, all or nothing
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding every escrow
+    /// * `items` - Recipient, token, amount and memo of each escrow (max 20)
+    ///
+    /// # Returns
+    /// * `Result<Vec<u64>, QuickexError>` - The new escrow ids, in item order
+    pub fn create_escrow_batch(
+        env: Env,
+        from: Address,
+        items: Vec<EscrowBatchItem>,
+    ) -> Result<Vec<u64>, QuickexError> {
+        escrow::create_escrow_batch(&env, from, items)
+    }
+
+    /// Fund an escrow claimable by any one of several recipients
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `token` - The token contract address
+    /// * `amount` - The amount to lock
+    /// * `allowed` - Addresses allowed to claim; the first to claim gets the funds
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_multi_recipient_escrow(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+        allowed: Vec<Address>,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_multi_recipient_escrow(&env, from, token, amount, allowed)
+    }
+
+    /// Claim a multi-recipient escrow as one of its allowed recipients
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `claimant` - An allowed recipient
+    /// * `escrow_id` - The escrow to claim
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn claim_escrow(env: Env, claimant: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::claim_escrow(&env, claimant, escrow_id)
+    }
+
+    /// Settle every escrow in a token that a recipient can release without further approval
+    ///
+    /// These are the multi-recipient escrows that allow the recipient. A
+    /// single-recipient escrow always needs the sender or admin to release it,
+    /// so it is skipped.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `recipient` - The claiming recipient (must authorize)
+    /// * `token` - Only escrows in this token are claimed
+    /// * `limit` - Most escrows to settle in this call
+    ///
+    /// # Returns
+    /// * `Result<Vec<u64>, QuickexError>` - The ids settled, paid out in one transfer
+    pub fn claim_all(
+        env: Env,
+        recipient: Address,
+        token: Address,
+        limit: u32,
+    ) -> Result<Vec<u64>, QuickexError> {
+        escrow::claim_all(&env, recipient, token, limit)
+    }
+
+    /// Release a pending escrow to its recipient
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The sender of the escrow or the admin
+    /// * `escrow_id` - The escrow to release
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    ///
+    /// # Errors
+    /// * `EscrowNotFound` - No escrow has this id
+    /// * `EscrowAlreadyReleased` - The escrow was already paid out
+    pub fn release_escrow(env: Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::release_escrow(&env, caller, escrow_id)
+    }
+
+    /// Settle an escrow with a negotiated split between recipient and sender
+    ///
+    /// The amounts must add up to what the escrow holds. The admin can't
+    /// split a disputed escrow that has its own arbiter.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Either party (both must then authorize) or the admin
+    /// * `escrow_id` - The escrow to settle
+    /// * `to_recipient` - Amount for the recipient, before the protocol fee
+    /// * `to_sender` - Amount returned to the sender
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `SplitMismatch` if the amounts don't add up
+    pub fn release_split_amounts(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        to_recipient: i128,
+        to_sender: i128,
+    ) -> Result<(), QuickexError> {
+        escrow::release_split_amounts(&env, caller, escrow_id, to_recipient, to_sender)
+    }
+
+    /// Create an escrow that credits the recipient on release instead of paying out
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient, who later calls `withdraw_credit`
+    /// * `token` - The token address
+    /// * `amount` - The amount to lock
+    /// * `memo` - Optional reference, at most 64 bytes
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_pull_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo: Bytes,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_pull_escrow(&env, from, to, token, amount, memo)
+    }
+
+    /// Hash a claim code the way `claim_with_code` does
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `code` - The human-entered code; surrounding whitespace and case are ignored
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The lock to pass to `create_code_escrow`
+    pub fn hash_claim_code(env: Env, code: Bytes) -> Result<BytesN<32>, QuickexError> {
+        escrow::hash_claim_code(&env, &code)
+    }
+
+    /// Create an escrow the recipient claims by entering a short code
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `amount` - The amount to lock
+    /// * `code_hash` - Lock from `hash_claim_code`
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_code_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        code_hash: BytesN<32>,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_code_escrow(&env, from, to, token, amount, code_hash)
+    }
+
+    /// Claim a code escrow by entering its code
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `claimer` - The escrow recipient (must authorize)
+    /// * `escrow_id` - The code escrow
+    /// * `code` - The code as entered
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `InvalidClaimCode` for a wrong code
+    pub fn claim_with_code(
+        env: Env,
+        claimer: Address,
+        escrow_id: u64,
+        code: Bytes,
+    ) -> Result<(), QuickexError> {
+        escrow::claim_with_code(&env, claimer, escrow_id, code)
+    }
+
+    /// Read an account's escrow activity since a ledger
+    ///
+    /// Every status change of an escrow the account sends or receives is
+    /// logged with its ledger, so clients without an indexer can ask what
+    /// happened since they last looked.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account
+    /// * `from_ledger` - Earliest ledger to include
+    /// * `offset` - Matching entries to skip
+    /// * `limit` - Entries to return, at most 100
+    ///
+    /// # Returns
+    /// * `Vec<ActivityEntry>` - (escrow id, new status, ledger) entries, oldest first
+    pub fn get_activity(
+        env: Env,
+        owner: Address,
+        from_ledger: u32,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<ActivityEntry> {
+        activity::read(&env, &owner, from_ledger, offset, limit)
+    }
+
+    /// Read an account's escrow activity one stored page at a time
+    ///
+    /// Cheaper than `get_activity` for long feeds: each call loads exactly
+    /// one page of 32 entries instead of re-reading the skipped ones.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account
+    /// * `cursor` - 0 for the first page, then the cursor returned by the previous call
+    ///
+    /// # Returns
+    /// * `(Vec<ActivityEntry>, Option<u32>)` - The page's entries, oldest first, and the next cursor or None once exhausted
+    pub fn get_activity_by_cursor(
+        env: Env,
+        owner: Address,
+        cursor: u32,
+    ) -> (Vec<ActivityEntry>, Option<u32>) {
+        activity::read_page(&env, &owner, cursor)
+    }
+
+    /// List the commitments an account has deposited under, one stored page at a time
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account
+    /// * `cursor` - 0 for the first page, then the cursor returned by the previous call
+    ///
+    /// # Returns
+    /// * `(Vec<BytesN<32>>, Option<u32>)` - Up to 32 commitments, oldest first, and the next cursor or None once exhausted
+    pub fn list_owner_commitments(
+        env: Env,
+        owner: Address,
+        cursor: u32,
+    ) -> (Vec<BytesN<32>>, Option<u32>) {
+        export::owner_commitments(&env, &owner, cursor)
+    }
+
+    /// Export an account's open escrows, outstanding commitment deposits
+    /// and privacy settings, for moving to a new wallet
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account
+    ///
+    /// # Returns
+    /// * `AccountExport` - The positions with a sha256 digest over their XDR (digest field zeroed)
+    pub fn export_account(env: Env, owner: Address) -> AccountExport {
+        export::export_account(&env, owner)
+    }
+
+    /// Export one page of an account's positions, for accounts too large to
+    /// export in one call
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account
+    /// * `page` - Page to read, starting at 0
+    ///
+    /// # Returns
+    /// * `AccountExport` - The positions on this page, each page with its own digest; `has_more` is set while pages remain
+    pub fn export_account_page(env: Env, owner: Address, page: u32) -> AccountExport {
+        export::export_account_page(&env, owner, page)
+    }
+
+    /// Get an account's settlement record as a counterparty
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `account` - The account to look up
+    ///
+    /// # Returns
+    /// * `CounterpartyStats` - Clean completions as sender and recipient, disputes opened against it and disputes lost; only `opted_out` is set if the account hides its record
+    pub fn get_counterparty_stats(env: Env, account: Address) -> CounterpartyStats {
+        reputation::stats(&env, account)
+    }
+
+    /// Hide or show an account's settlement record
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `account` - The account (must authorize and, to opt out, have privacy enabled)
+    /// * `opted_out` - True to hide the record while privacy stays enabled
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - `PrivacyNotEnabled` when opting out without privacy
+    pub fn set_counterparty_stats_opt_out(
+        env: Env,
+        account: Address,
+        opted_out: bool,
+    ) -> Result<(), QuickexError> {
+        reputation::set_opt_out(&env, account, opted_out)
+    }
+
+    /// List every distinct token that has ever been escrowed
+    ///
+    /// Covers id-based and commitment escrows, oldest first, so operators can
+    /// check the contract's balance of each asset against TVL.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `Vec<Address>` - Token addresses, each listed once
+    pub fn list_escrowed_tokens(env: Env) -> Vec<Address> {
+        get_escrowed_tokens(&env)
+    }
+
+    /// Create an escrow priced in a reference unit such as cents
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `ref_amount` - Amount in the reference unit
+    /// * `rate` - Token base units per reference unit
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id, `AmountOverflow` if the product overflows
+    pub fn create_escrow_denominated(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        ref_amount: i128,
+        rate: i128,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_escrow_denominated(&env, from, to, token, ref_amount, rate)
+    }
+
+    /// Get the reference-unit amount and rate an escrow was created from
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    ///
+    /// # Returns
+    /// * `Option<Denomination>` - None for escrows created in token units
+    pub fn get_escrow_denomination(env: Env, escrow_id: u64) -> Option<Denomination> {
+        get_denomination(&env, escrow_id)
+    }
+
+    /// Create an escrow whose refunds go to a different address than the sender
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `amount` - The amount to lock
+    /// * `memo` - Optional reference, at most 64 bytes
+    /// * `refund_to` - Address that receives refunds, e.g. a treasury
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_escrow_with_refund_to(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo: Bytes,
+        refund_to: Address,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_escrow_with_refund_to(&env, from, to, token, amount, memo, refund_to)
+    }
+
+    /// Create an escrow, optionally without publishing its creation event
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `amount` - The amount to lock
+    /// * `memo` - Optional reference, at most 64 bytes
+    /// * `emit_event` - False to skip the `EscrowCreated` event; indexers won't see the escrow
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_escrow_with_event(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo: Bytes,
+        emit_event: bool,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_escrow_with_event(&env, from, to, token, amount, memo, emit_event)
+    }
+
+    /// Create an escrow that a retried submission won't duplicate
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `amount` - The amount to lock
+    /// * `memo` - Optional reference, at most 64 bytes
+    /// * `idempotency_key` - Client-chosen key, unique per intended escrow; remembered for about a day
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id, or the one this key already created with the same parameters; `IdempotencyConflict` if the parameters differ
+    pub fn create_escrow_idempotent(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo: Bytes,
+        idempotency_key: BytesN<32>,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_escrow_idempotent(&env, from, to, token, amount, memo, idempotency_key)
+    }
+
+    /// Change or clear where an escrow's refunds go, before any dispute or settlement
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    /// * `caller` - The escrow sender (must authorize)
+    /// * `refund_to` - New refund address, or None to refund the sender
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `RefundAddressLocked` once disputed
+    pub fn set_refund_address(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        refund_to: Option<Address>,
+    ) -> Result<(), QuickexError> {
+        escrow::set_refund_address(&env, escrow_id, caller, refund_to)
+    }
+
+    /// Credit an escrow's refunds to the withdrawable balance instead of transferring them
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    /// * `caller` - The escrow sender (must authorize)
+    /// * `enabled` - Whether refunds are credited internally
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `RefundAddressLocked` once disputed
+    pub fn set_refund_to_internal(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        escrow::set_refund_to_internal(&env, escrow_id, caller, enabled)
+    }
+
+    /// Create an escrow that renews for another period each time it is released
+    ///
+    /// Any release renews it, including splits and dispute rulings for the
+    /// recipient; a refund ends the subscription.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The subscriber funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `amount` - Amount per period
+    /// * `interval` - Seconds per period
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The first period's escrow id
+    pub fn create_recurring_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        interval: u64,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_recurring_escrow(&env, from, to, token, amount, interval)
+    }
+
+    /// Stop a recurring escrow from renewing
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The subscriber (must authorize)
+    /// * `escrow_id` - The current period's escrow
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful
+    pub fn cancel_recurring(env: Env, from: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::cancel_recurring(&env, from, escrow_id)
+    }
+
+    /// Add funds to an account's withdrawable credit, used to renew recurring escrows
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account depositing (must authorize)
+    /// * `token` - The token address
+    /// * `amount` - The amount to deposit
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - The new credit balance
+    pub fn deposit_credit(
+        env: Env,
+        owner: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, QuickexError> {
+        escrow::deposit_credit(&env, owner, token, amount)
+    }
+
+    /// Withdraw everything credited to a recipient in a token
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `recipient` - The credited recipient
+    /// * `token` - The token to withdraw
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - The amount paid out
+    pub fn withdraw_credit(
+        env: Env,
+        recipient: Address,
+        token: Address,
+    ) -> Result<i128, QuickexError> {
+        escrow::withdraw_credit(&env, recipient, token)
+    }
+
+    /// Withdraw everything credited to a recipient in a token to another address
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `recipient` - The credited recipient (must authorize)
+    /// * `token` - The token to withdraw
+    /// * `dest` - Where to send the funds; must be approved if the recipient opted in
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - The amount paid out
+    pub fn withdraw_credit_to(
+        env: Env,
+        recipient: Address,
+        token: Address,
+        dest: Address,
+    ) -> Result<i128, QuickexError> {
+        escrow::withdraw_credit_to(&env, recipient, token, dest)
+    }
+
+    /// Opt an account in or out of restricting withdrawals to approved destinations
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account (must authorize)
+    /// * `enabled` - Whether the whitelist applies
+    pub fn set_withdraw_whitelist(env: Env, owner: Address, enabled: bool) {
+        escrow::set_withdraw_whitelist(&env, owner, enabled)
+    }
+
+    /// Approve a destination for an account's credit withdrawals
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account (must authorize)
+    /// * `dest` - The destination to approve
+    pub fn add_withdraw_destination(env: Env, owner: Address, dest: Address) {
+        escrow::add_withdraw_destination(&env, owner, dest)
+    }
+
+    /// Revoke a destination approved for an account's credit withdrawals
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account (must authorize)
+    /// * `dest` - The destination to revoke
+    pub fn remove_withdraw_destination(env: Env, owner: Address, dest: Address) {
+        escrow::remove_withdraw_destination(&env, owner, dest)
+    }
+
+    /// Get the released funds a recipient can withdraw in a token
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `recipient` - The recipient to query
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `i128` - The withdrawable balance
+    pub fn get_withdrawable(env: Env, recipient: Address, token: Address) -> i128 {
+        get_withdrawable(&env, &token, &recipient)
+    }
+
+    /// Open an escrow that the sender funds in tranches
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender who will fund the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token address
+    /// * `target` - Total amount the escrow must reach to become active
+    /// * `memo` - Optional reference, at most 64 bytes
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id, in `Funding` status
+    pub fn create_tranche_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        target: i128,
+        memo: Bytes,
+    ) -> Result<u64, QuickexError> {
+        escrow::create_tranche_escrow(&env, from, to, token, target, memo)
+    }
+
+    /// Deposit a tranche into an escrow that is still being funded
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The escrow's sender
+    /// * `escrow_id` - The escrow id
+    /// * `amount` - Amount to deposit
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - Amount funded so far; the escrow is active once it reaches the target
+    pub fn fund_escrow_partial(
+        env: Env,
+        from: Address,
+        escrow_id: u64,
+        amount: i128,
+    ) -> Result<i128, QuickexError> {
+        escrow::fund_escrow_partial(&env, from, escrow_id, amount)
+    }
+
+    /// Contribute to a tranche escrow as one of up to 16 funders
+    ///
+    /// Each funder's contribution is tracked: a refund returns it to them,
+    /// and release pays the recipient the full target.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow, still in `Funding`
+    /// * `funder` - The contributor (must authorize)
+    /// * `amount` - Amount to contribute
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - Amount funded so far; `Overfunded` past the target, `TooManyFunders` for a 17th funder
+    pub fn fund_escrow_shared(
+        env: Env,
+        escrow_id: u64,
+        funder: Address,
+        amount: i128,
+    ) -> Result<i128, QuickexError> {
+        escrow::fund_escrow_shared(&env, escrow_id, funder, amount)
+    }
+
+    /// Get what each shared funder has contributed to an escrow
+    pub fn get_escrow_funders(env: Env, escrow_id: u64) -> Map<Address, i128> {
+        get_funders(&env, escrow_id)
+    }
+
+    /// Hash an exchange rate for use as a swap escrow's rate commitment
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `num` - Rate numerator
+    /// * `den` - Rate denominator
+    /// * `salt` - Random salt hiding the rate until settlement
+    ///
+    /// # Returns
+    /// * `BytesN<32>` - The rate commitment
+    pub fn create_rate_commitment(env: Env, num: i128, den: i128, salt: Bytes) -> BytesN<32> {
+        swap::create_rate_commitment(&env, num, den, salt)
+    }
+
+    /// Lock an input amount against a committed exchange rate
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender funding the input side
+    /// * `to` - The recipient, who pays the output side at settlement
+    /// * `token_in` - Token escrowed now
+    /// * `token_out` - Token the recipient pays at settlement
+    /// * `amount_in` - Amount of `token_in` to escrow
+    /// * `rate_commitment` - Commitment to the `num / den` rate
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new escrow id
+    pub fn create_swap_escrow(
+        env: Env,
+        from: Address,
+        to: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        rate_commitment: BytesN<32>,
+    ) -> Result<u64, QuickexError> {
+        swap::create_swap_escrow(
+            &env,
+            from,
+            to,
+            token_in,
+            token_out,
+            amount_in,
+            rate_commitment,
+        )
+    }
+
+    /// Open a sealed-bid auction
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `seller` - The seller (must authorize)
+    /// * `item` - What is being auctioned
+    /// * `reveal_after` - Ledger timestamp at which bidding closes and reveals open
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The new auction id
+    pub fn open_auction(
+        env: Env,
+        seller: Address,
+        item: Symbol,
+        reveal_after: u64,
+    ) -> Result<u64, QuickexError> {
+        auction::open_auction(&env, seller, item, reveal_after)
+    }
+
+    /// Place or replace a hidden bid before bidding closes
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `bidder` - The bidder (must authorize)
+    /// * `auction_id` - The auction to bid in
+    /// * `commitment` - `create_amount_commitment(bidder, amount, salt)` of the bid
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn submit_bid(
+        env: Env,
+        bidder: Address,
+        auction_id: u64,
+        commitment: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        auction::submit_bid(&env, bidder, auction_id, commitment)
+    }
+
+    /// Reveal a bid during the reveal period
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `bidder` - The bidder (must authorize)
+    /// * `auction_id` - The auction bid in
+    /// * `amount` - The committed bid amount
+    /// * `salt` - The salt used in the commitment
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if the bid opened, Error otherwise
+    pub fn reveal_bid(
+        env: Env,
+        bidder: Address,
+        auction_id: u64,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<(), QuickexError> {
+        auction::reveal_bid(&env, bidder, auction_id, amount, salt)
+    }
+
+    /// Close an auction once its reveal period is over
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `auction_id` - The auction to finalize
+    ///
+    /// # Returns
+    /// * `Result<Option<Address>, QuickexError>` - The highest revealed bidder, if any
+    pub fn finalize_auction(env: Env, auction_id: u64) -> Result<Option<Address>, QuickexError> {
+        auction::finalize_auction(&env, auction_id)
+    }
+
+    /// Get an auction by id
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `auction_id` - The auction to query
+    ///
+    /// # Returns
+    /// * `Option<Auction>` - The auction, if it exists
+    pub fn get_auction(env: Env, auction_id: u64) -> Option<Auction> {
+        get_auction(&env, auction_id)
+    }
+
+    /// Start a deal grouping related escrows
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The deal owner (must authorize)
+    ///
+    /// # Returns
+    /// * `u64` - The new deal id
+    pub fn create_deal(env: Env, owner: Address) -> u64 {
+        deal::create_deal(&env, owner)
+    }
+
+    /// Link an escrow into a deal (at most 16 per deal, one deal per escrow)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `deal_id` - The deal
+    /// * `escrow_id` - The escrow to link
+    /// * `caller` - The deal owner, who must also be a party of the escrow
+    ///
+    /// # Errors
+    /// * `DealNotFound` - Unknown deal
+    /// * `Unauthorized` - Caller does not own the deal or is not a party
+    /// * `EscrowAlreadyLinked` - The escrow already belongs to a deal
+    /// * `TooManyDealEscrows` - The deal already links 16 escrows
+    pub fn link_escrow_to_deal(
+        env: Env,
+        deal_id: u64,
+        escrow_id: u64,
+        caller: Address,
+    ) -> Result<(), QuickexError> {
+        deal::link_escrow(&env, deal_id, escrow_id, caller)
+    }
+
+    /// Get a deal's linked escrow ids and aggregate status
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `deal_id` - The deal to query
+    ///
+    /// # Returns
+    /// * `Option<DealSummary>` - The deal, if it exists; Complete only once every linked escrow was released
+    pub fn get_deal(env: Env, deal_id: u64) -> Option<DealSummary> {
+        deal::deal_summary(&env, deal_id)
+    }
+
+    /// Reveal the committed rate and settle both legs of a swap escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The swap escrow id
+    /// * `num` - Committed rate numerator
+    /// * `den` - Committed rate denominator
+    /// * `salt` - Salt used in the rate commitment
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - Output amount paid to the sender
+    pub fn settle_swap(
+        env: Env,
+        escrow_id: u64,
+        num: i128,
+        den: i128,
+        salt: Bytes,
+    ) -> Result<i128, QuickexError> {
+        swap::settle_swap(&env, escrow_id, num, den, salt)
+    }
+
+    /// Correct the memo of an escrow while it is still pending
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The sender of the escrow (must authorize)
+    /// * `escrow_id` - The escrow to update
+    /// * `memo` - The new memo, at most `MAX_MEMO_LEN` bytes
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn update_escrow_memo(
+        env: Env,
+        from: Address,
+        escrow_id: u64,
+        memo: Bytes,
+    ) -> Result<(), QuickexError> {
+        escrow::update_escrow_memo(&env, from, escrow_id, memo)
+    }
+
+    /// List escrows between two counterparties, in either direction
+    ///
+    /// The pair is order-insensitive; use each escrow's `from`/`to` to
+    /// recover its direction.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `a` - One counterparty
+    /// * `b` - The other counterparty
+    /// * `offset` - Number of ids to skip
+    /// * `limit` - Maximum number of ids to return
+    ///
+    /// # Returns
+    /// * `Vec<u64>` - Escrow ids in creation order
+    pub fn list_escrows_between(
+        env: Env,
+        a: Address,
+        b: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<u64> {
+        escrow::list_escrows_between(&env, a, b, offset, limit)
+    }
+
+    /// Save reusable escrow settings for a repeat counterparty
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The template owner (must authorize)
+    /// * `template` - Recipient, token, arbiter, deadline offset and fee settings
+    ///
+    /// # Returns
+    /// * `Result<u32, QuickexError>` - The template id, scoped to the owner
+    pub fn create_template(
+        env: Env,
+        owner: Address,
+        template: EscrowTemplate,
+    ) -> Result<u32, QuickexError> {
+        template::create_template(&env, owner, template)
+    }
+
+    /// Replace an existing template; escrows already created from it are unaffected
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The template owner (must authorize)
+    /// * `template_id` - The template to replace
+    /// * `template` - The new settings
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn update_template(
+        env: Env,
+        owner: Address,
+        template_id: u32,
+        template: EscrowTemplate,
+    ) -> Result<(), QuickexError> {
+        template::update_template(&env, owner, template_id, template)
+    }
+
+    /// Delete a template, freeing its slot
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The template owner (must authorize)
+    /// * `template_id` - The template to delete
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn delete_template(env: Env, owner: Address, template_id: u32) -> Result<(), QuickexError> {
+        template::delete_template(&env, owner, template_id)
+    }
+
+    /// Get one of an owner's templates
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The template owner
+    /// * `template_id` - The template id
+    ///
+    /// # Returns
+    /// * `Option<EscrowTemplate>` - The template if it exists
+    pub fn get_template(env: Env, owner: Address, template_id: u32) -> Option<EscrowTemplate> {
+        template::get_template(&env, owner, template_id).ok()
+    }
+
+    /// `get_template` that says why a template is missing
+    ///
+    /// # Returns
+    /// * `Result<EscrowTemplate, QuickexError>` - `TemplateNotFound`, or `FeatureDisabled` if escrows are off
+    pub fn query_template(
+        env: Env,
+        owner: Address,
+        template_id: u32,
+    ) -> Result<EscrowTemplate, QuickexError> {
+        template::get_template(&env, owner, template_id)
+    }
+
+    /// Create an escrow from a template plus an amount
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The template owner, who funds the escrow (must authorize)
+    /// * `template_id` - The template to apply
+    /// * `amount` - Amount to escrow
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - The escrow id
+    pub fn create_from_template(
+        env: Env,
+        owner: Address,
+        template_id: u32,
+        amount: i128,
+    ) -> Result<u64, QuickexError> {
+        template::create_from_template(&env, owner, template_id, amount)
+    }
+
+    /// Choose between detailed and compact escrow events as a recipient
+    ///
+    /// Compact events carry only the escrow id and counterparties as topics,
+    /// for indexers that don't need full payloads.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The recipient account (must authorize)
+    /// * `detailed` - True for full events (the default), false for compact ones
+    pub fn set_event_detail(env: Env, owner: Address, detailed: bool) {
+        owner.require_auth();
+        set_event_detail(&env, &owner, detailed);
+    }
+
+    /// Check whether escrow events for a recipient carry full detail
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The recipient account
+    ///
+    /// # Returns
+    /// * `bool` - True if detailed (the default)
+    pub fn get_event_detail(env: Env, owner: Address) -> bool {
+        get_event_detail(&env, &owner)
+    }
+
+    /// Set the bond posted to open a dispute, in bps of the escrow amount (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `bond_bps` - Bond size in basis points; zero disables bonds
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_dispute_bond_bps(
+        env: Env,
+        caller: Address,
+        bond_bps: u32,
+    ) -> Result<(), QuickexError> {
+        dispute::set_bond_bps(&env, caller, bond_bps)
+    }
+
+    /// Dispute a pending escrow, posting the configured bond
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The sender or recipient of the escrow
+    /// * `escrow_id` - The escrow to dispute
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn open_dispute(env: Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        dispute::open_dispute(&env, caller, escrow_id)
+    }
+
+    /// Rule on a disputed escrow and settle the dispute bond
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The escrow's arbiter, or the admin if it has none
+    /// * `escrow_id` - The disputed escrow
+    /// * `release` - True to release to the recipient, false to refund the sender
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn resolve_dispute(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        release: bool,
+    ) -> Result<(), QuickexError> {
+        dispute::resolve_dispute(&env, caller, escrow_id, release)
+    }
+
+    /// Settle a disputed escrow by splitting it between recipient and sender
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The disputed escrow
+    /// * `arbiter` - The escrow's arbiter, or the admin if it has none
+    /// * `to_recipient_bps` - Share of the amount, after fees, awarded to the recipient
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn resolve_dispute_split(
+        env: Env,
+        escrow_id: u64,
+        arbiter: Address,
+        to_recipient_bps: u32,
+    ) -> Result<(), QuickexError> {
+        dispute::resolve_dispute_split(&env, escrow_id, arbiter, to_recipient_bps)
+    }
+
+    /// Post the hash of a negotiation message on a disputed escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The disputed escrow
+    /// * `caller` - Sender, recipient or resolver (must authorize)
+    /// * `msg_hash` - Hash of the off-chain message
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn post_dispute_message(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        msg_hash: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        dispute::post_message(&env, escrow_id, caller, msg_hash)
+    }
+
+    /// Anchor the hash of dispute evidence on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Sender or recipient (must authorize)
+    /// * `escrow_id` - A pending or disputed escrow
+    /// * `evidence_hash` - Hash of the off-chain evidence
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn submit_evidence(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        dispute::submit_evidence(&env, caller, escrow_id, evidence_hash)
+    }
+
+    /// List the evidence hashes anchored on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    ///
+    /// # Returns
+    /// * `Vec<BytesN<32>>` - Evidence hashes in submission order
+    pub fn get_evidence(env: Env, escrow_id: u64) -> Vec<BytesN<32>> {
+        get_evidence(&env, escrow_id)
+    }
+
+    /// List the messages posted in an escrow's dispute, in posting order
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    /// * `offset` - Number of messages to skip
+    /// * `limit` - Maximum number of messages to return
+    ///
+    /// # Returns
+    /// * `Vec<DisputeMessage>` - Sender, message hash and timestamp of each message
+    pub fn get_dispute_messages(
+        env: Env,
+        escrow_id: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<DisputeMessage> {
+        dispute::messages(&env, escrow_id, offset, limit)
+    }
+
+    /// Set the share of protocol fees that accrues to the insurance pool (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `share_bps` - Share of each collected fee, in basis points
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_insurance_share_bps(
+        env: Env,
+        caller: Address,
+        share_bps: u32,
+    ) -> Result<(), QuickexError> {
+        insurance::set_share_bps(&env, caller, share_bps)
+    }
+
+    /// Get the insurance pool balance for a token
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `i128` - The pool balance available for claims
+    pub fn get_insurance_pool(env: Env, token: Address) -> i128 {
+        get_insurance_pool(&env, &token)
+    }
+
+    /// Get the protocol fees collected in a token, net of the insurance share
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `i128` - The collected fee balance
+    pub fn get_collected_fees(env: Env, token: Address) -> i128 {
+        get_collected_fees(&env, &token)
+    }
+
+    /// Pay an insurance claim for an escrow from its token's pool (Admin only)
+    ///
+    /// Used when a dispute finds fault that can't be made whole from the
+    /// escrow itself.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `escrow_id` - The escrow the claim relates to
+    /// * `to` - The claimant receiving the payout
+    /// * `amount` - The amount to pay, bounded by the pool balance
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn pay_insurance_claim(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        to: Address,
+        amount: i128,
+    ) -> Result<(), QuickexError> {
+        insurance::pay_claim(&env, caller, escrow_id, to, amount)
+    }
+
+    /// Set the maximum time an escrow may stay funded (Admin only)
+    ///
+    /// New escrows can't have a deadline beyond `now + seconds`, and escrows
+    /// without one get that deadline. Zero disables the limit.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `seconds` - The maximum escrow lifetime
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_max_escrow_lifetime_secs(
+        env: Env,
+        caller: Address,
+        seconds: u64,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_max_escrow_lifetime(&env, seconds);
+        Ok(())
+    }
+
+    /// Get the maximum escrow lifetime in seconds (0 = unlimited)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u64` - The configured maximum lifetime
+    pub fn max_escrow_lifetime_secs(env: Env) -> u64 {
+        get_max_escrow_lifetime(&env)
+    }
+
+    /// Refund a pending escrow that outlived the maximum lifetime; callable by anyone
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The stale escrow
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn force_expire(env: Env, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::force_expire(&env, escrow_id)
+    }
+
+    /// Return a pending escrow whose deadline has passed to its sender
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The expired escrow (sender must authorize)
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn claim_expired(env: Env, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::claim_expired(&env, escrow_id)
+    }
+
+    /// Refund an expired escrow for its sender after the grace period, for a keeper reward
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The expired escrow
+    /// * `keeper` - Address that receives the reward
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - The reward paid to the keeper
+    pub fn sweep_expired(env: Env, escrow_id: u64, keeper: Address) -> Result<i128, QuickexError> {
+        escrow::sweep_expired(&env, escrow_id, keeper)
+    }
+
+    /// Set the reward and grace period for keepers sweeping expired escrows (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `reward_bps` - Keeper reward in bps of the escrow, at most 100 (1%)
+    /// * `grace` - Seconds after the deadline during which only the sender can claim
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_keeper_incentive(
+        env: Env,
+        caller: Address,
+        reward_bps: u32,
+        grace: u64,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        admin::validate_keeper_reward(reward_bps)?;
+        set_keeper_incentive(&env, reward_bps, grace);
+        Ok(())
+    }
+
+    /// Set how long before a deadline `poke` warns the recipient (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `seconds` - Warning window before the deadline, 0 to disable warnings
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_deadline_warning_window(
+        env: Env,
+        caller: Address,
+        seconds: u64,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_deadline_warning_window(&env, seconds);
+        Ok(())
+    }
+
+    /// Get how long before a deadline `poke` warns the recipient
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u64` - Warning window in seconds, 0 if disabled
+    pub fn get_deadline_warning_window(env: Env) -> u64 {
+        get_deadline_warning_window(&env)
+    }
+
+    /// Emit the one-time deadline warning for an escrow if it is due
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to check
+    ///
+    /// # Returns
+    /// * `Result<bool, QuickexError>` - Whether the warning was emitted by this call
+    pub fn poke(env: Env, escrow_id: u64) -> Result<bool, QuickexError> {
+        escrow::poke(&env, escrow_id)
+    }
+
+    /// Check a configuration without applying it
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `config` - The configuration to check
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if `set_config` would accept the values
+    pub fn validate_config(_env: Env, config: ContractConfig) -> Result<(), QuickexError> {
+        admin::validate_config(&config)
+    }
+
+    /// Replace every tunable parameter at once (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `config` - The new configuration
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized or invalid
+    pub fn set_config(
+        env: Env,
+        caller: Address,
+        config: ContractConfig,
+    ) -> Result<(), QuickexError> {
+        admin::set_config(&env, caller, config)
+    }
+
+    /// Get the current value of every tunable parameter
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `ContractConfig` - The current configuration
+    pub fn get_config(env: Env) -> ContractConfig {
+        admin::get_config(&env)
+    }
+
+    /// Set a cooling-off delay before large escrows can be released (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `threshold` - Escrows above this amount are subject to the delay; zero disables it
+    /// * `delay` - Seconds after creation before release is permitted
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_cooling_off(
+        env: Env,
+        caller: Address,
+        threshold: i128,
+        delay: u64,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        admin::validate_cooling_off(threshold)?;
+        set_cooling_off(&env, threshold, delay);
+        Ok(())
+    }
+
+    /// Cap the total escrow amount released per day (Admin only)
+    ///
+    /// Releases and claims that would take the day's total over the cap fail
+    /// with `DailyLimitExceeded`. Days are UTC, by ledger timestamp.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `cap` - Maximum amount released per day; zero disables the breaker
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_daily_outflow_cap(env: Env, caller: Address, cap: i128) -> Result<(), QuickexError> {
+        circuit_breaker::set_cap(&env, caller, cap)
+    }
+
+    /// Get how much can still be released today before the breaker trips
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `i128` - Cap minus today's releases, or `i128::MAX` when no cap is set
+    pub fn remaining_daily_limit(env: Env) -> i128 {
+        circuit_breaker::remaining(&env)
+    }
+
+    /// Choose whether the protocol fee is charged when an escrow is funded or released (Admin only)
+    ///
+    /// Applies to escrows funded after the change; existing escrows keep the
+    /// fee they have already been charged.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `timing` - `OnCreate` or `OnRelease`
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_fee_timing(
+        env: Env,
+        caller: Address,
+        timing: FeeTiming,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_fee_timing(&env, timing);
+        Ok(())
+    }
+
+    /// Set the fee rate for escrows created without their own `fee_bps` (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `bps` - Fee in basis points, at most 10,000
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `InvalidFeeBps` above 100%
+    pub fn set_default_fee_bps(env: Env, caller: Address, bps: u32) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        if bps > escrow::MAX_BPS {
+            return Err(QuickexError::InvalidFeeBps);
+        }
+        set_default_fee_bps(&env, bps);
+        Ok(())
+    }
+
+    /// Get the fee rate for escrows without their own `fee_bps`
+    ///
+    /// # Returns
+    /// * `u32` - Fee in basis points (default 0)
+    pub fn get_default_fee_bps(env: Env) -> u32 {
+        get_default_fee_bps(&env)
+    }
+
+    /// Get the fee rate a new escrow of `amount` would pay without its own `fee_bps`
+    ///
+    /// Currently the flat default for every size; clients should use this
+    /// rather than `get_default_fee_bps` so they pick up any future tiers.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `amount` - Size of the hypothetical escrow
+    ///
+    /// # Returns
+    /// * `u32` - Fee in basis points
+    pub fn effective_fee_bps(env: Env, amount: i128) -> u32 {
+        escrow::fee_bps_for_amount(&env, amount)
+    }
+
+    /// Exempt an account's escrows, sent or received, from the protocol fee (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `account` - The account
+    /// * `exempt` - True to exempt, false to charge fees again
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_fee_exempt(
+        env: Env,
+        caller: Address,
+        account: Address,
+        exempt: bool,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_fee_exempt(&env, &account, exempt);
+        Ok(())
+    }
+
+    /// Check whether an account's escrows are exempt from the protocol fee
+    pub fn is_fee_exempt(env: Env, account: Address) -> bool {
+        is_fee_exempt(&env, &account)
+    }
+
+    /// Preview the fee and the recipient's net if an escrow were released now
+    ///
+    /// Applies the same rules as release: a party exemption, then the escrow's
+    /// own `fee_bps`, then the default; a fee already charged at creation stands.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    ///
+    /// # Returns
+    /// * `Result<(i128, i128), QuickexError>` - (fee, net to recipient)
+    pub fn quote_escrow_release(env: Env, escrow_id: u64) -> Result<(i128, i128), QuickexError> {
+        escrow::quote_release(&env, escrow_id)
+    }
+
+    /// Preview every payout if an open escrow settled with `outcome` now
+    ///
+    /// Uses the same calculation as the settlement itself, so the amounts
+    /// match the transfers that would follow. Nothing is changed.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    /// * `outcome` - Release, refund, dispute split or keeper sweep
+    ///
+    /// # Returns
+    /// * `Result<SettlementBreakdown, QuickexError>` - Recipient, sender, fee, keeper and bond amounts
+    ///
+    /// # Errors
+    /// * `EscrowNotFound` - Unknown escrow
+    /// * `EscrowAlreadyResolved` - The escrow has already settled
+    /// * `InvalidFeeBps` - Split bps above 10,000
+    pub fn preview_settlement(
+        env: Env,
+        escrow_id: u64,
+        outcome: Resolution,
+    ) -> Result<SettlementBreakdown, QuickexError> {
+        escrow::preview_settlement(&env, escrow_id, outcome)
+    }
+
+    /// Get when the protocol fee is charged
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `FeeTiming` - The current setting (default: `OnRelease`)
+    pub fn get_fee_timing(env: Env) -> FeeTiming {
+        get_fee_timing(&env)
+    }
+
+    /// Cancel an open escrow and refund its sender, recording a reason (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `escrow_id` - The escrow to cancel
+    /// * `reason` - Short audit reason included in the `EscrowCancelled` event
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn admin_cancel_escrow(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        reason: Symbol,
+    ) -> Result<(), QuickexError> {
+        escrow::admin_cancel_escrow(&env, caller, escrow_id, reason)
+    }
+
+    /// Accept a pending escrow as its recipient, confirming the address is right
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to accept (recipient must authorize)
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn accept_escrow(env: Env, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::accept_escrow(&env, escrow_id)
+    }
+
+    /// Change the recipient of a pending escrow the recipient hasn't accepted yet
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - The escrow sender (must authorize)
+    /// * `escrow_id` - The escrow to fix
+    /// * `new_to` - The corrected recipient
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `CannotReassignAfterAcceptance` once accepted
+    pub fn reassign_recipient(
+        env: Env,
+        from: Address,
+        escrow_id: u64,
+        new_to: Address,
+    ) -> Result<(), QuickexError> {
+        escrow::reassign_recipient(&env, from, escrow_id, new_to)
+    }
+
+    /// Freeze a pending escrow, e.g. on suspected fraud, until it is unfrozen
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The sender or recipient of the escrow
+    /// * `escrow_id` - The escrow to freeze
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn freeze_escrow(env: Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::freeze_escrow(&env, caller, escrow_id)
+    }
+
+    /// Freeze a pending escrow with both parties' consent while they renegotiate
+    ///
+    /// Either party can lift a mutual freeze alone.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to freeze
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn freeze_escrow_mutual(env: Env, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::freeze_escrow_mutual(&env, escrow_id)
+    }
+
+    /// Lift a freeze as the party who set it, as either party after a mutual
+    /// freeze, or as the escrow's arbiter (the admin when it has none)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The freezing party, either party of a mutual freeze, or the resolver
+    /// * `escrow_id` - The frozen escrow
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn unfreeze_escrow(env: Env, caller: Address, escrow_id: u64) -> Result<(), QuickexError> {
+        escrow::unfreeze_escrow(&env, caller, escrow_id)
+    }
+
+    /// Tag an escrow for compliance review (Admin only)
+    ///
+    /// While any flag is set only the admin can release the escrow.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `escrow_id` - The escrow to flag
+    /// * `flag` - The flag, e.g. `high_risk`
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `TooManyFlags` past 8 flags
+    pub fn flag_escrow(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        flag: Symbol,
+    ) -> Result<(), QuickexError> {
+        escrow::flag_escrow(&env, caller, escrow_id, flag)
+    }
+
+    /// Clear a compliance flag from an escrow (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `escrow_id` - The escrow
+    /// * `flag` - The flag to clear
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn unflag_escrow(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        flag: Symbol,
+    ) -> Result<(), QuickexError> {
+        escrow::unflag_escrow(&env, caller, escrow_id, flag)
+    }
+
+    /// Get the compliance flags set on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    ///
+    /// # Returns
+    /// * `Vec<Symbol>` - Flags in the order they were set
+    pub fn get_escrow_flags(env: Env, escrow_id: u64) -> Vec<Symbol> {
+        get_escrow_flags(&env, escrow_id)
+    }
+
+    /// Get the status of an id-based escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to query
+    ///
+    /// # Returns
+    /// * `Option<EscrowStatus>` - The status, `Frozen` while frozen, or None if not found
+    pub fn get_escrow_status(env: Env, escrow_id: u64) -> Option<EscrowStatus> {
+        escrow::escrow_status(&env, escrow_id).ok()
+    }
+
+    /// `get_escrow_status` that says why an escrow is missing
+    ///
+    /// # Returns
+    /// * `Result<EscrowStatus, QuickexError>` - `EscrowNotFound`, or `FeatureDisabled` if escrows are off
+    pub fn query_escrow_status(env: Env, escrow_id: u64) -> Result<EscrowStatus, QuickexError> {
+        escrow::escrow_status(&env, escrow_id)
+    }
+
+    /// Check whether an account is a party to an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to query
+    /// * `account` - The account to check
+    ///
+    /// # Returns
+    /// * `bool` - True if the account is the sender, recipient or arbiter; false for unknown ids
+    pub fn is_party(env: Env, escrow_id: u64, account: Address) -> bool {
+        escrow::is_party(&env, escrow_id, account)
+    }
+
+    /// Cancel or dispute all of a sender's open escrows in an emergency
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `sender` - The sender unwinding their escrows (must authorize)
+    /// * `limit` - Most escrows to act on in this call
+    ///
+    /// # Returns
+    /// * `Result<(u32, u32), QuickexError>` - Counts of escrows cancelled and disputed
+    pub fn refund_all_mine(
+        env: Env,
+        sender: Address,
+        limit: u32,
+    ) -> Result<(u32, u32), QuickexError> {
+        escrow::refund_all_mine(&env, sender, limit)
+    }
+
+    /// Attach the hash of a party's KYC attestation to an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow
+    /// * `caller` - The sender or recipient attesting (must authorize)
+    /// * `attestation_hash` - Hash of the off-chain attestation
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if attached, `AttestationAlreadyAttached` if the party already has one
+    pub fn attach_attestation(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        attestation_hash: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        escrow::attach_attestation(&env, escrow_id, caller, attestation_hash)
+    }
+
+    /// Require both parties' attestations before an escrow is funded or released (Admin only)
+    ///
+    /// While on, tranche deposits and every path that pays a recipient
+    /// (release, claims, splits, swaps and dispute rulings for the recipient)
+    /// fail with `AttestationsMissing` until both parties have attested.
+    /// Escrows funded at creation are exempt from the funding check, as they
+    /// can only be attested once they exist. Multi-recipient escrows can't be
+    /// created, since no recipient is known to attest.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `required` - Whether attestations are required
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_require_attestations(
+        env: Env,
+        caller: Address,
+        required: bool,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        set_require_attestations(&env, required);
+        Ok(())
+    }
+
+    /// Whether escrows need both parties' attestations to be funded or released
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `bool` - True if attestations are required
+    pub fn get_require_attestations(env: Env) -> bool {
+        get_require_attestations(&env)
+    }
+
+    /// Attach the hash of a private off-chain note to an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to annotate
+    /// * `caller` - The sender, recipient or arbiter writing the note (must authorize)
+    /// * `note_hash` - Hash of the note, which stays off-chain
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn set_private_note(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        note_hash: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        escrow::set_private_note(&env, escrow_id, caller, note_hash)
+    }
+
+    /// Read the caller's own private note hash on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to query
+    /// * `caller` - The note's author (must authorize)
+    ///
+    /// # Returns
+    /// * `Result<BytesN<32>, QuickexError>` - The note hash, or `NoteNotFound`
+    pub fn get_private_note(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+    ) -> Result<BytesN<32>, QuickexError> {
+        escrow::get_private_note(&env, escrow_id, caller)
+    }
+
+    /// Register a third party as watching an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to watch
+    /// * `watcher` - The watching party (must authorize)
+    /// * `party` - The sender or recipient consenting to the watcher (must authorize)
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn add_watcher(
+        env: Env,
+        escrow_id: u64,
+        watcher: Address,
+        party: Address,
+    ) -> Result<(), QuickexError> {
+        escrow::add_watcher(&env, escrow_id, watcher, party)
+    }
+
+    /// List the watchers registered on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to query
+    ///
+    /// # Returns
+    /// * `Vec<Address>` - Watchers in registration order
+    pub fn list_watchers(env: Env, escrow_id: u64) -> Vec<Address> {
+        get_watchers(&env, escrow_id)
+    }
+
+    /// Let a third party see an escrow's full terms
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to share
+    /// * `owner` - The escrow's sender (must authorize)
+    /// * `viewer` - The address to allow
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if added or already present, Error otherwise
+    pub fn add_viewer(
+        env: Env,
+        escrow_id: u64,
+        owner: Address,
+        viewer: Address,
+    ) -> Result<(), QuickexError> {
+        escrow::add_viewer(&env, escrow_id, owner, viewer)
+    }
+
+    /// List the viewers allowed on an escrow
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow to query
+    ///
+    /// # Returns
+    /// * `Vec<Address>` - Viewers in the order they were added
+    pub fn list_viewers(env: Env, escrow_id: u64) -> Vec<Address> {
+        get_viewers(&env, escrow_id)
+    }
+
+    /// Get an escrow by id
+    ///
+    /// Private escrows come back with their amounts zeroed; parties read them
+    /// in full with `get_escrow_as`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow id
+    ///
+    /// # Returns
+    /// * `Option<Escrow>` - The escrow if it exists
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Option<Escrow> {
+        escrow::find_escrow(&env, escrow_id)
+            .map(escrow::redacted)
+            .ok()
+    }
+
+    /// Get several escrows by id in one call
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `ids` - Escrow ids to read, at most 100
+    ///
+    /// # Returns
+    /// * `Result<Vec<Option<Escrow>>, QuickexError>` - One entry per id in the same order, `None` for unknown ids; private escrows redacted as in `get_escrow`
+    pub fn get_escrows(env: Env, ids: Vec<u64>) -> Result<Vec<Option<Escrow>>, QuickexError> {
+        escrow::get_escrows(&env, ids)
+    }
+
+    /// `get_escrow` that says why an escrow is missing
+    ///
+    /// # Returns
+    /// * `Result<Escrow, QuickexError>` - `EscrowNotFound`, or `FeatureDisabled` if escrows are off
+    pub fn query_escrow(env: Env, escrow_id: u64) -> Result<Escrow, QuickexError> {
+        escrow::find_escrow(&env, escrow_id).map(escrow::redacted)
+    }
+
+    /// List the largest open escrows of a token for risk monitoring (Admin or auditor)
+    ///
+    /// Escrows are ranked as they are funded. The contract tracks the 50
+    /// largest so a settled escrow's slot is refilled from the runners-up;
+    /// only after 40 tracked escrows settle with no new funding can an open
+    /// escrow be missing from the top 10.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin or an auditor)
+    /// * `token` - The token to report on
+    ///
+    /// # Returns
+    /// * `Result<Vec<(u64, i128)>, QuickexError>` - Up to 10 (escrow id, amount) pairs, largest first, older first on ties
+    pub fn get_largest_escrows(
+        env: Env,
+        caller: Address,
+        token: Address,
+    ) -> Result<Vec<(u64, i128)>, QuickexError> {
+        ranking::largest(&env, caller, token)
+    }
+
+    /// Get an escrow by id as a given viewer
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - The escrow id
+    /// * `viewer` - The account reading (must authorize)
+    ///
+    /// # Returns
+    /// * `Option<Escrow>` - The escrow, with private amounts zeroed unless the viewer is a party or the arbiter
+    pub fn get_escrow_as(env: Env, escrow_id: u64, viewer: Address) -> Option<Escrow> {
+        escrow::view_escrow(&env, escrow_id, viewer).ok()
+    }
+
+    /// Aggregate settlement totals for a token over a ledger window
+    ///
+    /// Built from per-epoch counters (one epoch is 17,280 ledgers, about a
+    /// day), so it reveals no per-escrow detail. The window is widened to
+    /// whole epochs and may span at most 366 of them.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token to report on
+    /// * `from_ledger` - First ledger of the window
+    /// * `to_ledger` - Last ledger of the window
+    ///
+    /// # Returns
+    /// * `Result<AggregateReport, QuickexError>` - Settled count and volume in the window
+    pub fn get_aggregate_report(
+        env: Env,
+        token: Address,
+        from_ledger: u32,
+        to_ledger: u32,
+    ) -> Result<AggregateReport, QuickexError> {
+        reporting::aggregate_report(&env, token, from_ledger, to_ledger)
+    }
+
+    /// Get the amount currently held by open escrows in a token
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token to query
+    ///
+    /// # Returns
+    /// * `i128` - Funds held by funding, pending and disputed escrows, net of fees already taken
+    pub fn get_tvl(env: Env, token: Address) -> i128 {
+        get_tvl(&env, &token)
+    }
+
+    /// Prove the contract's balance of a token covers open escrows and accrued fees
+    ///
+    /// Computed live without listing escrows. Publishes `ReserveShortfall`
+    /// when it doesn't.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token to check
+    ///
+    /// # Returns
+    /// * `ReserveProof` - TVL, accrued fees, balance, parked principal, the `solvent` flag and the ledger
+    pub fn get_reserve_proof(env: Env, token: Address) -> ReserveProof {
+        reporting::reserve_proof(&env, token)
+    }
+
+    /// Rebuild a token's TVL from the escrows, one page of ids at a time (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token to recompute
+    /// * `start` - Scan position of the page, over imported ids and then created ones; 1 starts a new scan
+    /// * `limit` - Ids to scan in this call (capped at 200)
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - Sum so far; the stored TVL is replaced when the last page is scanned
+    ///
+    /// # Errors
+    /// * `StorageVersionMismatch` - Storage hasn't been migrated to the current version
+    pub fn recompute_tvl(
+        env: Env,
+        caller: Address,
+        token: Address,
+        start: u64,
+        limit: u32,
+    ) -> Result<i128, QuickexError> {
+        reporting::recompute_tvl(&env, caller, token, start, limit)
+    }
+
+    /// Read a page of the on-chain event summary log
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `start` - First sequence number to return; pruned entries are skipped
+    /// * `limit` - Maximum number of summaries (capped at 100)
+    ///
+    /// # Returns
+    /// * `Vec<EventSummary>` - Summaries in the order the events happened
+    pub fn get_event_log(env: Env, start: u64, limit: u32) -> Vec<EventSummary> {
+        event_log::page(&env, start, limit)
+    }
+
+    /// Get the range of sequence numbers currently held in the event log
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `(u64, u64)` - First retained sequence number and the next one to be written
+    pub fn get_event_log_bounds(env: Env) -> (u64, u64) {
+        get_event_log_bounds(&env)
+    }
+
+    /// Drop the oldest event summaries (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `before` - Entries below this sequence number are removed, at most 500 per call
+    ///
+    /// # Returns
+    /// * `Result<u64, QuickexError>` - Number of entries removed
+    pub fn prune_event_log(env: Env, caller: Address, before: u64) -> Result<u64, QuickexError> {
+        event_log::prune(&env, caller, before)
+    }
+
+    /// Register or clear the yield adapter that holds escrowed funds of a token (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token address
+    /// * `adapter` - Contract implementing `YieldAdapter`, or None for plain custody
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_yield_adapter(
+        env: Env,
+        caller: Address,
+        token: Address,
+        adapter: Option<Address>,
+    ) -> Result<(), QuickexError> {
+        yield_adapter::set_adapter(&env, caller, token, adapter)
+    }
+
+    /// Get the yield adapter registered for a token
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `Option<Address>` - The adapter, or None if funds stay in custody
+    pub fn get_yield_adapter(env: Env, token: Address) -> Option<Address> {
+        yield_adapter::adapter(&env, &token).ok()
+    }
+
+    /// `get_yield_adapter` that fails with `YieldAdapterNotSet` instead of returning None
+    ///
+    /// # Returns
+    /// * `Result<Address, QuickexError>` - The adapter
+    pub fn query_yield_adapter(env: Env, token: Address) -> Result<Address, QuickexError> {
+        yield_adapter::adapter(&env, &token)
+    }
+
+    /// Move yield earned by a token's adapter into collected fees (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `Result<i128, QuickexError>` - The yield harvested
+    pub fn harvest_yield(env: Env, caller: Address, token: Address) -> Result<i128, QuickexError> {
+        yield_adapter::harvest(&env, caller, token)
+    }
+
+    /// Get the escrow principal a yield adapter failed to return
+    ///
+    /// Those escrows were settled from custody instead; `harvest_yield`
+    /// recovers the amount once the adapter pays out again.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `adapter` - The adapter address
+    ///
+    /// # Returns
+    /// * `i128` - Principal still owed by the adapter
+    pub fn get_yield_shortfall(env: Env, adapter: Address) -> i128 {
+        get_yield_shortfall(&env, &adapter)
+    }
+
+    /// Get the settlement totals for a token in a single reporting epoch
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token to report on
+    /// * `epoch` - The epoch, i.e. ledger sequence / 17,280
+    ///
+    /// # Returns
+    /// * `SettlementBucket` - Settled count and volume in the epoch
+    pub fn get_settlement_bucket(env: Env, token: Address, epoch: u32) -> SettlementBucket {
+        reporting::bucket(&env, token, epoch)
+    }
+
+    /// Drop old settlement buckets for a token (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token whose buckets are pruned
+    /// * `from_epoch` - First epoch to drop
+    /// * `to_epoch` - Last epoch to drop
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
+    pub fn prune_settlement_buckets(
+        env: Env,
+        caller: Address,
+        token: Address,
+        from_epoch: u32,
+        to_epoch: u32,
+    ) -> Result<(), QuickexError> {
+        reporting::prune_buckets(&env, caller, token, from_epoch, to_epoch)
+    }
+
+    /// Get the escrow counter, the id of the most recently created escrow
+    ///
+    /// Created ids start at `RESERVED_ESCROW_IDS + 1`, so once storage is
+    /// migrated the counter minus `RESERVED_ESCROW_IDS` equals
+    /// `get_escrow_count`. Before migration a counter below
+    /// `RESERVED_ESCROW_IDS` is the legacy one and equals the count itself.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `Option<u64>` - The last assigned escrow id, or None if no escrow was ever created
+    pub fn get_escrow_counter(env: Env) -> Option<u64> {
+        find_escrow_counter(&env)
+    }
+
+    /// Get the number of id-based escrows created, not counting imports
+    ///
+    /// Created ids are assigned sequentially from `RESERVED_ESCROW_IDS + 1`
+    /// and never reused. Until storage is migrated, escrows created by a
+    /// build from before versioning are counted; migration moves them in
+    /// with the imports.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u64` - The number of escrows created
+    pub fn get_escrow_count(env: Env) -> u64 {
+        migration::created_escrow_count(&env)
     }
 
-    pub fn privacy_status(env: Env, account: Address) -> Option<u32> {
-        get_privacy_level(&env, &account)
+    /// Recreate a legacy escrow under its original id (Admin only)
+    ///
+    /// Ids below 1,000,000 are reserved for imports and never assigned by
+    /// the counter. The admin pays in the funds an open escrow holds.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `escrow_id` - The original id, from 1 to 999,999
+    /// * `record` - The escrow as it stood in the legacy system
+    ///
+    /// # Errors
+    /// * `EscrowIdNotImportable` - Id is 0 or at or above the reserved threshold
+    /// * `EscrowAlreadyExists` - An escrow already has this id
+    /// * `InvalidImportRecord` - The record carries a dispute bond, parked
+    ///   principal, a prepaid fee, an open dispute or a freeze
+    /// * Any error `create_escrow` validation returns for the record
+    pub fn import_escrow(
+        env: Env,
+        caller: Address,
+        escrow_id: u64,
+        record: Escrow,
+    ) -> Result<(), QuickexError> {
+        escrow::import_escrow(&env, caller, escrow_id, record)
     }
 
-    pub fn privacy_history(env: Env, account: Address) -> Vec<u32> {
-        get_privacy_history(&env, &account)
+    /// Set the salt for opaque escrow handles; can only be done once (Admin only)
+    ///
+    /// Once set, every new escrow also gets a handle of sha256(id || salt) so
+    /// clients can refer to escrows without exposing the sequential id.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `salt` - Secret salt mixed into every handle
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, `AlreadyInitialized` if already set
+    pub fn initialize_handle_salt(
+        env: Env,
+        caller: Address,
+        salt: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        admin::require_admin(&env, &caller)?;
+        if get_handle_salt(&env).is_some() {
+            return Err(QuickexError::AlreadyInitialized);
+        }
+        set_handle_salt(&env, &salt);
+        Ok(())
     }
 
-    /// Enable or disable privacy for an account
+    /// Replace the handle salt with a fresh random one (Admin only)
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `owner` - The account address to configure
-    /// * `enabled` - True to enable privacy, False to disable
+    /// * `caller` - The caller address (must be admin)
     ///
     /// # Returns
-    /// * `Result<(), QuickexError>` - Ok if successful, Error otherwise
-    pub fn set_privacy(env: Env, owner: Address, enabled: bool) -> Result<(), QuickexError> {
-        privacy::set_privacy(&env, owner, enabled)
+    /// * `Result<u32, QuickexError>` - The new salt version; `HandleSaltNotSet` before `initialize_handle_salt`
+    pub fn rotate_handle_salt(env: Env, caller: Address) -> Result<u32, QuickexError> {
+        escrow::rotate_handle_salt(&env, caller)
     }
 
-    /// Check the current privacy status of an account
+    /// Get the version of the current handle salt (0 until the first rotation)
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `owner` - The account address to query
     ///
     /// # Returns
-    /// * `bool` - Current privacy status (true = enabled)
-    pub fn get_privacy(env: Env, owner: Address) -> bool {
-        privacy::get_privacy(&env, owner)
+    /// * `u32` - The salt version new handles are derived with
+    pub fn handle_salt_version(env: Env) -> u32 {
+        get_handle_salt_version(&env)
     }
 
-    /// Deposit funds and create an escrow entry
+    /// Fund an escrow and return its opaque handle instead of the numeric id
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `token` - The token address
-    /// * `amount` - The amount to deposit
-    /// * `owner` - The owner of the funds
-    /// * `salt` - Random salt for privacy
+    /// * `from` - The sender funding the escrow
+    /// * `to` - The recipient
+    /// * `token` - The token contract address
+    /// * `amount` - The amount to lock
+    /// * `memo` - Free-form reference, at most 64 bytes
     ///
     /// # Returns
-    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
-    pub fn deposit(
+    /// * `Result<BytesN<32>, QuickexError>` - The escrow handle; `HandleSaltNotSet` if no salt is set
+    pub fn create_escrow_with_handle(
         env: Env,
+        from: Address,
+        to: Address,
         token: Address,
         amount: i128,
-        owner: Address,
-        salt: Bytes,
+        memo: Bytes,
     ) -> Result<BytesN<32>, QuickexError> {
-        if amount <= 0 {
-            return Err(QuickexError::InvalidAmount);
+        if get_handle_salt(&env).is_none() {
+            return Err(QuickexError::HandleSaltNotSet);
         }
+        let escrow_id = escrow::create_escrow(&env, from, to, token, amount, memo)?;
+        escrow::derive_handle(&env, escrow_id).ok_or(QuickexError::HandleSaltNotSet)
+    }
 
-        owner.require_auth();
+    /// Get an escrow by its opaque handle
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `handle` - The escrow handle
+    ///
+    /// # Returns
+    /// * `Option<Escrow>` - The escrow if the handle is known
+    pub fn get_escrow_by_handle(env: Env, handle: BytesN<32>) -> Option<Escrow> {
+        escrow::find_escrow_by_handle(&env, &handle).ok()
+    }
 
-        let commitment = commitment::create_amount_commitment(&env, owner.clone(), amount, salt)?;
+    /// `get_escrow_by_handle` that says why an escrow is missing
+    ///
+    /// # Returns
+    /// * `Result<Escrow, QuickexError>` - `EscrowNotFound`, or `FeatureDisabled` if escrows are off
+    pub fn query_escrow_by_handle(env: Env, handle: BytesN<32>) -> Result<Escrow, QuickexError> {
+        escrow::find_escrow_by_handle(&env, &handle)
+    }
 
-        let entry = EscrowEntry {
-            token: token.clone(),
-            amount,
-            owner: owner.clone(),
-            status: EscrowStatus::Pending,
-            created_at: env.ledger().timestamp(),
-        };
+    /// Get the status of an escrow by its opaque handle
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `handle` - The escrow handle
+    ///
+    /// # Returns
+    /// * `Option<EscrowStatus>` - The status if the handle is known
+    pub fn get_escrow_status_by_handle(env: Env, handle: BytesN<32>) -> Option<EscrowStatus> {
+        let escrow_id = escrow::resolve_handle(&env, &handle).ok()?;
+        escrow::escrow_status(&env, escrow_id).ok()
+    }
 
-        put_escrow(&env, &commitment.clone().into(), &entry);
+    /// `release_escrow` addressed by opaque handle
+    pub fn release_escrow_by_handle(
+        env: Env,
+        caller: Address,
+        handle: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        escrow::release_escrow(&env, caller, escrow_id)
+    }
 
-        let token_client = token::Client::new(&env, &token);
-        token_client.transfer(&owner, env.current_contract_address(), &amount);
+    /// `claim_escrow` addressed by opaque handle
+    pub fn claim_escrow_by_handle(
+        env: Env,
+        claimant: Address,
+        handle: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        escrow::claim_escrow(&env, claimant, escrow_id)
+    }
 
-        Ok(commitment)
+    /// `release_split_amounts` addressed by opaque handle
+    pub fn release_split_amounts_by_handle(
+        env: Env,
+        caller: Address,
+        handle: BytesN<32>,
+        to_recipient: i128,
+        to_sender: i128,
+    ) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        escrow::release_split_amounts(&env, caller, escrow_id, to_recipient, to_sender)
     }
 
-    /// Create a commitment for a hidden amount
+    /// `open_dispute` addressed by opaque handle
+    pub fn open_dispute_by_handle(
+        env: Env,
+        caller: Address,
+        handle: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        dispute::open_dispute(&env, caller, escrow_id)
+    }
+
+    /// `resolve_dispute` addressed by opaque handle
+    pub fn resolve_dispute_by_handle(
+        env: Env,
+        caller: Address,
+        handle: BytesN<32>,
+        release: bool,
+    ) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        dispute::resolve_dispute(&env, caller, escrow_id, release)
+    }
+
+    /// `force_expire` addressed by opaque handle
+    pub fn force_expire_by_handle(env: Env, handle: BytesN<32>) -> Result<(), QuickexError> {
+        let escrow_id = escrow::resolve_handle(&env, &handle)?;
+        escrow::force_expire(&env, escrow_id)
+    }
+
+    /// Preview the id the next escrow will be assigned, without reserving it
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `owner` - The owner of the funds
-    /// * `amount` - The amount to commit
-    /// * `salt` - Random salt for privacy
     ///
     /// # Returns
-    /// * `Result<BytesN<32>, QuickexError>` - The commitment hash
-    pub fn create_amount_commitment(
-        env: Env,
-        owner: Address,
-        amount: i128,
-        salt: Bytes,
-    ) -> Result<BytesN<32>, QuickexError> {
-        commitment::create_amount_commitment(&env, owner, amount, salt)
+    /// * `u64` - The id the next created escrow gets
+    pub fn preview_next_escrow_id(env: Env) -> u64 {
+        next_escrow_id(&env)
     }
 
-    /// Verify a commitment matches the provided values
+    /// Create an escrow only if it will be assigned the expected id
+    ///
+    /// Lets clients show "send funds referencing escrow N" before the escrow
+    /// exists, without risking the wrong association if another escrow was
+    /// created in between.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `commitment` - The commitment hash to verify
-    /// * `owner` - The owner of the funds
-    /// * `amount` - The amount to verify
-    /// * `salt` - The salt used for the commitment
+    /// * `expected_id` - The id previously returned by `preview_next_escrow_id`
+    /// * `from` - Sender address (must authorize)
+    /// * `to` - Recipient address
+    /// * `token` - The token address
+    /// * `amount` - Amount to escrow
+    /// * `memo` - Free-form reference, at most `MAX_MEMO_LEN` bytes
     ///
     /// # Returns
-    /// * `bool` - True if valid
-    pub fn verify_amount_commitment(
+    /// * `Result<u64, QuickexError>` - The escrow id, or `EscrowIdMismatch` if the counter moved
+    pub fn create_escrow_at(
         env: Env,
-        commitment: BytesN<32>,
-        owner: Address,
+        expected_id: u64,
+        from: Address,
+        to: Address,
+        token: Address,
         amount: i128,
-        salt: Bytes,
-    ) -> bool {
-        commitment::verify_amount_commitment(&env, commitment, owner, amount, salt)
-    }
-
-    pub fn create_escrow(env: Env, _from: Address, _to: Address, _amount: u64) -> u64 {
-        increment_escrow_counter(&env)
+        memo: Bytes,
+    ) -> Result<u64, QuickexError> {
+        if next_escrow_id(&env) != expected_id {
+            return Err(QuickexError::EscrowIdMismatch);
+        }
+        escrow::create_escrow(&env, from, to, token, amount, memo)
     }
 
     pub fn health_check() -> bool {
         true
     }
 
+    /// Get the contract name, interface version and supported features
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `Metadata` - `features` is a bitfield of the `FEATURE_*` flags
+    pub fn metadata(env: Env) -> Metadata {
+        metadata::metadata(&env)
+    }
+
     /// Deposit funds and create an escrow entry using a pre-generated commitment
     ///
     /// # Arguments
@@ -208,18 +3677,23 @@ impl QuickexContract {
         amount: i128,
         commitment: BytesN<32>,
     ) -> Result<(), QuickexError> {
+        features::require_enabled(&env, Feature::Commitments)?;
+
         if amount <= 0 {
             return Err(QuickexError::InvalidAmount);
         }
+        admin::require_token_not_paused(&env, &token)?;
 
         from.require_auth();
 
         if has_escrow(&env, &commitment.clone().into()) {
             return Err(QuickexError::CommitmentAlreadyExists);
         }
+        commitment::record_mode(&env, &commitment);
 
         let token_client = token::Client::new(&env, &token);
         token_client.transfer(&from, env.current_contract_address(), &amount);
+        reporting::adjust_owed(&env, DataKey::DepositsHeld(token.clone()), amount);
 
         let entry = EscrowEntry {
             token: token.clone(),
@@ -230,8 +3704,11 @@ impl QuickexContract {
         };
 
         put_escrow(&env, &commitment.clone().into(), &entry);
+        push_owner_deposit(&env, &from, &commitment);
+        reporting::record_token(&env, &token);
 
-        events::publish_deposit(&env, commitment, token, amount);
+        let private = privacy::hides_amounts(&env, &from, &token);
+        events::publish_deposit(&env, commitment, token, amount, private);
 
         Ok(())
     }
@@ -249,9 +3726,57 @@ impl QuickexContract {
             return Err(QuickexError::AlreadyInitialized);
         }
         set_admin(&env, &admin);
+        set_storage_version(&env, migration::STORAGE_VERSION);
+        Ok(())
+    }
+
+    /// Initialize the contract with an admin and a subset of modules enabled
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `admin` - The admin address to set
+    /// * `flags` - Modules to enable; the rest reject calls with `FeatureDisabled`
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if already initialized
+    pub fn initialize_with_features(
+        env: Env,
+        admin: Address,
+        flags: FeatureFlags,
+    ) -> Result<(), QuickexError> {
+        Self::initialize(env.clone(), admin)?;
+        set_feature_flags(&env, &flags);
         Ok(())
     }
 
+    /// Turn on modules left disabled at initialization (Admin only)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `flags` - Modules to enable; false entries are left unchanged
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn enable_features(
+        env: Env,
+        caller: Address,
+        flags: FeatureFlags,
+    ) -> Result<(), QuickexError> {
+        features::enable(&env, caller, flags)
+    }
+
+    /// Get the modules enabled on this deployment
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `FeatureFlags` - Enabled modules
+    pub fn get_feature_flags(env: Env) -> FeatureFlags {
+        get_feature_flags(&env)
+    }
+
     /// Set the paused state of the contract (Admin only)
     ///
     /// # Arguments
@@ -270,6 +3795,74 @@ impl QuickexContract {
         Ok(())
     }
 
+    /// Block or unblock new escrows and funding in one token (Admin only)
+    ///
+    /// Escrows already funded in the token can still be released, disputed
+    /// and refunded.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `token` - The token to pause or unpause
+    /// * `paused` - True to block new escrows in the token
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_token_paused(
+        env: Env,
+        caller: Address,
+        token: Address,
+        paused: bool,
+    ) -> Result<(), QuickexError> {
+        admin::set_token_paused(&env, caller, token, paused)
+    }
+
+    /// Grant or revoke the auditor role (Admin only)
+    ///
+    /// Auditors can read reports that expose private amounts, such as
+    /// `get_largest_escrows`, but can't change anything.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `auditor` - The account to grant or revoke
+    /// * `enabled` - True to grant the role
+    ///
+    /// # Returns
+    /// * `Result<(), QuickexError>` - Ok if successful, Error if unauthorized
+    pub fn set_auditor(
+        env: Env,
+        caller: Address,
+        auditor: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        admin::set_auditor(&env, caller, auditor, enabled)
+    }
+
+    /// Check whether an account holds the auditor role
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `account` - The account to check
+    ///
+    /// # Returns
+    /// * `bool` - True if the account is an auditor
+    pub fn is_auditor(env: Env, account: Address) -> bool {
+        is_auditor(&env, &account)
+    }
+
+    /// Check whether new escrows in a token are blocked
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token address
+    ///
+    /// # Returns
+    /// * `bool` - True if the token is paused
+    pub fn is_token_paused(env: Env, token: Address) -> bool {
+        is_token_paused(&env, &token)
+    }
+
     /// Transfer admin rights to a new address (Admin only)
     ///
     /// # Arguments
@@ -307,41 +3900,46 @@ impl QuickexContract {
     /// # Returns
     /// * `Option<Address>` - The admin address if set, None otherwise
     pub fn get_admin(env: Env) -> Option<Address> {
-        get_admin(&env)
+        admin::admin(&env).ok()
     }
 
-    pub fn get_commitment_state(env: Env, commitment: BytesN<32>) -> Option<EscrowStatus> {
-        let commitment_bytes: Bytes = commitment.into();
-        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
+    /// Get the current admin address, failing with `NotInitialized` before `initialize`
+    ///
+    /// # Returns
+    /// * `Result<Address, QuickexError>` - The admin address
+    pub fn query_admin(env: Env) -> Result<Address, QuickexError> {
+        admin::admin(&env)
+    }
 
-        entry.map(|e| e.status)
+    pub fn get_commitment_state(env: Env, commitment: BytesN<32>) -> Option<EscrowStatus> {
+        commitment::find_entry(&env, commitment)
+            .map(|e| e.status)
+            .ok()
     }
 
     // Verify proof parameters without submitting a transaction
     pub fn verify_proof_view(env: Env, amount: i128, salt: Bytes, owner: Address) -> bool {
-        let commitment_result =
-            commitment::create_amount_commitment(&env, owner.clone(), amount, salt);
-
-        let commitment = match commitment_result {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
-        // Check if commitment exists in storage
-        let commitment_bytes: Bytes = commitment.into();
-        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
-
         // Verify the entry exists, is pending, and amount matches
-        match entry {
-            Some(e) => e.status == EscrowStatus::Pending && e.amount == amount,
-            None => false,
+        match commitment::find_amount_deposit(&env, owner, amount, salt) {
+            Ok((_, e)) => e.status == EscrowStatus::Pending && e.amount == amount,
+            Err(_) => false,
         }
     }
 
     // Get detailed escrow information for a commitment
     pub fn get_escrow_details(env: Env, commitment: BytesN<32>) -> Option<EscrowEntry> {
-        let commitment_bytes: Bytes = commitment.into();
-        get_escrow(&env, &commitment_bytes)
+        commitment::find_entry(&env, commitment).ok()
+    }
+
+    /// `get_escrow_details` that says why an entry is missing
+    ///
+    /// # Returns
+    /// * `Result<EscrowEntry, QuickexError>` - `CommitmentNotFound`, or `FeatureDisabled` if commitments are off
+    pub fn query_escrow_details(
+        env: Env,
+        commitment: BytesN<32>,
+    ) -> Result<EscrowEntry, QuickexError> {
+        commitment::find_entry(&env, commitment)
     }
     /// Upgrade the contract to a new WASM implementation (Admin only)
     ///
@@ -380,7 +3978,39 @@ impl QuickexContract {
 
         Ok(())
     }
+
+    /// Migrate storage from `from_version` to the version this build uses (Admin only)
+    ///
+    /// Run once after `upgrade`. Dispatches each migration step in turn, then
+    /// stores the new version.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The caller address (must be admin)
+    /// * `from_version` - The stored version, as reported by `get_storage_version`
+    ///
+    /// # Returns
+    /// * `Result<u32, QuickexError>` - The version storage now has
+    ///
+    /// # Errors
+    /// * `StorageVersionMismatch` - `from_version` isn't the stored version
+    /// * `UnknownMigration` - No migration starts at `from_version`
+    pub fn migrate(env: Env, caller: Address, from_version: u32) -> Result<u32, QuickexError> {
+        migration::migrate(&env, caller, from_version)
+    }
+
+    /// Get the storage layout version (0 for deployments from before versioning)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    ///
+    /// # Returns
+    /// * `u32` - The stored version
+    pub fn get_storage_version(env: Env) -> u32 {
+        get_storage_version(&env)
+    }
 }
 
+mod escrow_test;
 mod storage_test;
 mod test;
@@ -0,0 +1,63 @@
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// Prefix separating leaf hashes from interior node hashes
+const LEAF_PREFIX: u8 = 0;
+
+/// Prefix for interior node hashes
+const NODE_PREFIX: u8 = 1;
+
+fn hash_leaf(env: &Env, leaf: &BytesN<32>) -> BytesN<32> {
+    let mut payload = Bytes::from_array(env, &[LEAF_PREFIX]);
+    payload.append(&leaf.clone().into());
+    env.crypto().sha256(&payload).into()
+}
+
+/// Hash two nodes in sorted order, so proofs need no left/right flags
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut payload = Bytes::from_array(env, &[NODE_PREFIX]);
+    payload.append(&first.clone().into());
+    payload.append(&second.clone().into());
+    env.crypto().sha256(&payload).into()
+}
+
+/// Merkle root over `leaves`, or None if there are none
+///
+/// Leaves are hashed as `sha256(0x00 || leaf)` and nodes as
+/// `sha256(0x01 || min(a, b) || max(a, b))`. An unpaired node is carried up
+/// to the next level unchanged.
+pub fn root(env: &Env, leaves: &Vec<BytesN<32>>) -> Option<BytesN<32>> {
+    let mut level = Vec::new(env);
+    for leaf in leaves.iter() {
+        level.push_back(hash_leaf(env, &leaf));
+    }
+
+    while level.len() > 1 {
+        let mut next = Vec::new(env);
+        let mut i = 0;
+        while i < level.len() {
+            let left = level.get_unchecked(i);
+            match level.get(i + 1) {
+                Some(right) => next.push_back(hash_pair(env, &left, &right)),
+                None => next.push_back(left),
+            }
+            i += 2;
+        }
+        level = next;
+    }
+
+    level.first()
+}
+
+/// Check that `proof` links `leaf` to `root`
+///
+/// The proof lists the sibling at each level, skipping levels where the
+/// node was carried up without one.
+pub fn verify(env: &Env, root: &BytesN<32>, leaf: &BytesN<32>, proof: &Vec<BytesN<32>>) -> bool {
+    let mut node = hash_leaf(env, leaf);
+    for sibling in proof.iter() {
+        node = hash_pair(env, &node, &sibling);
+    }
+    node == *root
+}
@@ -0,0 +1,43 @@
+use crate::features::{is_enabled, Feature};
+use crate::types::Metadata;
+use soroban_sdk::{symbol_short, Env};
+
+/// Interface version reported by `metadata`
+pub const CONTRACT_VERSION: u32 = 1;
+
+/// Id-based escrows with release, refund and settlement flows
+pub const FEATURE_ESCROW: u32 = 1 << 0;
+
+/// Amount commitments, the commitment registry and salt escrow
+pub const FEATURE_COMMITMENTS: u32 = 1 << 1;
+
+/// Payment streams (not part of this build)
+#[allow(dead_code)]
+pub const FEATURE_STREAMS: u32 = 1 << 2;
+
+/// Arbitrated disputes with bonds and split resolutions
+pub const FEATURE_DISPUTES: u32 = 1 << 3;
+
+/// Opaque salted escrow handles
+pub const FEATURE_HANDLES: u32 = 1 << 4;
+
+/// Features compiled into this build
+pub const SUPPORTED_FEATURES: u32 =
+    FEATURE_ESCROW | FEATURE_COMMITMENTS | FEATURE_DISPUTES | FEATURE_HANDLES;
+
+/// Features compiled in, less any module this deployment has switched off
+pub fn metadata(env: &Env) -> Metadata {
+    let mut features = SUPPORTED_FEATURES;
+    if !is_enabled(env, Feature::Escrow) {
+        features &= !(FEATURE_ESCROW | FEATURE_DISPUTES | FEATURE_HANDLES);
+    }
+    if !is_enabled(env, Feature::Commitments) {
+        features &= !FEATURE_COMMITMENTS;
+    }
+
+    Metadata {
+        name: symbol_short!("quickex"),
+        version: CONTRACT_VERSION,
+        features,
+    }
+}
@@ -0,0 +1,79 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::escrow::RESERVED_ESCROW_IDS;
+use crate::events::publish_storage_migrated;
+use crate::storage::{
+    get_escrow_counter, get_legacy_escrow_count, get_storage_version, set_legacy_escrow_count,
+    set_storage_version,
+};
+use soroban_sdk::{Address, Env};
+
+/// Storage layout version this build reads and writes
+///
+/// * 0 - before versioning; escrow ids were counted up from 1
+/// * 1 - ids below `RESERVED_ESCROW_IDS` are reserved for imports
+pub const STORAGE_VERSION: u32 = 1;
+
+/// Bring storage written by an older build up to `STORAGE_VERSION` (Admin only)
+///
+/// `from_version` must match the stored version, so a migration can't be
+/// replayed or skipped by mistake. Each step runs in turn and the new version
+/// is only stored once all of them succeed.
+pub fn migrate(env: &Env, caller: Address, from_version: u32) -> Result<u32, QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    if from_version != get_storage_version(env) {
+        return Err(QuickexError::StorageVersionMismatch);
+    }
+    if from_version >= STORAGE_VERSION {
+        return Err(QuickexError::UnknownMigration);
+    }
+
+    let mut version = from_version;
+    while version < STORAGE_VERSION {
+        match version {
+            0 => record_legacy_count(env),
+            _ => return Err(QuickexError::UnknownMigration),
+        }
+        version += 1;
+    }
+
+    set_storage_version(env, version);
+    publish_storage_migrated(env, from_version, version, &caller);
+
+    Ok(version)
+}
+
+/// Number of escrows a build from before versioning counted up from 1
+///
+/// They occupy ids `1..=count` with no gaps. Until a new-layout escrow is
+/// created the counter itself still holds the count.
+pub fn legacy_escrow_count(env: &Env) -> u64 {
+    get_legacy_escrow_count(env).unwrap_or_else(|| {
+        let counter = get_escrow_counter(env);
+        if counter < RESERVED_ESCROW_IDS {
+            counter
+        } else {
+            0
+        }
+    })
+}
+
+/// Number of escrows created under the current id layout
+///
+/// Before migration the counter still counts legacy escrows up from 1;
+/// afterwards those sit in the reserved range with the imports.
+pub fn created_escrow_count(env: &Env) -> u64 {
+    let counter = get_escrow_counter(env);
+    if counter < RESERVED_ESCROW_IDS && get_storage_version(env) < STORAGE_VERSION {
+        counter
+    } else {
+        counter.saturating_sub(RESERVED_ESCROW_IDS)
+    }
+}
+
+/// 0 -> 1: escrows counted up from 1 now sit in the reserved range, so
+/// record how many there are to keep them in TVL scans
+fn record_legacy_count(env: &Env) {
+    set_legacy_escrow_count(env, legacy_escrow_count(env));
+}
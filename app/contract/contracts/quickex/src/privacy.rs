@@ -1,25 +1,132 @@
+use crate::admin;
 use crate::errors::QuickexError;
-use crate::events::publish_privacy_toggled;
-use soroban_sdk::{Address, Env, Symbol};
+use crate::events::{publish_data_forgotten, publish_privacy_toggled};
+use crate::features::{self, Feature};
+use soroban_sdk::{Address, Env, Symbol, Vec};
 
 pub fn set_privacy(env: &Env, owner: Address, enabled: bool) -> Result<(), QuickexError> {
+    features::require_enabled(env, Feature::Privacy)?;
+
+    owner.require_auth();
+    store_privacy(env, owner, enabled);
+    Ok(())
+}
+
+/// Enable privacy for `owner` until the ledger timestamp `valid_until`
+///
+/// `get_privacy` reads false from that moment on, with no transaction needed
+/// to switch it off. A later `set_privacy` replaces the window.
+pub fn set_privacy_until(env: &Env, owner: Address, valid_until: u64) -> Result<(), QuickexError> {
+    features::require_enabled(env, Feature::Privacy)?;
+
+    if valid_until <= env.ledger().timestamp() {
+        return Err(QuickexError::InvalidExpiry);
+    }
+
+    owner.require_auth();
+    store_privacy(env, owner.clone(), true);
+
+    let key = Symbol::new(env, "privacy_until");
+    env.storage().persistent().set(&(key, owner), &valid_until);
+    Ok(())
+}
+
+/// Put accounts back to the default of no privacy (Admin only)
+///
+/// Emergency recovery for when the privacy logic misbehaves. Clears both the
+/// privacy toggle and any privacy level, and emits a `PrivacyToggled` event
+/// per account.
+pub fn admin_reset_privacy(
+    env: &Env,
+    caller: Address,
+    accounts: Vec<Address>,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    for account in accounts.iter() {
+        crate::storage::clear_privacy_level(env, &account);
+        store_privacy(env, account, false);
+    }
+
+    Ok(())
+}
+
+/// Delete everything stored about `owner`'s privacy choices
+///
+/// Removes the privacy toggle and its expiry, the privacy level and its
+/// history, the standing escrow privacy setting and the reputation opt-out,
+/// so every getter reads its default again. Escrows, including their viewers
+/// and notes, are shared with counterparties and stay as they are. Works
+/// even while the privacy module is switched off.
+pub fn forget_me(env: &Env, owner: Address) {
     owner.require_auth();
 
+    let storage = env.storage().persistent();
+    storage.remove(&(Symbol::new(env, "privacy_enabled"), owner.clone()));
+    storage.remove(&(Symbol::new(env, "privacy_until"), owner.clone()));
+    crate::storage::clear_privacy_level(env, &owner);
+    crate::storage::clear_privacy_history(env, &owner);
+    crate::storage::clear_private_escrows_by_default(env, &owner);
+    crate::storage::set_stats_opt_out(env, &owner, false);
+
+    publish_data_forgotten(env, owner);
+}
+
+fn store_privacy(env: &Env, owner: Address, enabled: bool) {
     let key = Symbol::new(env, "privacy_enabled");
     env.storage()
         .persistent()
         .set(&(key, owner.clone()), &enabled);
 
+    let until_key = Symbol::new(env, "privacy_until");
+    env.storage()
+        .persistent()
+        .remove(&(until_key, owner.clone()));
+
     let timestamp = env.ledger().timestamp();
     publish_privacy_toggled(env, owner, enabled, timestamp);
-
-    Ok(())
 }
 
 pub fn get_privacy(env: &Env, owner: Address) -> bool {
     let key = Symbol::new(env, "privacy_enabled");
-    env.storage()
+    let enabled = env
+        .storage()
         .persistent()
-        .get(&(key, owner))
-        .unwrap_or(false)
+        .get(&(key, owner.clone()))
+        .unwrap_or(false);
+
+    let until_key = Symbol::new(env, "privacy_until");
+    match env
+        .storage()
+        .persistent()
+        .get::<_, u64>(&(until_key, owner))
+    {
+        Some(valid_until) => enabled && env.ledger().timestamp() < valid_until,
+        None => enabled,
+    }
+}
+
+/// Make every escrow `owner` creates from now on private, or stop doing so
+///
+/// Escrows capture their privacy at creation, so turning this off later
+/// doesn't expose escrows created while it was on.
+pub fn set_private_escrows_by_default(
+    env: &Env,
+    owner: Address,
+    enabled: bool,
+) -> Result<(), QuickexError> {
+    features::require_enabled(env, Feature::Privacy)?;
+
+    owner.require_auth();
+    crate::storage::set_private_escrows_by_default(env, &owner, enabled);
+    Ok(())
+}
+
+/// Whether events for `owner`'s escrows in `token` should hide the amount,
+/// through account-level privacy, the account's standing escrow privacy or
+/// the token's privacy default
+pub fn hides_amounts(env: &Env, owner: &Address, token: &Address) -> bool {
+    get_privacy(env, owner.clone())
+        || crate::storage::get_private_escrows_by_default(env, owner)
+        || crate::storage::get_token_privacy_default(env, token)
 }
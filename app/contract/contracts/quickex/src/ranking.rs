@@ -0,0 +1,64 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::storage::{get_largest_escrows, set_largest_escrows};
+use soroban_sdk::{Address, Env, Vec};
+
+/// Number of open escrows reported per token
+pub const MAX_RANKED: u32 = 10;
+
+/// Number of open escrows tracked per token, so settling a ranked escrow
+/// can refill the report from the runners-up
+pub const MAX_TRACKED: u32 = 5 * MAX_RANKED;
+
+/// Whether `a` ranks above `b`: larger amounts first, then older escrows
+fn outranks(a: (u64, i128), b: (u64, i128)) -> bool {
+    a.1 > b.1 || (a.1 == b.1 && a.0 < b.0)
+}
+
+/// Rank a newly funded escrow among its token's largest
+///
+/// When the tracked list is full the lowest entry is evicted, or the new
+/// escrow is dropped if it ranks below all of them.
+pub fn record(env: &Env, token: &Address, escrow_id: u64, amount: i128) {
+    let mut ranked = get_largest_escrows(env, token);
+    let entry = (escrow_id, amount);
+
+    let position = ranked
+        .iter()
+        .position(|other| outranks(entry, other))
+        .unwrap_or(ranked.len() as usize) as u32;
+    if position >= MAX_TRACKED {
+        return;
+    }
+
+    ranked.insert(position, entry);
+    if ranked.len() > MAX_TRACKED {
+        ranked.pop_back();
+    }
+    set_largest_escrows(env, token, &ranked);
+}
+
+/// Drop a settled escrow from its token's ranking
+pub fn remove(env: &Env, token: &Address, escrow_id: u64) {
+    let mut ranked = get_largest_escrows(env, token);
+    if let Some(position) = ranked.iter().position(|(id, _)| id == escrow_id) {
+        ranked.remove(position as u32);
+        set_largest_escrows(env, token, &ranked);
+    }
+}
+
+/// The largest open escrows of a token as (escrow id, amount), largest first (Admin or auditor)
+///
+/// Restricted because it exposes the amounts of private escrows. The report
+/// is exact while fewer than `MAX_TRACKED - MAX_RANKED` tracked escrows have
+/// settled without new ones being funded; past that an escrow evicted from
+/// the tracked list can be missing.
+pub fn largest(
+    env: &Env,
+    caller: Address,
+    token: Address,
+) -> Result<Vec<(u64, i128)>, QuickexError> {
+    admin::require_admin_or_auditor(env, &caller)?;
+    let tracked = get_largest_escrows(env, &token);
+    Ok(tracked.slice(0..tracked.len().min(MAX_RANKED)))
+}
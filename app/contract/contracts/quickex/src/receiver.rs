@@ -0,0 +1,53 @@
+use crate::storage::{get_escrow_notify, is_receiver};
+use crate::types::EscrowStatus;
+use soroban_sdk::{Address, BytesN, Env, IntoVal, InvokeError, Symbol, Val, Vec};
+
+/// Notify a registered receiver contract that funds were released to it.
+///
+/// Only contracts registered by the admin are called, so arbitrary recipients
+/// can't burn the budget or re-enter. The call is a try-invoke: a trapping
+/// receiver is reported back as `false` instead of reverting the release.
+///
+/// # Returns
+/// * `bool` - False if the receiver is registered and the callback failed
+pub fn notify_released(
+    env: &Env,
+    receiver: &Address,
+    commitment: &BytesN<32>,
+    token: &Address,
+    amount: i128,
+) -> bool {
+    if !is_receiver(env, receiver) {
+        return true;
+    }
+
+    let args: Vec<Val> = (commitment.clone(), token.clone(), amount).into_val(env);
+    let result = env.try_invoke_contract::<Val, InvokeError>(
+        receiver,
+        &Symbol::new(env, "on_escrow_released"),
+        args,
+    );
+
+    matches!(result, Ok(Ok(_)))
+}
+
+/// Tell an escrow's notify contract, if it has one, that the escrow settled
+///
+/// Calls `on_escrow_event(escrow_id, status)` as a try-invoke, so a failing
+/// callback never reverts the settlement. Only registered receivers can be
+/// set as notify contracts, and one cleared since is no longer called.
+pub fn notify_escrow_event(env: &Env, escrow_id: u64, status: EscrowStatus) {
+    let Some(notify) = get_escrow_notify(env, escrow_id) else {
+        return;
+    };
+    if !is_receiver(env, &notify) {
+        return;
+    }
+
+    let args: Vec<Val> = (escrow_id, status).into_val(env);
+    let _ = env.try_invoke_contract::<Val, InvokeError>(
+        &notify,
+        &Symbol::new(env, "on_escrow_event"),
+        args,
+    );
+}
@@ -0,0 +1,228 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::escrow::{held, RESERVED_ESCROW_IDS};
+use crate::events::publish_reserve_shortfall;
+use crate::migration;
+use crate::storage::{
+    get_collected_fees, get_escrow_by_id, get_escrow_counter, get_escrowed_tokens,
+    get_imported_count, get_imported_id, get_insurance_pool, get_owed_total, get_settlement_bucket,
+    get_tvl, get_tvl_scan, get_yield_adapter, get_yield_principal, remove_settlement_bucket,
+    set_escrowed_tokens, set_owed_total, set_settlement_bucket, set_tvl, set_tvl_scan, DataKey,
+};
+use crate::types::{AggregateReport, EscrowStatus, ReserveProof, SettlementBucket};
+use soroban_sdk::{token, Address, Env};
+
+/// Ledgers per reporting epoch (about one day at 5s per ledger)
+pub const LEDGERS_PER_EPOCH: u32 = 17_280;
+
+/// Maximum number of epochs a single report or prune may span
+pub const MAX_REPORT_EPOCHS: u32 = 366;
+
+/// Most escrow ids one `recompute_tvl` call scans
+pub const MAX_TVL_SCAN: u32 = 200;
+
+/// Reporting epoch a ledger sequence falls into
+pub fn epoch_of(ledger: u32) -> u32 {
+    ledger / LEDGERS_PER_EPOCH
+}
+
+/// Count a settled escrow in the current epoch's bucket for its token
+pub fn record_settlement(env: &Env, token: &Address, amount: i128) {
+    let epoch = epoch_of(env.ledger().sequence());
+    let mut bucket = get_settlement_bucket(env, token, epoch);
+    bucket.settled_count += 1;
+    bucket.volume += amount;
+    set_settlement_bucket(env, token, epoch, &bucket);
+}
+
+/// Add a token to the escrowed-token list the first time it is escrowed
+///
+/// Lets operators enumerate every asset the contract may hold when checking
+/// balances against TVL.
+pub fn record_token(env: &Env, token: &Address) {
+    let mut tokens = get_escrowed_tokens(env);
+    if !tokens.contains(token) {
+        tokens.push_back(token.clone());
+        set_escrowed_tokens(env, &tokens);
+    }
+}
+
+/// Whether an escrow in this state still holds its funds
+pub fn is_open(status: EscrowStatus) -> bool {
+    matches!(
+        status,
+        EscrowStatus::Funding | EscrowStatus::Pending | EscrowStatus::Disputed
+    )
+}
+
+/// Move a token's TVL by `delta` as escrows are funded or settled
+pub fn adjust_tvl(env: &Env, token: &Address, delta: i128) {
+    if delta != 0 {
+        set_tvl(env, token, get_tvl(env, token) + delta);
+    }
+}
+
+/// Rebuild a token's TVL from the escrows themselves (Admin only)
+///
+/// Sums what open escrows in `token` hold over positions `start..start +
+/// limit` (at most `MAX_TVL_SCAN`). Positions run over the escrows counted
+/// up from 1 before versioning, then the imported ones in import order,
+/// then the created ones, so sparse imported ids cost nothing to skip and
+/// the scan works before and after `migrate`. Pages must follow on from
+/// each other, starting at 1; the page that reaches the last escrow
+/// overwrites the stored TVL. Returns the sum so far, which is the new TVL
+/// once the scan completes.
+pub fn recompute_tvl(
+    env: &Env,
+    caller: Address,
+    token: Address,
+    start: u64,
+    limit: u32,
+) -> Result<i128, QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let (next, mut sum) = if start == 1 {
+        (1, 0)
+    } else {
+        get_tvl_scan(env, &token)
+    };
+    if start != next {
+        return Err(QuickexError::TvlScanOutOfOrder);
+    }
+
+    let legacy = migration::legacy_escrow_count(env);
+    let imported = legacy + get_imported_count(env);
+    let last = imported + get_escrow_counter(env).saturating_sub(RESERVED_ESCROW_IDS);
+    let end = start
+        .saturating_add(limit.min(MAX_TVL_SCAN) as u64)
+        .min(last + 1);
+
+    for position in start..end {
+        let escrow_id = if position <= legacy {
+            Some(position)
+        } else if position <= imported {
+            get_imported_id(env, position - legacy)
+        } else {
+            Some(RESERVED_ESCROW_IDS + position - imported)
+        };
+        if let Some(escrow) = escrow_id.and_then(|id| get_escrow_by_id(env, id)) {
+            if escrow.token == token && is_open(escrow.status) {
+                sum += held(&escrow);
+            }
+        }
+    }
+
+    if end > last {
+        set_tvl(env, &token, sum);
+        set_tvl_scan(env, &token, None);
+    } else {
+        set_tvl_scan(env, &token, Some((end, sum)));
+    }
+
+    Ok(sum)
+}
+
+fn epoch_window(from_ledger: u32, to_ledger: u32) -> Result<(u32, u32), QuickexError> {
+    let (from_epoch, to_epoch) = (epoch_of(from_ledger), epoch_of(to_ledger));
+    if from_ledger > to_ledger || to_epoch - from_epoch >= MAX_REPORT_EPOCHS {
+        return Err(QuickexError::InvalidReportWindow);
+    }
+    Ok((from_epoch, to_epoch))
+}
+
+/// Compare what the contract holds of a token with what it owes, without
+/// listing escrows
+///
+/// Everything is read live in one call. A shortfall publishes
+/// `ReserveShortfall` so monitoring can alarm on it.
+pub fn reserve_proof(env: &Env, token: Address) -> ReserveProof {
+    let tvl = get_tvl(env, &token);
+    let fees_accrued = get_collected_fees(env, &token) + get_insurance_pool(env, &token);
+    let credits = get_owed_total(env, &DataKey::CreditsOwed(token.clone()));
+    let bonds = get_owed_total(env, &DataKey::BondsHeld(token.clone()));
+    let deposits = get_owed_total(env, &DataKey::DepositsHeld(token.clone()));
+    let balance = token::Client::new(env, &token).balance(&env.current_contract_address());
+    let parked = get_yield_adapter(env, &token)
+        .map(|adapter| get_yield_principal(env, &adapter))
+        .unwrap_or(0);
+
+    let owed = tvl + fees_accrued + credits + bonds + deposits;
+    let proof = ReserveProof {
+        tvl,
+        fees_accrued,
+        credits,
+        bonds,
+        deposits,
+        balance,
+        parked,
+        solvent: balance + parked >= owed,
+        ledger: env.ledger().sequence(),
+        token,
+    };
+    if !proof.solvent {
+        publish_reserve_shortfall(env, &proof);
+    }
+
+    proof
+}
+
+/// Move a per-token total owed outside open escrows (`BondsHeld` or
+/// `DepositsHeld`) by `delta`
+pub fn adjust_owed(env: &Env, key: DataKey, delta: i128) {
+    if delta != 0 {
+        set_owed_total(env, &key, get_owed_total(env, &key) + delta);
+    }
+}
+
+/// Sum the settlement buckets of a token over the epochs covering a ledger window
+///
+/// Only counts and volume are kept, never per-escrow detail. The window is
+/// widened to whole epochs.
+pub fn aggregate_report(
+    env: &Env,
+    token: Address,
+    from_ledger: u32,
+    to_ledger: u32,
+) -> Result<AggregateReport, QuickexError> {
+    let (from_epoch, to_epoch) = epoch_window(from_ledger, to_ledger)?;
+
+    let mut report = AggregateReport {
+        from_epoch,
+        to_epoch,
+        settled_count: 0,
+        total_volume: 0,
+    };
+    for epoch in from_epoch..=to_epoch {
+        let bucket = get_settlement_bucket(env, &token, epoch);
+        report.settled_count += bucket.settled_count;
+        report.total_volume += bucket.volume;
+    }
+
+    Ok(report)
+}
+
+/// Drop the settlement buckets of a token for an epoch range (Admin only)
+pub fn prune_buckets(
+    env: &Env,
+    caller: Address,
+    token: Address,
+    from_epoch: u32,
+    to_epoch: u32,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    if from_epoch > to_epoch || to_epoch - from_epoch >= MAX_REPORT_EPOCHS {
+        return Err(QuickexError::InvalidReportWindow);
+    }
+
+    for epoch in from_epoch..=to_epoch {
+        remove_settlement_bucket(env, &token, epoch);
+    }
+
+    Ok(())
+}
+
+/// Bucket totals of a token for one epoch
+pub fn bucket(env: &Env, token: Address, epoch: u32) -> SettlementBucket {
+    get_settlement_bucket(env, &token, epoch)
+}
@@ -0,0 +1,62 @@
+use crate::errors::QuickexError;
+use crate::privacy::get_privacy;
+use crate::storage::{
+    get_counterparty_stats, is_stats_opt_out, set_counterparty_stats, set_stats_opt_out,
+};
+use crate::types::{CounterpartyStats, Escrow};
+use soroban_sdk::{Address, Env};
+
+fn update(env: &Env, account: &Address, apply: impl FnOnce(&mut CounterpartyStats)) {
+    let mut stats = get_counterparty_stats(env, account);
+    apply(&mut stats);
+    set_counterparty_stats(env, account, &stats);
+}
+
+/// Count a release that needed no dispute for both parties
+pub fn record_completion(env: &Env, escrow: &Escrow) {
+    update(env, &escrow.from, |stats| stats.completed_as_sender += 1);
+    update(env, &escrow.to, |stats| stats.completed_as_recipient += 1);
+}
+
+/// Count a dispute against the party `opener` is disputing with
+pub fn record_dispute_opened(env: &Env, escrow: &Escrow, opener: &Address) {
+    let against = if *opener == escrow.from {
+        &escrow.to
+    } else {
+        &escrow.from
+    };
+    update(env, against, |stats| stats.disputes_against += 1);
+}
+
+/// Count a dispute ruled wholly for one side: a release goes against the
+/// sender, a refund against the recipient
+pub fn record_dispute_loss(env: &Env, escrow: &Escrow, released: bool) {
+    let loser = if released { &escrow.from } else { &escrow.to };
+    update(env, loser, |stats| stats.disputes_lost += 1);
+}
+
+/// Hide or show an account's record
+///
+/// Only accounts with privacy enabled may opt out, and the opt-out only
+/// holds while their privacy does. Counters keep running while hidden.
+pub fn set_opt_out(env: &Env, account: Address, opted_out: bool) -> Result<(), QuickexError> {
+    account.require_auth();
+
+    if opted_out && !get_privacy(env, account.clone()) {
+        return Err(QuickexError::PrivacyNotEnabled);
+    }
+
+    set_stats_opt_out(env, &account, opted_out);
+    Ok(())
+}
+
+/// Read an account's record, or the opted-out marker
+pub fn stats(env: &Env, account: Address) -> CounterpartyStats {
+    if is_stats_opt_out(env, &account) && get_privacy(env, account.clone()) {
+        return CounterpartyStats {
+            opted_out: true,
+            ..Default::default()
+        };
+    }
+    get_counterparty_stats(env, &account)
+}
@@ -0,0 +1,133 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::events::{
+    publish_salt_blob_released, publish_salt_recovery_cancelled, publish_salt_recovery_requested,
+};
+use crate::features::{self, Feature};
+use crate::storage::{
+    get_salt_escrow, get_salt_recovery_delay, put_salt_escrow, set_salt_recovery_delay,
+};
+use crate::types::SaltEscrow;
+use soroban_sdk::{Address, Bytes, BytesN, Env};
+
+/// Maximum size of an escrowed salt blob in bytes
+pub const MAX_SALT_BLOB_LEN: u32 = 256;
+
+/// Default wait between a recovery request and the blob's release (7 days)
+pub const DEFAULT_SALT_RECOVERY_DELAY: u64 = 7 * 24 * 60 * 60;
+
+/// Set the recovery delay (Admin only)
+pub fn set_delay(env: &Env, caller: Address, seconds: u64) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    set_salt_recovery_delay(env, seconds);
+    Ok(())
+}
+
+/// Deposit an encrypted salt blob for a commitment with a recovery party
+///
+/// The blob is opaque to the contract; only its size is checked. Storing a
+/// new blob replaces the previous one and any pending recovery request.
+pub fn store_salt_blob(
+    env: &Env,
+    owner: Address,
+    commitment: BytesN<32>,
+    blob: Bytes,
+    recovery: Address,
+) -> Result<(), QuickexError> {
+    features::require_enabled(env, Feature::Commitments)?;
+
+    owner.require_auth();
+
+    if blob.len() > MAX_SALT_BLOB_LEN {
+        return Err(QuickexError::SaltBlobTooLarge);
+    }
+
+    if let Some(existing) = get_salt_escrow(env, &commitment) {
+        if existing.owner != owner {
+            return Err(QuickexError::Unauthorized);
+        }
+    }
+
+    let escrow = SaltEscrow {
+        owner,
+        recovery,
+        blob,
+        requested_at: None,
+    };
+    put_salt_escrow(env, &commitment, &escrow);
+
+    Ok(())
+}
+
+/// Start the recovery delay on behalf of the recovery party
+pub fn request_recovery(
+    env: &Env,
+    commitment: BytesN<32>,
+    recovery: Address,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_salt_escrow(env, &commitment).ok_or(QuickexError::SaltEscrowNotFound)?;
+
+    if recovery != escrow.recovery {
+        return Err(QuickexError::Unauthorized);
+    }
+    recovery.require_auth();
+
+    let now = env.ledger().timestamp();
+    escrow.requested_at = Some(now);
+    put_salt_escrow(env, &commitment, &escrow);
+
+    publish_salt_recovery_requested(
+        env,
+        commitment,
+        recovery,
+        now + get_salt_recovery_delay(env),
+    );
+
+    Ok(())
+}
+
+/// Cancel a pending recovery request as the owner
+pub fn cancel_recovery(
+    env: &Env,
+    owner: Address,
+    commitment: BytesN<32>,
+) -> Result<(), QuickexError> {
+    let mut escrow = get_salt_escrow(env, &commitment).ok_or(QuickexError::SaltEscrowNotFound)?;
+
+    if owner != escrow.owner {
+        return Err(QuickexError::Unauthorized);
+    }
+    owner.require_auth();
+
+    escrow.requested_at = None;
+    put_salt_escrow(env, &commitment, &escrow);
+
+    publish_salt_recovery_cancelled(env, commitment);
+
+    Ok(())
+}
+
+/// Hand the blob to the recovery party once the delay since its request has passed
+pub fn release_salt_blob(
+    env: &Env,
+    commitment: BytesN<32>,
+    recovery: Address,
+) -> Result<Bytes, QuickexError> {
+    let escrow = get_salt_escrow(env, &commitment).ok_or(QuickexError::SaltEscrowNotFound)?;
+
+    if recovery != escrow.recovery {
+        return Err(QuickexError::Unauthorized);
+    }
+    recovery.require_auth();
+
+    let requested_at = escrow
+        .requested_at
+        .ok_or(QuickexError::RecoveryDelayActive)?;
+    if env.ledger().timestamp() < requested_at.saturating_add(get_salt_recovery_delay(env)) {
+        return Err(QuickexError::RecoveryDelayActive);
+    }
+
+    publish_salt_blob_released(env, commitment, recovery, escrow.blob.clone());
+
+    Ok(escrow.blob)
+}
@@ -1,6 +1,10 @@
-use soroban_sdk::{contracttype, Address, Bytes, Env, Vec};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Map, Symbol, Vec};
 
-use crate::types::EscrowEntry;
+use crate::types::{
+    ActivityEntry, AnchoredRoot, Auction, CommitmentRecord, CounterpartyStats, Deal, Denomination,
+    DisputeMessage, Escrow, EscrowEntry, EscrowTemplate, EventSummary, FeatureFlags, FeeTiming,
+    SaltEscrow, SettlementBucket, SwapQuote,
+};
 
 /// Storage keys for the contract
 #[contracttype]
@@ -10,8 +14,98 @@ pub enum DataKey {
     EscrowCounter,
     Admin,
     Paused,
+    TokenPaused(Address),
+    Auditor(Address),
+    TokenMinAmount(Address),
     PrivacyLevel(Address),
     PrivacyHistory(Address),
+    Receiver(Address),
+    CallbackFailed(BytesN<32>),
+    EscrowNotify(u64),
+    Funders(u64),
+    FrozenBy(u64),
+    ImportedCount,
+    ImportedId(u64),
+    LegacyEscrowCount,
+    StorageVersion,
+    CommitmentCounter,
+    RegisteredCommitment(u64),
+    CommitmentId(BytesN<32>),
+    AnchoredRoot(BytesN<32>),
+    AnchoredLeaf(BytesN<32>),
+    RootHistory(u32),
+    RootHistoryCount,
+    DisputeMessages(u64),
+    Evidence(u64),
+    Denomination(u64),
+    EscrowFlags(u64),
+    LargestEscrows(Address),
+    EscrowedTokens,
+    Tvl(Address),
+    TvlScan(Address),
+    ActivityPage(Address, u32),
+    ActivityCount(Address),
+    TestMode,
+    CommitmentSalted(BytesN<32>),
+    EscrowById(u64),
+    PairEscrows(BytesN<32>),
+    OpenEscrows(Address),
+    TokenPrivacyDefault(Address),
+    PrivateEscrowsByDefault(Address),
+    Templates(Address),
+    NextTemplateId(Address),
+    EventDetail(Address),
+    InsuranceShareBps,
+    InsurancePool(Address),
+    CollectedFees(Address),
+    NullifierRetention,
+    MaxProofLen,
+    SpentCommitmentCount,
+    DisputeBondBps,
+    Watchers(u64),
+    Viewers(u64),
+    ClaimableEscrows(Address),
+    IdempotencyKey(Address, BytesN<32>),
+    CounterpartyStats(Address),
+    StatsOptOut(Address),
+    OwnerDeposits(Address, u32),
+    OwnerDepositCount(Address),
+    PrivateNote(u64, Address),
+    MaxEscrowLifetime,
+    HandleSalt,
+    HandleSaltVersion,
+    EscrowHandle(BytesN<32>),
+    SettlementBucket(Address, u32),
+    CoolingOff,
+    SaltEscrow(BytesN<32>),
+    SaltRecoveryDelay,
+    FeatureFlags,
+    SwapQuote(u64),
+    AuctionCounter,
+    Auction(u64),
+    AuctionBid(u64, Address),
+    DealCounter,
+    Deal(u64),
+    EscrowDeal(u64),
+    Withdrawable(Address, Address),
+    CreditsOwed(Address),
+    BondsHeld(Address),
+    DepositsHeld(Address),
+    EventLogBounds,
+    EventLog(u64),
+    YieldAdapter(Address),
+    YieldPrincipal(Address),
+    YieldShortfall(Address),
+    FeeTiming,
+    DefaultFeeBps,
+    FeeExempt(Address),
+    WithdrawWhitelist(Address),
+    WithdrawDestination(Address, Address),
+    DailyOutflowCap,
+    DailyOutflow,
+    KeeperIncentive,
+    DeadlineWarningWindow,
+    RequireAttestations,
 }
 
 /// Put an escrow entry into storage
@@ -26,6 +120,12 @@ pub fn get_escrow(env: &Env, commitment: &Bytes) -> Option<EscrowEntry> {
     env.storage().persistent().get(&key)
 }
 
+/// Remove an escrow entry from storage
+pub fn remove_escrow(env: &Env, commitment: &Bytes) {
+    let key = DataKey::Escrow(commitment.clone());
+    env.storage().persistent().remove(&key);
+}
+
 /// Check if an escrow entry exists in storage
 #[allow(dead_code)]
 pub fn has_escrow(env: &Env, commitment: &Bytes) -> bool {
@@ -33,20 +133,1042 @@ pub fn has_escrow(env: &Env, commitment: &Bytes) -> bool {
     env.storage().persistent().has(&key)
 }
 
-/// Get the next escrow counter value
-#[allow(dead_code)]
-pub fn get_escrow_counter(env: &Env) -> u64 {
-    let key = DataKey::EscrowCounter;
-    env.storage().persistent().get(&key).unwrap_or(0)
+/// Get how many commitments have been spent
+pub fn get_spent_commitment_count(env: &Env) -> u64 {
+    let key = DataKey::SpentCommitmentCount;
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Count one more spent commitment
+pub fn increment_spent_commitment_count(env: &Env) {
+    let key = DataKey::SpentCommitmentCount;
+    let count = get_spent_commitment_count(env);
+    env.storage().persistent().set(&key, &(count + 1));
+}
+
+/// Get the escrow counter, i.e. the last id assigned (0 if none yet)
+pub fn get_escrow_counter(env: &Env) -> u64 {
+    find_escrow_counter(env).unwrap_or(0)
+}
+
+/// Get the last id assigned by the escrow counter, or None if it never assigned one
+pub fn find_escrow_counter(env: &Env) -> Option<u64> {
+    env.storage().persistent().get(&DataKey::EscrowCounter)
+}
+
+/// Id the next created escrow will get
+///
+/// Created ids start above the range reserved for imports, so the counter
+/// never reaches an imported id.
+pub fn next_escrow_id(env: &Env) -> u64 {
+    get_escrow_counter(env).max(crate::escrow::RESERVED_ESCROW_IDS) + 1
+}
+
+/// Increment and return the escrow counter
+///
+/// The first id assigned above the reserved range records how many escrows
+/// a build from before versioning had counted up from 1.
+pub fn increment_escrow_counter(env: &Env) -> u64 {
+    let legacy = get_escrow_counter(env);
+    if legacy > 0 && legacy < crate::escrow::RESERVED_ESCROW_IDS {
+        set_legacy_escrow_count(env, legacy);
+    }
+    let count = next_escrow_id(env);
+    env.storage()
+        .persistent()
+        .set(&DataKey::EscrowCounter, &count);
+    count
+}
+
+/// Get the number of escrows written by `import_escrow`
+pub fn get_imported_count(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ImportedCount)
+        .unwrap_or(0)
+}
+
+/// Get the id of the `position`th imported escrow, counting from 1
+pub fn get_imported_id(env: &Env, position: u64) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ImportedId(position))
+}
+
+/// Append an escrow id to the index of imported escrows
+pub fn push_imported_id(env: &Env, escrow_id: u64) {
+    let position = get_imported_count(env) + 1;
+    env.storage()
+        .persistent()
+        .set(&DataKey::ImportedId(position), &escrow_id);
+    env.storage()
+        .persistent()
+        .set(&DataKey::ImportedCount, &position);
+}
+
+/// Get the number of escrows counted up from 1 by a build from before
+/// versioning, if it has been recorded
+pub fn get_legacy_escrow_count(env: &Env) -> Option<u64> {
+    env.storage().persistent().get(&DataKey::LegacyEscrowCount)
+}
+
+/// Record the number of escrows counted up from 1 before versioning
+pub fn set_legacy_escrow_count(env: &Env, count: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::LegacyEscrowCount, &count);
+}
+
+/// Get the storage layout version (0 for deployments from before versioning)
+pub fn get_storage_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StorageVersion)
+        .unwrap_or(0)
+}
+
+/// Set the storage layout version
+pub fn set_storage_version(env: &Env, version: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::StorageVersion, &version);
+}
+
+/// Put an id-based escrow into storage
+pub fn put_escrow_by_id(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let key = DataKey::EscrowById(escrow_id);
+    env.storage().persistent().set(&key, escrow);
+}
+
+/// Get an id-based escrow from storage
+pub fn get_escrow_by_id(env: &Env, escrow_id: u64) -> Option<Escrow> {
+    let key = DataKey::EscrowById(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+/// Get the watchers registered on an escrow
+pub fn get_watchers(env: &Env, escrow_id: u64) -> Vec<Address> {
+    let key = DataKey::Watchers(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the watchers registered on an escrow
+pub fn set_watchers(env: &Env, escrow_id: u64, watchers: &Vec<Address>) {
+    let key = DataKey::Watchers(escrow_id);
+    env.storage().persistent().set(&key, watchers);
+}
+
+/// Get what each shared funder has contributed to an escrow
+pub fn get_funders(env: &Env, escrow_id: u64) -> Map<Address, i128> {
+    let key = DataKey::Funders(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Map::new(env))
+}
+
+/// Set what each shared funder has contributed to an escrow
+pub fn set_funders(env: &Env, escrow_id: u64, funders: &Map<Address, i128>) {
+    let key = DataKey::Funders(escrow_id);
+    env.storage().persistent().set(&key, funders);
+}
+
+/// Get the party who froze an escrow
+pub fn get_frozen_by(env: &Env, escrow_id: u64) -> Option<Address> {
+    let key = DataKey::FrozenBy(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+/// Record the party who froze an escrow, or clear it on unfreeze
+pub fn set_frozen_by(env: &Env, escrow_id: u64, party: Option<&Address>) {
+    let key = DataKey::FrozenBy(escrow_id);
+    match party {
+        Some(party) => env.storage().persistent().set(&key, party),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Get the addresses allowed to see an escrow's full terms
+pub fn get_viewers(env: &Env, escrow_id: u64) -> Vec<Address> {
+    let key = DataKey::Viewers(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the addresses allowed to see an escrow's full terms
+pub fn set_viewers(env: &Env, escrow_id: u64, viewers: &Vec<Address>) {
+    let key = DataKey::Viewers(escrow_id);
+    env.storage().persistent().set(&key, viewers);
+}
+
+/// Get the hash of an author's private note on an escrow
+pub fn get_private_note(env: &Env, escrow_id: u64, author: &Address) -> Option<BytesN<32>> {
+    let key = DataKey::PrivateNote(escrow_id, author.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Set the hash of an author's private note on an escrow
+pub fn set_private_note(env: &Env, escrow_id: u64, author: &Address, note_hash: &BytesN<32>) {
+    let key = DataKey::PrivateNote(escrow_id, author.clone());
+    env.storage().persistent().set(&key, note_hash);
+}
+
+/// Get the messages posted in an escrow's dispute, oldest first
+pub fn get_dispute_messages(env: &Env, escrow_id: u64) -> Vec<DisputeMessage> {
+    let key = DataKey::DisputeMessages(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the messages posted in an escrow's dispute
+pub fn set_dispute_messages(env: &Env, escrow_id: u64, messages: &Vec<DisputeMessage>) {
+    let key = DataKey::DisputeMessages(escrow_id);
+    env.storage().persistent().set(&key, messages);
+}
+
+/// Get the evidence hashes anchored on an escrow, in submission order
+pub fn get_evidence(env: &Env, escrow_id: u64) -> Vec<BytesN<32>> {
+    let key = DataKey::Evidence(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the evidence hashes anchored on an escrow
+pub fn set_evidence(env: &Env, escrow_id: u64, evidence: &Vec<BytesN<32>>) {
+    let key = DataKey::Evidence(escrow_id);
+    env.storage().persistent().set(&key, evidence);
+}
+
+/// Get the compliance flags set on an escrow
+pub fn get_escrow_flags(env: &Env, escrow_id: u64) -> Vec<Symbol> {
+    let key = DataKey::EscrowFlags(escrow_id);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the compliance flags on an escrow
+pub fn set_escrow_flags(env: &Env, escrow_id: u64, flags: &Vec<Symbol>) {
+    let key = DataKey::EscrowFlags(escrow_id);
+    if flags.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, flags);
+    }
+}
+
+/// Get the reference-unit amount an escrow was created from, if any
+pub fn get_denomination(env: &Env, escrow_id: u64) -> Option<Denomination> {
+    let key = DataKey::Denomination(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+/// Set the reference-unit amount an escrow was created from
+pub fn set_denomination(env: &Env, escrow_id: u64, denomination: &Denomination) {
+    let key = DataKey::Denomination(escrow_id);
+    env.storage().persistent().set(&key, denomination);
+}
+
+/// Get every distinct token that has ever been escrowed, in first-use order
+pub fn get_escrowed_tokens(env: &Env) -> Vec<Address> {
+    let key = DataKey::EscrowedTokens;
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the list of tokens that have been escrowed
+pub fn set_escrowed_tokens(env: &Env, tokens: &Vec<Address>) {
+    let key = DataKey::EscrowedTokens;
+    env.storage().persistent().set(&key, tokens);
+}
+
+/// Get the amount held by open escrows in a token
+pub fn get_tvl(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::Tvl(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set the amount held by open escrows in a token
+pub fn set_tvl(env: &Env, token: &Address, tvl: i128) {
+    let key = DataKey::Tvl(token.clone());
+    env.storage().persistent().set(&key, &tvl);
+}
+
+/// Get the progress of a TVL recompute as (next escrow id, running sum)
+pub fn get_tvl_scan(env: &Env, token: &Address) -> (u64, i128) {
+    let key = DataKey::TvlScan(token.clone());
+    env.storage().persistent().get(&key).unwrap_or((1, 0))
+}
+
+/// Set the progress of a TVL recompute, or clear it once finished
+pub fn set_tvl_scan(env: &Env, token: &Address, scan: Option<(u64, i128)>) {
+    let key = DataKey::TvlScan(token.clone());
+    match scan {
+        Some(scan) => env.storage().persistent().set(&key, &scan),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Get one fixed-size page of an account's activity feed
+pub fn get_activity_page(env: &Env, owner: &Address, page: u32) -> Vec<ActivityEntry> {
+    let key = DataKey::ActivityPage(owner.clone(), page);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set one page of an account's activity feed
+pub fn set_activity_page(env: &Env, owner: &Address, page: u32, entries: &Vec<ActivityEntry>) {
+    let key = DataKey::ActivityPage(owner.clone(), page);
+    env.storage().persistent().set(&key, entries);
+}
+
+/// Get the number of entries in an account's activity feed
+pub fn get_activity_count(env: &Env, owner: &Address) -> u32 {
+    let key = DataKey::ActivityCount(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set the number of entries in an account's activity feed
+pub fn set_activity_count(env: &Env, owner: &Address, count: u32) {
+    let key = DataKey::ActivityCount(owner.clone());
+    env.storage().persistent().set(&key, &count);
+}
+
+/// Get the ranked largest open escrows of a token as (escrow id, amount)
+pub fn get_largest_escrows(env: &Env, token: &Address) -> Vec<(u64, i128)> {
+    let key = DataKey::LargestEscrows(token.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the ranked largest open escrows of a token
+pub fn set_largest_escrows(env: &Env, token: &Address, ranked: &Vec<(u64, i128)>) {
+    let key = DataKey::LargestEscrows(token.clone());
+    env.storage().persistent().set(&key, ranked);
+}
+
+/// Set the salt used to derive opaque escrow handles
+pub fn set_handle_salt(env: &Env, salt: &BytesN<32>) {
+    env.storage().persistent().set(&DataKey::HandleSalt, salt);
+}
+
+/// Get the salt used to derive opaque escrow handles
+pub fn get_handle_salt(env: &Env) -> Option<BytesN<32>> {
+    env.storage().persistent().get(&DataKey::HandleSalt)
+}
+
+/// Set the version of the current handle salt, bumped on each rotation
+pub fn set_handle_salt_version(env: &Env, version: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::HandleSaltVersion, &version);
+}
+
+/// Get the version of the current handle salt, 0 for the first salt
+pub fn get_handle_salt_version(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::HandleSaltVersion)
+        .unwrap_or(0)
+}
+
+/// Map an opaque escrow handle to its escrow id and the salt version it was
+/// derived with
+pub fn set_escrow_handle(env: &Env, handle: &BytesN<32>, escrow_id: u64, version: u32) {
+    let key = DataKey::EscrowHandle(handle.clone());
+    env.storage().persistent().set(&key, &(escrow_id, version));
+}
+
+/// Look up the escrow id and salt version behind an opaque handle
+pub fn get_escrow_handle(env: &Env, handle: &BytesN<32>) -> Option<(u64, u32)> {
+    let key = DataKey::EscrowHandle(handle.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Get the multi-recipient escrow ids an account may claim
+pub fn get_claimable_escrows(env: &Env, claimant: &Address) -> Vec<u64> {
+    let key = DataKey::ClaimableEscrows(claimant.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Remember which escrow a sender's idempotency key created, and with what
+/// parameters, for `ttl_ledgers` ledgers
+pub fn set_idempotency_key(
+    env: &Env,
+    from: &Address,
+    key: &BytesN<32>,
+    record: &(u64, BytesN<32>),
+    ttl_ledgers: u32,
+) {
+    let key = DataKey::IdempotencyKey(from.clone(), key.clone());
+    env.storage().temporary().set(&key, record);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, ttl_ledgers, ttl_ledgers);
+}
+
+/// Get the (escrow id, parameters hash) a sender's idempotency key created
+pub fn get_idempotency_key(
+    env: &Env,
+    from: &Address,
+    key: &BytesN<32>,
+) -> Option<(u64, BytesN<32>)> {
+    let key = DataKey::IdempotencyKey(from.clone(), key.clone());
+    env.storage().temporary().get(&key)
+}
+
+/// Get an account's settlement counters
+pub fn get_counterparty_stats(env: &Env, account: &Address) -> CounterpartyStats {
+    let key = DataKey::CounterpartyStats(account.clone());
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+/// Set an account's settlement counters
+pub fn set_counterparty_stats(env: &Env, account: &Address, stats: &CounterpartyStats) {
+    let key = DataKey::CounterpartyStats(account.clone());
+    env.storage().persistent().set(&key, stats);
+}
+
+/// Set whether an account hides its settlement counters
+pub fn set_stats_opt_out(env: &Env, account: &Address, opted_out: bool) {
+    let key = DataKey::StatsOptOut(account.clone());
+    if opted_out {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether an account hides its settlement counters
+pub fn is_stats_opt_out(env: &Env, account: &Address) -> bool {
+    let key = DataKey::StatsOptOut(account.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Get one fixed-size page of the commitments an account has deposited under
+pub fn get_owner_deposit_page(env: &Env, owner: &Address, page: u32) -> Vec<BytesN<32>> {
+    let key = DataKey::OwnerDeposits(owner.clone(), page);
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Get the number of commitment deposits recorded against an account
+pub fn get_owner_deposit_count(env: &Env, owner: &Address) -> u32 {
+    let key = DataKey::OwnerDepositCount(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Record a commitment deposit against its owner, on the last deposit page
+pub fn push_owner_deposit(env: &Env, owner: &Address, commitment: &BytesN<32>) {
+    let count = get_owner_deposit_count(env, owner);
+    let page = count / crate::export::EXPORT_PAGE_SIZE;
+
+    let mut deposits = get_owner_deposit_page(env, owner, page);
+    deposits.push_back(commitment.clone());
+    let key = DataKey::OwnerDeposits(owner.clone(), page);
+    env.storage().persistent().set(&key, &deposits);
+
+    let key = DataKey::OwnerDepositCount(owner.clone());
+    env.storage().persistent().set(&key, &(count + 1));
+}
+
+/// Set the multi-recipient escrow ids an account may claim
+pub fn set_claimable_escrows(env: &Env, claimant: &Address, ids: &Vec<u64>) {
+    let key = DataKey::ClaimableEscrows(claimant.clone());
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, ids);
+    }
+}
+
+/// Get the escrow ids indexed under a counterparty pair hash
+pub fn get_pair_escrows(env: &Env, pair: &BytesN<32>) -> Vec<u64> {
+    let key = DataKey::PairEscrows(pair.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the escrow ids indexed under a counterparty pair hash
+pub fn set_pair_escrows(env: &Env, pair: &BytesN<32>, ids: &Vec<u64>) {
+    let key = DataKey::PairEscrows(pair.clone());
+    env.storage().persistent().set(&key, ids);
+}
+
+/// Get the ids of a sender's open escrows, oldest first
+pub fn get_open_escrows(env: &Env, sender: &Address) -> Vec<u64> {
+    let key = DataKey::OpenEscrows(sender.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Set the ids of a sender's open escrows
+pub fn set_open_escrows(env: &Env, sender: &Address, ids: &Vec<u64>) {
+    let key = DataKey::OpenEscrows(sender.clone());
+    env.storage().persistent().set(&key, ids);
+}
+
+/// Get an owner's escrow templates keyed by template id
+pub fn get_templates(env: &Env, owner: &Address) -> Map<u32, EscrowTemplate> {
+    let key = DataKey::Templates(owner.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Map::new(env))
+}
+
+/// Set an owner's escrow templates
+pub fn set_templates(env: &Env, owner: &Address, templates: &Map<u32, EscrowTemplate>) {
+    let key = DataKey::Templates(owner.clone());
+    env.storage().persistent().set(&key, templates);
+}
+
+/// Increment and return an owner's template id counter
+pub fn increment_template_id(env: &Env, owner: &Address) -> u32 {
+    let key = DataKey::NextTemplateId(owner.clone());
+    let mut id: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    id += 1;
+    env.storage().persistent().set(&key, &id);
+    id
+}
+
+/// Set whether escrow events for a recipient carry full detail
+pub fn set_event_detail(env: &Env, owner: &Address, detailed: bool) {
+    let key = DataKey::EventDetail(owner.clone());
+    env.storage().persistent().set(&key, &detailed);
+}
+
+/// Get whether escrow events for a recipient carry full detail (default true)
+pub fn get_event_detail(env: &Env, owner: &Address) -> bool {
+    let key = DataKey::EventDetail(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(true)
+}
+
+/// Set the share of protocol fees (in bps) that goes to the insurance pool
+pub fn set_insurance_share_bps(env: &Env, share_bps: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::InsuranceShareBps, &share_bps);
+}
+
+/// Get the share of protocol fees (in bps) that goes to the insurance pool
+pub fn get_insurance_share_bps(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::InsuranceShareBps)
+        .unwrap_or(0)
+}
+
+/// Set the insurance pool balance for a token
+pub fn set_insurance_pool(env: &Env, token: &Address, balance: i128) {
+    let key = DataKey::InsurancePool(token.clone());
+    env.storage().persistent().set(&key, &balance);
+}
+
+/// Get the insurance pool balance for a token
+pub fn get_insurance_pool(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::InsurancePool(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set the protocol fees collected in a token
+pub fn set_collected_fees(env: &Env, token: &Address, balance: i128) {
+    let key = DataKey::CollectedFees(token.clone());
+    env.storage().persistent().set(&key, &balance);
+}
+
+/// Get the protocol fees collected in a token
+pub fn get_collected_fees(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::CollectedFees(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set the released funds a recipient can pull in a token
+///
+/// Keeps the token's total of credits owed in step, for the reserve proof.
+pub fn set_withdrawable(env: &Env, token: &Address, recipient: &Address, balance: i128) {
+    let owed = get_owed_total(env, &DataKey::CreditsOwed(token.clone()));
+    let delta = balance - get_withdrawable(env, token, recipient);
+    set_owed_total(env, &DataKey::CreditsOwed(token.clone()), owed + delta);
+
+    let key = DataKey::Withdrawable(token.clone(), recipient.clone());
+    if balance == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &balance);
+    }
+}
+
+/// Get the released funds a recipient can pull in a token
+pub fn get_withdrawable(env: &Env, token: &Address, recipient: &Address) -> i128 {
+    let key = DataKey::Withdrawable(token.clone(), recipient.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Get a per-token total the contract owes outside open escrows
+///
+/// `key` is one of `CreditsOwed`, `BondsHeld` or `DepositsHeld`.
+pub fn get_owed_total(env: &Env, key: &DataKey) -> i128 {
+    env.storage().persistent().get(key).unwrap_or(0)
+}
+
+/// Set a per-token total the contract owes outside open escrows
+pub fn set_owed_total(env: &Env, key: &DataKey, total: i128) {
+    env.storage().persistent().set(key, &total);
+}
+
+/// Get the first retained and next free sequence numbers of the event log
+pub fn get_event_log_bounds(env: &Env) -> (u64, u64) {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EventLogBounds)
+        .unwrap_or((0, 0))
+}
+
+/// Set the first retained and next free sequence numbers of the event log
+pub fn set_event_log_bounds(env: &Env, first: u64, next: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::EventLogBounds, &(first, next));
+}
+
+/// Store an event log entry
+pub fn set_event_log_entry(env: &Env, seq: u64, summary: &EventSummary) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::EventLog(seq), summary);
+}
+
+/// Get an event log entry
+pub fn get_event_log_entry(env: &Env, seq: u64) -> Option<EventSummary> {
+    env.storage().persistent().get(&DataKey::EventLog(seq))
+}
+
+/// Remove an event log entry
+pub fn remove_event_log_entry(env: &Env, seq: u64) {
+    env.storage().persistent().remove(&DataKey::EventLog(seq));
+}
+
+/// Set or clear the yield adapter registered for a token
+pub fn set_yield_adapter(env: &Env, token: &Address, adapter: Option<Address>) {
+    let key = DataKey::YieldAdapter(token.clone());
+    match adapter {
+        Some(adapter) => env.storage().persistent().set(&key, &adapter),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Get the yield adapter registered for a token
+pub fn get_yield_adapter(env: &Env, token: &Address) -> Option<Address> {
+    let key = DataKey::YieldAdapter(token.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Set the escrow principal currently parked with a yield adapter
+pub fn set_yield_principal(env: &Env, adapter: &Address, principal: i128) {
+    let key = DataKey::YieldPrincipal(adapter.clone());
+    env.storage().persistent().set(&key, &principal);
+}
+
+/// Get the escrow principal currently parked with a yield adapter
+pub fn get_yield_principal(env: &Env, adapter: &Address) -> i128 {
+    let key = DataKey::YieldPrincipal(adapter.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set the principal a yield adapter failed to return
+pub fn set_yield_shortfall(env: &Env, adapter: &Address, shortfall: i128) {
+    let key = DataKey::YieldShortfall(adapter.clone());
+    env.storage().persistent().set(&key, &shortfall);
+}
+
+/// Get the principal a yield adapter failed to return
+pub fn get_yield_shortfall(env: &Env, adapter: &Address) -> i128 {
+    let key = DataKey::YieldShortfall(adapter.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set when the protocol fee is charged on new escrows
+pub fn set_fee_timing(env: &Env, timing: FeeTiming) {
+    env.storage().persistent().set(&DataKey::FeeTiming, &timing);
+}
+
+/// Get when the protocol fee is charged on new escrows (default: on release)
+pub fn get_fee_timing(env: &Env) -> FeeTiming {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeeTiming)
+        .unwrap_or(FeeTiming::OnRelease)
+}
+
+/// Set the fee rate for escrows without their own `fee_bps`
+pub fn set_default_fee_bps(env: &Env, bps: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DefaultFeeBps, &bps);
+}
+
+/// Get the fee rate for escrows without their own `fee_bps` (default: 0)
+pub fn get_default_fee_bps(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DefaultFeeBps)
+        .unwrap_or(0)
+}
+
+/// Set whether escrows sent or received by an account pay no fee
+pub fn set_fee_exempt(env: &Env, account: &Address, exempt: bool) {
+    let key = DataKey::FeeExempt(account.clone());
+    if exempt {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether escrows sent or received by an account pay no fee
+pub fn is_fee_exempt(env: &Env, account: &Address) -> bool {
+    let key = DataKey::FeeExempt(account.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set whether an account only withdraws credit to approved destinations
+pub fn set_withdraw_whitelist(env: &Env, owner: &Address, enabled: bool) {
+    let key = DataKey::WithdrawWhitelist(owner.clone());
+    if enabled {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether an account only withdraws credit to approved destinations
+pub fn has_withdraw_whitelist(env: &Env, owner: &Address) -> bool {
+    let key = DataKey::WithdrawWhitelist(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Approve or revoke a destination for an account's credit withdrawals
+pub fn set_withdraw_destination(env: &Env, owner: &Address, dest: &Address, allowed: bool) {
+    let key = DataKey::WithdrawDestination(owner.clone(), dest.clone());
+    if allowed {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether a destination is approved for an account's credit withdrawals
+pub fn is_withdraw_destination(env: &Env, owner: &Address, dest: &Address) -> bool {
+    let key = DataKey::WithdrawDestination(owner.clone(), dest.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set the cap on escrow amounts released per day (0 = no cap)
+pub fn set_daily_outflow_cap(env: &Env, cap: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DailyOutflowCap, &cap);
 }
 
-/// Increment and return the escrow counter
-pub fn increment_escrow_counter(env: &Env) -> u64 {
-    let key = DataKey::EscrowCounter;
-    let mut count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
-    count += 1;
-    env.storage().persistent().set(&key, &count);
-    count
+/// Get the cap on escrow amounts released per day (0 = no cap)
+pub fn get_daily_outflow_cap(env: &Env) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DailyOutflowCap)
+        .unwrap_or(0)
+}
+
+/// Record the amount released so far on `day`
+pub fn set_daily_outflow(env: &Env, day: u64, used: i128) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DailyOutflow, &(day, used));
+}
+
+/// Get the day of the last recorded release and the amount released on it
+pub fn get_daily_outflow(env: &Env) -> (u64, i128) {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DailyOutflow)
+        .unwrap_or((0, 0))
+}
+
+/// Set whether every new escrow from `account` is created private
+pub fn set_private_escrows_by_default(env: &Env, account: &Address, enabled: bool) {
+    let key = DataKey::PrivateEscrowsByDefault(account.clone());
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Remove an account's standing escrow privacy setting
+pub fn clear_private_escrows_by_default(env: &Env, account: &Address) {
+    let key = DataKey::PrivateEscrowsByDefault(account.clone());
+    env.storage().persistent().remove(&key);
+}
+
+/// Whether every new escrow from `account` is created private
+pub fn get_private_escrows_by_default(env: &Env, account: &Address) -> bool {
+    let key = DataKey::PrivateEscrowsByDefault(account.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set whether commitments use the insecure deterministic test path
+pub fn set_test_mode(env: &Env, enabled: bool) {
+    env.storage().persistent().set(&DataKey::TestMode, &enabled);
+}
+
+/// Whether commitments use the insecure deterministic test path
+pub fn is_test_mode(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TestMode)
+        .unwrap_or(false)
+}
+
+/// Record whether a commitment was hashed with its salt, keeping the first answer
+///
+/// Later calls for the same commitment are ignored, so switching test mode
+/// doesn't change how an existing commitment is opened or exported.
+pub fn record_commitment_salted(env: &Env, commitment: &BytesN<32>, salted: bool) {
+    let key = DataKey::CommitmentSalted(commitment.clone());
+    if !env.storage().persistent().has(&key) {
+        env.storage().persistent().set(&key, &salted);
+    }
+}
+
+/// Whether a commitment was hashed with its salt (true unless it came in under test mode)
+pub fn is_commitment_salted(env: &Env, commitment: &BytesN<32>) -> bool {
+    let key = DataKey::CommitmentSalted(commitment.clone());
+    env.storage().persistent().get(&key).unwrap_or(true)
+}
+
+/// Set how long spent commitments are kept before they can be pruned
+pub fn set_nullifier_retention(env: &Env, seconds: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::NullifierRetention, &seconds);
+}
+
+/// Get how long spent commitments are kept before they can be pruned
+pub fn get_nullifier_retention(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::NullifierRetention)
+        .unwrap_or(crate::commitment::DEFAULT_NULLIFIER_RETENTION)
+}
+
+/// Set the most sibling hashes an inclusion proof may carry
+pub fn set_max_proof_len(env: &Env, max_len: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::MaxProofLen, &max_len);
+}
+
+/// Get the most sibling hashes an inclusion proof may carry
+pub fn get_max_proof_len(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MaxProofLen)
+        .unwrap_or(crate::commitment::DEFAULT_MAX_PROOF_LEN)
+}
+
+/// Set the dispute bond in bps of the escrow amount
+pub fn set_dispute_bond_bps(env: &Env, bond_bps: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DisputeBondBps, &bond_bps);
+}
+
+/// Get the dispute bond in bps of the escrow amount (0 = no bond)
+pub fn get_dispute_bond_bps(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DisputeBondBps)
+        .unwrap_or(0)
+}
+
+/// Set the maximum escrow lifetime in seconds (0 = unlimited)
+pub fn set_max_escrow_lifetime(env: &Env, seconds: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::MaxEscrowLifetime, &seconds);
+}
+
+/// Get the maximum escrow lifetime in seconds (0 = unlimited)
+pub fn get_max_escrow_lifetime(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MaxEscrowLifetime)
+        .unwrap_or(0)
+}
+
+/// Get the settlement totals of a token for a reporting epoch
+pub fn get_settlement_bucket(env: &Env, token: &Address, epoch: u32) -> SettlementBucket {
+    let key = DataKey::SettlementBucket(token.clone(), epoch);
+    env.storage().persistent().get(&key).unwrap_or_default()
+}
+
+/// Set the settlement totals of a token for a reporting epoch
+pub fn set_settlement_bucket(env: &Env, token: &Address, epoch: u32, bucket: &SettlementBucket) {
+    let key = DataKey::SettlementBucket(token.clone(), epoch);
+    env.storage().persistent().set(&key, bucket);
+}
+
+/// Remove the settlement totals of a token for a reporting epoch
+pub fn remove_settlement_bucket(env: &Env, token: &Address, epoch: u32) {
+    let key = DataKey::SettlementBucket(token.clone(), epoch);
+    env.storage().persistent().remove(&key);
+}
+
+/// Set the cooling-off rule: escrows above `threshold` wait `delay` seconds
+pub fn set_cooling_off(env: &Env, threshold: i128, delay: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::CoolingOff, &(threshold, delay));
+}
+
+/// Get the cooling-off rule as (threshold, delay); a zero threshold disables it
+pub fn get_cooling_off(env: &Env) -> (i128, u64) {
+    env.storage()
+        .persistent()
+        .get(&DataKey::CoolingOff)
+        .unwrap_or((0, 0))
+}
+
+/// Set the keeper incentive: `reward_bps` of a swept escrow after `grace` seconds
+pub fn set_keeper_incentive(env: &Env, reward_bps: u32, grace: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::KeeperIncentive, &(reward_bps, grace));
+}
+
+/// Get the keeper incentive as (reward_bps, grace); no reward by default
+pub fn get_keeper_incentive(env: &Env) -> (u32, u64) {
+    env.storage()
+        .persistent()
+        .get(&DataKey::KeeperIncentive)
+        .unwrap_or((0, 0))
+}
+
+/// Set how many seconds before a deadline `poke` warns (0 = never)
+pub fn set_deadline_warning_window(env: &Env, seconds: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::DeadlineWarningWindow, &seconds);
+}
+
+/// Get how many seconds before a deadline `poke` warns (0 = never)
+pub fn get_deadline_warning_window(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::DeadlineWarningWindow)
+        .unwrap_or(0)
+}
+
+/// Set whether escrows need both parties' attestations to be funded or released
+pub fn set_require_attestations(env: &Env, required: bool) {
+    if required {
+        env.storage()
+            .persistent()
+            .set(&DataKey::RequireAttestations, &true);
+    } else {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::RequireAttestations);
+    }
+}
+
+/// Whether escrows need both parties' attestations to be funded or released
+pub fn get_require_attestations(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RequireAttestations)
+        .unwrap_or(false)
+}
+
+/// Put the salt escrow for a commitment
+pub fn put_salt_escrow(env: &Env, commitment: &BytesN<32>, escrow: &SaltEscrow) {
+    let key = DataKey::SaltEscrow(commitment.clone());
+    env.storage().persistent().set(&key, escrow);
+}
+
+/// Get the salt escrow for a commitment
+pub fn get_salt_escrow(env: &Env, commitment: &BytesN<32>) -> Option<SaltEscrow> {
+    let key = DataKey::SaltEscrow(commitment.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Set the delay between a salt recovery request and release
+pub fn set_salt_recovery_delay(env: &Env, seconds: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SaltRecoveryDelay, &seconds);
+}
+
+/// Get the delay between a salt recovery request and release
+pub fn get_salt_recovery_delay(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SaltRecoveryDelay)
+        .unwrap_or(crate::salt_escrow::DEFAULT_SALT_RECOVERY_DELAY)
+}
+
+/// Get the modules enabled on this deployment; everything is on by default
+pub fn get_feature_flags(env: &Env) -> FeatureFlags {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeatureFlags)
+        .unwrap_or(FeatureFlags {
+            escrow_enabled: true,
+            commitments_enabled: true,
+            privacy_enabled: true,
+        })
+}
+
+/// Set the modules enabled on this deployment
+pub fn set_feature_flags(env: &Env, flags: &FeatureFlags) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::FeatureFlags, flags);
+}
+
+/// Store the output token and rate commitment for a swap escrow
+pub fn put_swap_quote(env: &Env, escrow_id: u64, quote: &SwapQuote) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SwapQuote(escrow_id), quote);
+}
+
+/// Get the output token and rate commitment for a swap escrow
+pub fn get_swap_quote(env: &Env, escrow_id: u64) -> Option<SwapQuote> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SwapQuote(escrow_id))
 }
 
 /// Set admin address
@@ -73,6 +1195,54 @@ pub fn is_paused(env: &Env) -> bool {
     env.storage().persistent().get(&key).unwrap_or(false)
 }
 
+/// Set whether new escrows in a token are blocked
+pub fn set_token_paused(env: &Env, token: &Address, paused: bool) {
+    let key = DataKey::TokenPaused(token.clone());
+    if paused {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Whether new escrows in a token are blocked
+pub fn is_token_paused(env: &Env, token: &Address) -> bool {
+    let key = DataKey::TokenPaused(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Grant or revoke the auditor role of an account
+pub fn set_auditor(env: &Env, account: &Address, enabled: bool) {
+    let key = DataKey::Auditor(account.clone());
+    if enabled {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Check whether an account holds the auditor role
+pub fn is_auditor(env: &Env, account: &Address) -> bool {
+    let key = DataKey::Auditor(account.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set the smallest escrow amount accepted in a token, 0 for no minimum
+pub fn set_token_min_amount(env: &Env, token: &Address, min: i128) {
+    let key = DataKey::TokenMinAmount(token.clone());
+    if min > 0 {
+        env.storage().persistent().set(&key, &min);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+/// Get the smallest escrow amount accepted in a token, 0 if unset
+pub fn get_token_min_amount(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::TokenMinAmount(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
 /// Set privacy level for an account
 pub fn set_privacy_level(env: &Env, account: &Address, level: u32) {
     let key = DataKey::PrivacyLevel(account.clone());
@@ -85,6 +1255,24 @@ pub fn get_privacy_level(env: &Env, account: &Address) -> Option<u32> {
     env.storage().persistent().get(&key)
 }
 
+/// Remove the privacy level for an account
+pub fn clear_privacy_level(env: &Env, account: &Address) {
+    let key = DataKey::PrivacyLevel(account.clone());
+    env.storage().persistent().remove(&key);
+}
+
+/// Set whether escrows in a token hide amounts by default
+pub fn set_token_privacy_default(env: &Env, token: &Address, enabled: bool) {
+    let key = DataKey::TokenPrivacyDefault(token.clone());
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Get whether escrows in a token hide amounts by default
+pub fn get_token_privacy_default(env: &Env, token: &Address) -> bool {
+    let key = DataKey::TokenPrivacyDefault(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
 /// Add to privacy history for an account
 pub fn add_privacy_history(env: &Env, account: &Address, level: u32) {
     let key = DataKey::PrivacyHistory(account.clone());
@@ -97,6 +1285,12 @@ pub fn add_privacy_history(env: &Env, account: &Address, level: u32) {
     env.storage().persistent().set(&key, &history);
 }
 
+/// Remove the privacy history for an account
+pub fn clear_privacy_history(env: &Env, account: &Address) {
+    let key = DataKey::PrivacyHistory(account.clone());
+    env.storage().persistent().remove(&key);
+}
+
 /// Get privacy history for an account
 pub fn get_privacy_history(env: &Env, account: &Address) -> Vec<u32> {
     let key = DataKey::PrivacyHistory(account.clone());
@@ -105,3 +1299,203 @@ pub fn get_privacy_history(env: &Env, account: &Address) -> Vec<u32> {
         .get(&key)
         .unwrap_or(Vec::new(env))
 }
+
+/// Mark a contract as a registered receiver for release callbacks
+pub fn set_receiver(env: &Env, contract: &Address) {
+    let key = DataKey::Receiver(contract.clone());
+    env.storage().persistent().set(&key, &true);
+}
+
+/// Check if a contract is a registered receiver
+pub fn is_receiver(env: &Env, contract: &Address) -> bool {
+    let key = DataKey::Receiver(contract.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Stop calling a contract back on releases
+pub fn remove_receiver(env: &Env, contract: &Address) {
+    let key = DataKey::Receiver(contract.clone());
+    env.storage().persistent().remove(&key);
+}
+
+/// Record that the release callback for a commitment trapped
+///
+/// Kept apart from `EscrowEntry` so entries stored by older builds still decode.
+pub fn set_callback_failed(env: &Env, commitment: &BytesN<32>) {
+    let key = DataKey::CallbackFailed(commitment.clone());
+    env.storage().persistent().set(&key, &true);
+}
+
+/// Check whether the release callback for a commitment trapped
+pub fn has_callback_failed(env: &Env, commitment: &BytesN<32>) -> bool {
+    let key = DataKey::CallbackFailed(commitment.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Get the contract notified when an escrow settles
+pub fn get_escrow_notify(env: &Env, escrow_id: u64) -> Option<Address> {
+    let key = DataKey::EscrowNotify(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+/// Set or clear the contract notified when an escrow settles
+pub fn set_escrow_notify(env: &Env, escrow_id: u64, notify: Option<&Address>) {
+    let key = DataKey::EscrowNotify(escrow_id);
+    match notify {
+        Some(contract) => env.storage().persistent().set(&key, contract),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Increment and return the commitment registry counter
+pub fn increment_commitment_counter(env: &Env) -> u64 {
+    let key = DataKey::CommitmentCounter;
+    let mut count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    count += 1;
+    env.storage().persistent().set(&key, &count);
+    count
+}
+
+/// Store a registered commitment under its id, with a reverse lookup by hash
+pub fn put_registered_commitment(env: &Env, id: u64, record: &CommitmentRecord) {
+    let key = DataKey::RegisteredCommitment(id);
+    env.storage().persistent().set(&key, record);
+    let id_key = DataKey::CommitmentId(record.commitment.clone());
+    env.storage().persistent().set(&id_key, &id);
+}
+
+/// Get a registered commitment by id
+pub fn get_registered_commitment(env: &Env, id: u64) -> Option<CommitmentRecord> {
+    let key = DataKey::RegisteredCommitment(id);
+    env.storage().persistent().get(&key)
+}
+
+/// Put the record of an anchored commitment root
+pub fn put_anchored_root(env: &Env, root: &BytesN<32>, record: &AnchoredRoot) {
+    let key = DataKey::AnchoredRoot(root.clone());
+    env.storage().persistent().set(&key, record);
+}
+
+/// Get the record of an anchored commitment root
+pub fn get_anchored_root(env: &Env, root: &BytesN<32>) -> Option<AnchoredRoot> {
+    let key = DataKey::AnchoredRoot(root.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Record the anchored root and leaf position of a commitment
+pub fn put_anchored_leaf(env: &Env, leaf: &BytesN<32>, root: &BytesN<32>, index: u32) {
+    let key = DataKey::AnchoredLeaf(leaf.clone());
+    env.storage().persistent().set(&key, &(root.clone(), index));
+}
+
+/// Get the anchored root and leaf position of a commitment, if it was anchored
+pub fn get_anchored_leaf(env: &Env, leaf: &BytesN<32>) -> Option<(BytesN<32>, u32)> {
+    let key = DataKey::AnchoredLeaf(leaf.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Store a (root, ledger) entry in a slot of the root history ring
+pub fn put_root_history(env: &Env, slot: u32, entry: &(BytesN<32>, u32)) {
+    let key = DataKey::RootHistory(slot);
+    env.storage().persistent().set(&key, entry);
+}
+
+/// Get the (root, ledger) entry in a slot of the root history ring
+pub fn get_root_history(env: &Env, slot: u32) -> Option<(BytesN<32>, u32)> {
+    let key = DataKey::RootHistory(slot);
+    env.storage().persistent().get(&key)
+}
+
+/// Get the number of roots ever written to the history ring
+pub fn get_root_history_count(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RootHistoryCount)
+        .unwrap_or(0)
+}
+
+/// Set the number of roots ever written to the history ring
+pub fn set_root_history_count(env: &Env, count: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RootHistoryCount, &count);
+}
+
+/// Get the registry id of a commitment hash
+pub fn get_commitment_id(env: &Env, commitment: &BytesN<32>) -> Option<u64> {
+    let key = DataKey::CommitmentId(commitment.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Increment and return the auction counter
+pub fn increment_auction_counter(env: &Env) -> u64 {
+    let key = DataKey::AuctionCounter;
+    let mut count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    count += 1;
+    env.storage().persistent().set(&key, &count);
+    count
+}
+
+/// Store an auction
+pub fn put_auction(env: &Env, auction_id: u64, auction: &Auction) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Auction(auction_id), auction);
+}
+
+/// Get an auction by id
+pub fn get_auction(env: &Env, auction_id: u64) -> Option<Auction> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Auction(auction_id))
+}
+
+/// Get a bidder's unrevealed bid commitment in an auction
+pub fn get_bid(env: &Env, auction_id: u64, bidder: &Address) -> Option<BytesN<32>> {
+    let key = DataKey::AuctionBid(auction_id, bidder.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Set a bidder's bid commitment in an auction, or clear it once revealed
+pub fn set_bid(env: &Env, auction_id: u64, bidder: &Address, commitment: Option<&BytesN<32>>) {
+    let key = DataKey::AuctionBid(auction_id, bidder.clone());
+    match commitment {
+        Some(commitment) => env.storage().persistent().set(&key, commitment),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Increment and return the deal counter
+pub fn increment_deal_counter(env: &Env) -> u64 {
+    let key = DataKey::DealCounter;
+    let mut count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    count += 1;
+    env.storage().persistent().set(&key, &count);
+    count
+}
+
+/// Store a deal
+pub fn put_deal(env: &Env, deal_id: u64, deal: &Deal) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Deal(deal_id), deal);
+}
+
+/// Get a deal by id
+pub fn get_deal(env: &Env, deal_id: u64) -> Option<Deal> {
+    env.storage().persistent().get(&DataKey::Deal(deal_id))
+}
+
+/// Get the deal an escrow is linked to
+pub fn get_escrow_deal(env: &Env, escrow_id: u64) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::EscrowDeal(escrow_id))
+}
+
+/// Record the deal an escrow is linked to
+pub fn set_escrow_deal(env: &Env, escrow_id: u64, deal_id: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::EscrowDeal(escrow_id), &deal_id);
+}
@@ -92,15 +92,37 @@ fn test_escrow_counter() {
         // Test initial counter value
         assert_eq!(get_escrow_counter(&env), 0);
 
-        // Test incrementing counter
-        assert_eq!(increment_escrow_counter(&env), 1);
-        assert_eq!(get_escrow_counter(&env), 1);
+        // Created ids start above the range reserved for imports
+        let first = crate::escrow::RESERVED_ESCROW_IDS + 1;
+        assert_eq!(next_escrow_id(&env), first);
+        assert_eq!(increment_escrow_counter(&env), first);
+        assert_eq!(get_escrow_counter(&env), first);
+
+        assert_eq!(increment_escrow_counter(&env), first + 1);
+        assert_eq!(get_escrow_counter(&env), first + 1);
+
+        assert_eq!(increment_escrow_counter(&env), first + 2);
+        assert_eq!(get_escrow_counter(&env), first + 2);
+        assert_eq!(get_legacy_escrow_count(&env), None);
+    });
+}
+
+#[test]
+fn test_escrow_counter_records_legacy_count() {
+    let env = Env::default();
+    let contract_id = env.register(crate::QuickexContract, ());
+    env.as_contract(&contract_id, || {
+        // A build from before versioning counted three escrows up from 1
+        env.storage()
+            .persistent()
+            .set(&DataKey::EscrowCounter, &3u64);
 
-        assert_eq!(increment_escrow_counter(&env), 2);
-        assert_eq!(get_escrow_counter(&env), 2);
+        let first = crate::escrow::RESERVED_ESCROW_IDS + 1;
+        assert_eq!(increment_escrow_counter(&env), first);
+        assert_eq!(get_legacy_escrow_count(&env), Some(3));
 
-        assert_eq!(increment_escrow_counter(&env), 3);
-        assert_eq!(get_escrow_counter(&env), 3);
+        increment_escrow_counter(&env);
+        assert_eq!(get_legacy_escrow_count(&env), Some(3));
     });
 }
 
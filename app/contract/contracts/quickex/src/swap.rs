@@ -0,0 +1,99 @@
+use crate::circuit_breaker;
+use crate::errors::QuickexError;
+use crate::escrow::{
+    advance, check_cooling_off, check_flags, new_escrow, open_escrow, pay_recipient,
+};
+use crate::events::publish_swap_settled;
+use crate::storage::{get_escrow_by_id, get_swap_quote, put_escrow_by_id, put_swap_quote};
+use crate::types::{EscrowStatus, SwapQuote};
+use soroban_sdk::{token, Address, Bytes, BytesN, Env};
+
+/// Hash a `num / den` exchange rate with a salt
+///
+/// Rates are committed as `sha256(num || den || salt)` with both terms as
+/// 16-byte big-endian integers.
+pub fn create_rate_commitment(env: &Env, num: i128, den: i128, salt: Bytes) -> BytesN<32> {
+    let mut payload = Bytes::new(env);
+    payload.extend_from_array(&num.to_be_bytes());
+    payload.extend_from_array(&den.to_be_bytes());
+    payload.append(&salt);
+    env.crypto().sha256(&payload).into()
+}
+
+/// Lock `amount_in` of `token_in` against a committed exchange rate
+///
+/// The recipient funds the output side: on settlement they pay `token_out`
+/// to the sender at the revealed rate and receive the escrowed input.
+pub fn create_swap_escrow(
+    env: &Env,
+    from: Address,
+    to: Address,
+    token_in: Address,
+    token_out: Address,
+    amount_in: i128,
+    rate_commitment: BytesN<32>,
+) -> Result<u64, QuickexError> {
+    let escrow = new_escrow(env, from, to, token_in, amount_in, Bytes::new(env));
+    let escrow_id = open_escrow(env, escrow)?;
+
+    put_swap_quote(
+        env,
+        escrow_id,
+        &SwapQuote {
+            token_out,
+            rate_commitment,
+        },
+    );
+
+    Ok(escrow_id)
+}
+
+/// Reveal the committed rate and settle both legs of a swap escrow
+///
+/// `amount_out = amount_in * num / den`, rounded down. The recipient must
+/// authorize paying that amount of the output token to the sender; the
+/// escrowed input is then released to them as for `release_escrow`.
+pub fn settle_swap(
+    env: &Env,
+    escrow_id: u64,
+    num: i128,
+    den: i128,
+    salt: Bytes,
+) -> Result<i128, QuickexError> {
+    let mut escrow = get_escrow_by_id(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+    let quote = get_swap_quote(env, escrow_id).ok_or(QuickexError::EscrowNotFound)?;
+
+    if create_rate_commitment(env, num, den, salt) != quote.rate_commitment {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    if num <= 0 || den <= 0 {
+        return Err(QuickexError::InvalidRate);
+    }
+
+    let amount_out = escrow
+        .amount
+        .checked_mul(num)
+        .ok_or(QuickexError::AmountOverflow)?
+        / den;
+    if amount_out <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    escrow.to.require_auth();
+
+    check_flags(env, escrow_id, &escrow.to)?;
+    check_cooling_off(env, &escrow)?;
+    circuit_breaker::consume(env, escrow.amount)?;
+    advance(env, escrow_id, &mut escrow, EscrowStatus::Released)?;
+    put_escrow_by_id(env, escrow_id, &escrow);
+
+    let token_out = token::Client::new(env, &quote.token_out);
+    token_out.transfer(&escrow.to, &escrow.from, &amount_out);
+
+    pay_recipient(env, escrow_id, &escrow);
+
+    publish_swap_settled(env, escrow_id, quote.token_out, amount_out);
+
+    Ok(amount_out)
+}
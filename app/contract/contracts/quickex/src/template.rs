@@ -0,0 +1,107 @@
+use crate::errors::QuickexError;
+use crate::escrow::{new_escrow, open_escrow, MAX_BPS};
+use crate::features::{self, Feature};
+use crate::storage::{get_templates, increment_template_id, set_templates};
+use crate::types::EscrowTemplate;
+use soroban_sdk::{Address, Bytes, Env};
+
+/// Maximum number of templates an owner can keep
+pub const MAX_TEMPLATES: u32 = 16;
+
+fn validate_template(template: &EscrowTemplate) -> Result<(), QuickexError> {
+    if template.fee_bps.is_some_and(|bps| bps > MAX_BPS) {
+        return Err(QuickexError::InvalidFeeBps);
+    }
+    Ok(())
+}
+
+pub fn create_template(
+    env: &Env,
+    owner: Address,
+    template: EscrowTemplate,
+) -> Result<u32, QuickexError> {
+    owner.require_auth();
+    validate_template(&template)?;
+
+    let mut templates = get_templates(env, &owner);
+    if templates.len() >= MAX_TEMPLATES {
+        return Err(QuickexError::TooManyTemplates);
+    }
+
+    let template_id = increment_template_id(env, &owner);
+    templates.set(template_id, template);
+    set_templates(env, &owner, &templates);
+
+    Ok(template_id)
+}
+
+pub fn update_template(
+    env: &Env,
+    owner: Address,
+    template_id: u32,
+    template: EscrowTemplate,
+) -> Result<(), QuickexError> {
+    owner.require_auth();
+    validate_template(&template)?;
+
+    let mut templates = get_templates(env, &owner);
+    if !templates.contains_key(template_id) {
+        return Err(QuickexError::TemplateNotFound);
+    }
+
+    templates.set(template_id, template);
+    set_templates(env, &owner, &templates);
+
+    Ok(())
+}
+
+pub fn delete_template(env: &Env, owner: Address, template_id: u32) -> Result<(), QuickexError> {
+    owner.require_auth();
+
+    let mut templates = get_templates(env, &owner);
+    if templates.remove(template_id).is_none() {
+        return Err(QuickexError::TemplateNotFound);
+    }
+    set_templates(env, &owner, &templates);
+
+    Ok(())
+}
+
+pub fn get_template(
+    env: &Env,
+    owner: Address,
+    template_id: u32,
+) -> Result<EscrowTemplate, QuickexError> {
+    match get_templates(env, &owner).get(template_id) {
+        Some(template) => Ok(template),
+        None if !features::is_enabled(env, Feature::Escrow) => Err(QuickexError::FeatureDisabled),
+        None => Err(QuickexError::TemplateNotFound),
+    }
+}
+
+/// Stamp out an escrow from a template; the escrow keeps its own copy of the
+/// terms, so later template changes don't affect it
+pub fn create_from_template(
+    env: &Env,
+    owner: Address,
+    template_id: u32,
+    amount: i128,
+) -> Result<u64, QuickexError> {
+    let template = get_template(env, owner.clone(), template_id)?;
+
+    let mut escrow = new_escrow(
+        env,
+        owner,
+        template.recipient,
+        template.token,
+        amount,
+        Bytes::new(env),
+    );
+    escrow.arbiter = template.arbiter;
+    if template.deadline_offset > 0 {
+        escrow.deadline = Some(env.ledger().timestamp() + template.deadline_offset);
+    }
+    escrow.fee_bps = template.fee_bps;
+
+    open_escrow(env, escrow)
+}
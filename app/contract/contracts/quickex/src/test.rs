@@ -1,8 +1,15 @@
 #![cfg(test)]
 use crate::{
-    storage::put_escrow, EscrowEntry, EscrowStatus, QuickexContract, QuickexContractClient,
+    errors::QuickexError, storage::put_escrow, types::CommitmentProofBundle, EscrowEntry,
+    EscrowStatus, QuickexContract, QuickexContractClient,
+};
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Events as _, Ledger, MockAuth, MockAuthInvoke},
+    token, vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, Val,
 };
-use soroban_sdk::{testutils::Address as _, token, xdr::ToXdr, Address, Bytes, BytesN, Env};
 
 fn setup<'a>() -> (Env, QuickexContractClient<'a>) {
     let env = Env::default();
@@ -69,6 +76,32 @@ fn test_successful_withdrawal() {
     let _ = client.withdraw(&token, &amount, &commitment, &to, &salt);
 }
 
+#[test]
+fn test_spent_commitment_count_tracks_withdrawals() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let to = Address::generate(&env);
+    let token_client = token::StellarAssetClient::new(&env, &token);
+    assert_eq!(client.spent_commitment_count(), 0);
+
+    let salts = [b"salt_a", b"salt_b", b"salt_c"];
+    for salt in salts {
+        let salt = Bytes::from_slice(&env, salt);
+        let commitment = client.create_amount_commitment(&to, &100, &salt);
+        setup_escrow(&env, &client.address, &token, 100, commitment.clone());
+        token_client.mint(&client.address, &100);
+        client.withdraw(&token, &100, &commitment, &to, &salt);
+    }
+    assert_eq!(client.spent_commitment_count(), 3);
+
+    // A rejected replay records no new nullifier
+    let salt = Bytes::from_slice(&env, b"salt_a");
+    let commitment = client.create_amount_commitment(&to, &100, &salt);
+    let result = client.try_withdraw(&token, &100, &commitment, &to, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::AlreadySpent)));
+    assert_eq!(client.spent_commitment_count(), 3);
+}
+
 #[test]
 #[should_panic]
 fn test_double_withdrawal_fails() {
@@ -230,6 +263,139 @@ fn test_set_and_get_privacy() {
     assert!(!client.get_privacy(&account));
 }
 
+#[test]
+fn test_privacy_until_lapses() {
+    let (env, client) = setup();
+    let account = Address::generate(&env);
+    env.ledger().set_timestamp(1_000);
+
+    client.set_privacy_until(&account, &1_500);
+    assert!(client.get_privacy(&account));
+
+    env.ledger().set_timestamp(1_499);
+    assert!(client.get_privacy(&account));
+
+    env.ledger().set_timestamp(1_500);
+    assert!(!client.get_privacy(&account));
+
+    // A plain toggle replaces the window with permanent privacy
+    client.set_privacy(&account, &true);
+    env.ledger().set_timestamp(10_000);
+    assert!(client.get_privacy(&account));
+}
+
+#[test]
+fn test_privacy_until_in_past_fails() {
+    let (env, client) = setup();
+    let account = Address::generate(&env);
+    env.ledger().set_timestamp(1_000);
+
+    let result = client.try_set_privacy_until(&account, &1_000);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidExpiry)));
+    assert!(!client.get_privacy(&account));
+}
+
+#[test]
+fn test_admin_reset_privacy() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    client.initialize(&admin);
+    env.ledger().set_timestamp(42);
+
+    client.set_privacy(&alice, &true);
+    client.enable_privacy(&alice, &2);
+    client.set_privacy(&bob, &true);
+
+    client.admin_reset_privacy(&admin, &vec![&env, alice.clone(), bob.clone()]);
+
+    let reset_event = |account: &Address| {
+        (
+            client.address.clone(),
+            (Symbol::new(&env, "PrivacyToggled"), account.clone()).into_val(&env),
+            Map::<Symbol, Val>::from_array(
+                &env,
+                [
+                    (Symbol::new(&env, "enabled"), false.into_val(&env)),
+                    (Symbol::new(&env, "timestamp"), 42u64.into_val(&env)),
+                ],
+            )
+            .into_val(&env),
+        )
+    };
+    assert_eq!(
+        env.events().all(),
+        vec![&env, reset_event(&alice), reset_event(&bob)]
+    );
+
+    assert!(!client.get_privacy(&alice));
+    assert!(!client.get_privacy(&bob));
+    assert_eq!(client.privacy_status(&alice), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_admin_reset_privacy_by_non_admin_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let account = Address::generate(&env);
+    client.admin_reset_privacy(&account, &vec![&env, account.clone()]);
+}
+
+#[test]
+fn test_forget_me_clears_privacy_storage() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    env.ledger().set_timestamp(42);
+
+    client.set_privacy_until(&alice, &1_000);
+    client.enable_privacy(&alice, &2);
+    client.enable_privacy(&alice, &3);
+    client.set_private_escrows_by_default(&alice, &true);
+    client.set_privacy(&bob, &true);
+
+    client.forget_me(&alice);
+
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "DataForgotten"), alice.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [(Symbol::new(&env, "timestamp"), 42u64.into_val(&env))],
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+
+    assert!(!client.get_privacy(&alice));
+    assert_eq!(client.privacy_status(&alice), None);
+    assert_eq!(client.privacy_history(&alice), vec![&env]);
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        assert!(!storage.has(&(Symbol::new(&env, "privacy_enabled"), alice.clone())));
+        assert!(!storage.has(&(Symbol::new(&env, "privacy_until"), alice.clone())));
+        assert!(!storage.has(&crate::storage::DataKey::PrivacyLevel(alice.clone())));
+        assert!(!storage.has(&crate::storage::DataKey::PrivacyHistory(alice.clone())));
+        assert!(
+            !storage.has(&crate::storage::DataKey::PrivateEscrowsByDefault(
+                alice.clone()
+            ))
+        );
+    });
+
+    // Other accounts keep their settings
+    assert!(client.get_privacy(&bob));
+}
+
 #[test]
 fn test_commitment_cycle() {
     let (env, client) = setup();
@@ -259,13 +425,366 @@ fn test_commitment_cycle() {
 }
 
 #[test]
-fn test_create_escrow() {
+fn test_mixed_commitment_returns_opening_salt() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"weak_salt");
+
+    let first = client.create_amount_commitment_mixed(&owner, &500, &salt);
+    assert!(client.verify_amount_commitment(
+        &first.commitment,
+        &owner,
+        &500,
+        &first.effective_salt
+    ));
+    assert!(!client.verify_amount_commitment(&first.commitment, &owner, &500, &salt));
+
+    let second = client.create_amount_commitment_mixed(&owner, &500, &salt);
+    assert_ne!(first.effective_salt, second.effective_salt);
+    assert_ne!(first.commitment, second.commitment);
+}
+
+#[test]
+fn test_timestamped_commitment_verifies_only_with_its_timestamp() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"fresh_salt");
+    env.ledger().set_timestamp(1_700_000_000);
+
+    let (commitment, created_at) = client.create_timestamped_commitment(&owner, &500, &salt);
+    assert_eq!(created_at, 1_700_000_000);
+    assert_eq!(client.timestamped_commitment_params().version, 3);
+    assert!(client.verify_timestamped_commitment(&commitment, &owner, &500, &created_at, &salt));
+    assert!(!client.verify_timestamped_commitment(
+        &commitment,
+        &owner,
+        &500,
+        &(created_at - 1),
+        &salt
+    ));
+
+    // The same opening made later is a different commitment
+    env.ledger().set_timestamp(1_700_000_060);
+    let (later, later_at) = client.create_timestamped_commitment(&owner, &500, &salt);
+    assert_eq!(later_at, 1_700_000_060);
+    assert_ne!(later, commitment);
+    assert_ne!(
+        commitment,
+        client.create_amount_commitment(&owner, &500, &salt)
+    );
+}
+
+#[test]
+fn test_verify_and_diff() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"diff_salt");
+    let commitment = client.create_amount_commitment(&owner, &500, &salt);
+
+    let (matched, recomputed) = client.verify_and_diff(&commitment, &owner, &500, &salt);
+    assert!(matched);
+    assert_eq!(recomputed, commitment);
+
+    // A wrong amount reports the hash it actually produces
+    let (matched, recomputed) = client.verify_and_diff(&commitment, &owner, &501, &salt);
+    assert!(!matched);
+    assert_eq!(
+        recomputed,
+        client.create_amount_commitment(&owner, &501, &salt)
+    );
+    assert_ne!(recomputed, commitment);
+}
+
+#[test]
+fn test_salt_blob_released_after_delay() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let recovery = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_salt_recovery_delay(&admin, &600);
+    env.ledger().set_timestamp(1_000);
+
+    let commitment = client.create_amount_commitment(&owner, &10, &Bytes::from_slice(&env, b"s"));
+    let blob = Bytes::from_slice(&env, b"ciphertext");
+    client.store_salt_blob(&owner, &commitment, &blob, &recovery);
+
+    // Nothing is released before a request has waited out the delay
+    let result = client.try_release_salt_blob(&commitment, &recovery);
+    assert_eq!(result, Err(Ok(QuickexError::RecoveryDelayActive)));
+    client.request_salt_recovery(&commitment, &recovery);
+    env.ledger().set_timestamp(1_599);
+    let result = client.try_release_salt_blob(&commitment, &recovery);
+    assert_eq!(result, Err(Ok(QuickexError::RecoveryDelayActive)));
+
+    env.ledger().set_timestamp(1_600);
+    assert_eq!(client.release_salt_blob(&commitment, &recovery), blob);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "SaltBlobReleased"), commitment.clone()).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "recovery"), recovery.into_val(&env)),
+                        (Symbol::new(&env, "blob"), blob.into_val(&env)),
+                    ],
+                )
+                .into_val(&env),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_salt_recovery_cancel_and_access() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let recovery = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let commitment = BytesN::from_array(&env, &[9; 32]);
+    env.ledger().set_timestamp(1_000);
+
+    let oversized = Bytes::from_array(&env, &[0u8; 257]);
+    let result = client.try_store_salt_blob(&owner, &commitment, &oversized, &recovery);
+    assert_eq!(result, Err(Ok(QuickexError::SaltBlobTooLarge)));
+
+    let blob = Bytes::from_slice(&env, b"ciphertext");
+    client.store_salt_blob(&owner, &commitment, &blob, &recovery);
+
+    // Only the designated recovery party may request or receive the blob
+    let result = client.try_request_salt_recovery(&commitment, &stranger);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_release_salt_blob(&commitment, &stranger);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    let result = client.try_store_salt_blob(&stranger, &commitment, &blob, &stranger);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    // The owner can cancel while the delay runs, resetting the clock
+    client.request_salt_recovery(&commitment, &recovery);
+    let result = client.try_cancel_salt_recovery(&stranger, &commitment);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+    client.cancel_salt_recovery(&owner, &commitment);
+
+    env.ledger().set_timestamp(1_000 + 7 * 24 * 60 * 60);
+    let result = client.try_release_salt_blob(&commitment, &recovery);
+    assert_eq!(result, Err(Ok(QuickexError::RecoveryDelayActive)));
+}
+
+#[test]
+fn test_commitment_to_hex() {
+    let (env, client) = setup();
+
+    let mut raw = [0u8; 32];
+    for (i, byte) in raw.iter_mut().enumerate() {
+        *byte = (i as u8) * 8;
+    }
+    raw[31] = 0xff;
+
+    let hex = client.commitment_to_hex(&BytesN::from_array(&env, &raw));
+    assert_eq!(
+        hex,
+        Bytes::from_slice(
+            &env,
+            b"0008101820283038404850586068707880889098a0a8b0b8c0c8d0d8e0e8f0ff"
+        )
+    );
+    assert_eq!(hex.len(), 64);
+}
+
+#[test]
+fn test_commitment_params_reconstruct_off_chain() {
+    let (env, client) = setup();
+    let params = client.commitment_params();
+
+    assert_eq!(params.algorithm, soroban_sdk::symbol_short!("sha256"));
+    assert_eq!(params.rounds, 1);
+    assert_eq!(params.domain, Bytes::new(&env));
+    assert_eq!(params.version, 1);
+    assert_eq!(params.max_salt_len, 1024);
+
+    // Rebuild the commitment from the published parameters alone
+    let owner = Address::generate(&env);
+    let amount = 2_500i128;
+    let salt = Bytes::from_slice(&env, b"off_chain_salt");
+
+    let mut preimage = params.domain.clone();
+    preimage.append(&owner.clone().to_xdr(&env));
+    preimage.append(&Bytes::from_slice(&env, &amount.to_be_bytes()));
+    preimage.append(&salt);
+    let mut digest: BytesN<32> = env.crypto().sha256(&preimage).into();
+    for _ in 1..params.rounds {
+        digest = env.crypto().sha256(&digest.into()).into();
+    }
+
+    assert!(client.verify_amount_commitment(&digest, &owner, &amount, &salt));
+}
+
+#[test]
+fn test_verify_registered_valid_opening() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let amount = 5_000i128;
+    let salt = Bytes::from_slice(&env, b"registered_salt");
+
+    let commitment = client.create_amount_commitment(&owner, &amount, &salt);
+    let id = client.register_commitment(&owner, &commitment);
+
+    let record = client.get_registered_commitment(&id).unwrap();
+    assert_eq!(record.commitment, commitment);
+    assert_eq!(record.owner, owner);
+
+    assert!(client.verify_registered(&id, &owner, &amount, &salt));
+    assert!(!client.verify_registered(&id, &owner, &(amount + 1), &salt));
+}
+
+#[test]
+fn test_third_party_cannot_register_for_owner() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let token = create_test_token(&env);
+    let salt = Bytes::from_slice(&env, b"spam");
+    let commitment = client.create_amount_commitment(&owner, &1, &salt);
+
+    // Only the attacker signs
+    let register = |fn_name: &'static str, args: soroban_sdk::Vec<Val>| {
+        env.mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name,
+                args,
+                sub_invokes: &[],
+            },
+        }]);
+    };
+
+    register(
+        "register_commitment",
+        (owner.clone(), commitment.clone()).into_val(&env),
+    );
+    assert!(client.try_register_commitment(&owner, &commitment).is_err());
+
+    register(
+        "register_asset_commitment",
+        (owner.clone(), token.clone(), commitment.clone()).into_val(&env),
+    );
+    assert!(client
+        .try_register_asset_commitment(&owner, &token, &commitment)
+        .is_err());
+
+    let leaves = vec![&env, commitment.clone()];
+    register(
+        "register_commitments_anchored",
+        (owner.clone(), leaves.clone()).into_val(&env),
+    );
+    assert!(client
+        .try_register_commitments_anchored(&owner, &leaves)
+        .is_err());
+
+    assert!(client.get_registered_commitment(&1).is_none());
+
+    // Computing a commitment stores nothing and needs no signature
+    env.set_auths(&[]);
+    assert_eq!(
+        client.compute_amount_commitment(&owner, &1, &salt),
+        commitment
+    );
+    assert!(env.auths().is_empty());
+}
+
+#[test]
+fn test_asset_commitment_binds_token() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"asset_salt");
+    let usdc = create_test_token(&env);
+    let xlm = create_test_token(&env);
+
+    let for_usdc = client.create_asset_amount_commitment(&owner, &usdc, &1_000_000, &salt);
+    let for_xlm = client.create_asset_amount_commitment(&owner, &xlm, &1_000_000, &salt);
+    let legacy = client.create_amount_commitment(&owner, &1_000_000, &salt);
+    assert_eq!(client.asset_commitment_params().version, 2);
+    assert_ne!(for_usdc, for_xlm);
+    assert_ne!(for_usdc, legacy);
+
+    // Registered v2 records keep the token and verify with it
+    let id = client.register_asset_commitment(&owner, &usdc, &for_usdc);
+    assert_eq!(
+        client.get_registered_commitment(&id).unwrap().token,
+        Some(usdc)
+    );
+    assert!(client.verify_registered(&id, &owner, &1_000_000, &salt));
+    assert!(!client.verify_registered(&id, &owner, &999_999, &salt));
+
+    // v1 records still verify under the token-less scheme
+    let legacy_id = client.register_commitment(&owner, &legacy);
+    assert_eq!(
+        client.get_registered_commitment(&legacy_id).unwrap().token,
+        None
+    );
+    assert!(client.verify_registered(&legacy_id, &owner, &1_000_000, &salt));
+}
+
+#[test]
+fn test_asset_commitment_withdrawal_enforces_token() {
     let (env, client) = setup();
-    let from = Address::generate(&env);
     let to = Address::generate(&env);
-    let amount = 1_000;
-    let escrow_id = client.create_escrow(&from, &to, &amount);
-    assert!(escrow_id > 0);
+    let amount = 1_000i128;
+    let salt = Bytes::from_slice(&env, b"asset_salt");
+    let promised = create_test_token(&env);
+    let cheap = create_test_token(&env);
+
+    // A commitment to `promised` funded with a different token can't be
+    // withdrawn as either asset
+    let commitment = client.create_asset_amount_commitment(&to, &promised, &amount, &salt);
+    setup_escrow(&env, &client.address, &cheap, amount, commitment.clone());
+    token::StellarAssetClient::new(&env, &cheap).mint(&client.address, &amount);
+
+    let result = client.try_withdraw(&promised, &amount, &commitment, &to, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::TokenMismatch)));
+    let result = client.try_withdraw(&cheap, &amount, &commitment, &to, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentNotFound)));
+
+    // Funded with the committed token it pays out
+    let from = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &promised).mint(&from, &amount);
+    let salt = Bytes::from_slice(&env, b"second_salt");
+    let commitment = client.create_asset_amount_commitment(&to, &promised, &amount, &salt);
+    client.deposit_with_commitment(&from, &promised, &amount, &commitment);
+
+    assert!(client.withdraw(&promised, &amount, &commitment, &to, &salt));
+    assert_eq!(token::Client::new(&env, &promised).balance(&to), amount);
+}
+
+#[test]
+fn test_verify_and_require_registered() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"registered_salt");
+
+    let commitment = client.create_amount_commitment(&owner, &5_000, &salt);
+    let result = client.try_verify_and_require_registered(&commitment, &owner, &5_000, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentNotRegistered)));
+
+    client.register_commitment(&owner, &commitment);
+    client.verify_and_require_registered(&commitment, &owner, &5_000, &salt);
+
+    let result = client.try_verify_and_require_registered(&commitment, &owner, &4_999, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentMismatch)));
+}
+
+#[test]
+fn test_verify_registered_unknown_id() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"registered_salt");
+
+    assert!(!client.verify_registered(&42, &owner, &5_000, &salt));
 }
 
 #[test]
@@ -274,6 +793,22 @@ fn test_health_check() {
     assert!(client.health_check());
 }
 
+#[test]
+fn test_metadata_reports_features() {
+    use crate::metadata::*;
+
+    let (_, client) = setup();
+    let metadata = client.metadata();
+
+    assert_eq!(metadata.name, soroban_sdk::symbol_short!("quickex"));
+    assert_eq!(metadata.version, CONTRACT_VERSION);
+    assert_ne!(metadata.features & FEATURE_ESCROW, 0);
+    assert_ne!(metadata.features & FEATURE_COMMITMENTS, 0);
+    assert_ne!(metadata.features & FEATURE_DISPUTES, 0);
+    assert_ne!(metadata.features & FEATURE_HANDLES, 0);
+    assert_eq!(metadata.features & FEATURE_STREAMS, 0);
+}
+
 #[test]
 fn test_deposit() {
     let env = Env::default();
@@ -315,6 +850,33 @@ fn test_initialize_admin() {
     assert!(!client.is_paused());
 }
 
+#[test]
+fn test_query_admin_not_initialized() {
+    let (env, client) = setup();
+
+    assert_eq!(
+        client.try_query_admin().err(),
+        Some(Ok(QuickexError::NotInitialized))
+    );
+    assert_eq!(client.get_admin(), None);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.query_admin(), admin);
+}
+
+#[test]
+fn test_query_escrow_details_missing_commitment() {
+    let (env, client) = setup();
+    let commitment = BytesN::from_array(&env, &[7; 32]);
+
+    assert_eq!(
+        client.try_query_escrow_details(&commitment).err(),
+        Some(Ok(QuickexError::CommitmentNotFound))
+    );
+    assert!(client.get_escrow_details(&commitment).is_none());
+}
+
 #[test]
 #[should_panic(expected = "Error(Contract, #1)")]
 fn test_initialize_twice_fails() {
@@ -775,3 +1337,738 @@ fn test_upgrade_without_admin_initialized_fails() {
     // Try to upgrade without admin set - should fail with Unauthorized
     client.upgrade(&caller, &new_wasm_hash);
 }
+
+// ============================================================================
+// Receiver Callback Tests
+// ============================================================================
+
+#[contract]
+pub struct TrappingReceiver;
+
+#[contractimpl]
+impl TrappingReceiver {
+    pub fn on_escrow_released(_env: Env, _commitment: BytesN<32>, _token: Address, _amount: i128) {
+        panic!("receiver trapped");
+    }
+
+    pub fn on_escrow_event(_env: Env, _escrow_id: u64, _status: EscrowStatus) {
+        panic!("receiver trapped");
+    }
+}
+
+/// Notify contract that remembers the last status it was told per escrow
+#[contract]
+pub struct RecordingNotify;
+
+#[contractimpl]
+impl RecordingNotify {
+    pub fn on_escrow_event(env: Env, escrow_id: u64, status: EscrowStatus) {
+        env.storage().instance().set(&escrow_id, &status);
+    }
+
+    pub fn last_status(env: Env, escrow_id: u64) -> Option<EscrowStatus> {
+        env.storage().instance().get(&escrow_id)
+    }
+}
+
+/// Pending escrow of 1_000 from a fresh sender, returning (sender, recipient, token, id)
+fn setup_notify_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+) -> (Address, Address, Address, u64) {
+    let from = Address::generate(env);
+    let to = Address::generate(env);
+    let token = create_test_token(env);
+    token::StellarAssetClient::new(env, &token).mint(&from, &1_000);
+    let escrow_id = client.create_escrow(&from, &to, &token, &1_000, &Bytes::new(env));
+    (from, to, token, escrow_id)
+}
+
+#[test]
+fn test_escrow_notify_called_on_release_and_refund() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let notify = env.register(RecordingNotify, ());
+    let notify_client = RecordingNotifyClient::new(&env, &notify);
+
+    let (from, _, _, released_id) = setup_notify_escrow(&env, &client);
+    let (refunded_from, _, _, refunded_id) = setup_notify_escrow(&env, &client);
+
+    // Only the sender may set it, and only to a registered receiver
+    let result = client.try_set_escrow_notify(&from, &released_id, &Some(notify.clone()));
+    assert_eq!(result, Err(Ok(QuickexError::ReceiverNotRegistered)));
+    client.register_receiver(&admin, &notify);
+    let result = client.try_set_escrow_notify(&refunded_from, &released_id, &Some(notify.clone()));
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+
+    client.set_escrow_notify(&from, &released_id, &Some(notify.clone()));
+    client.set_escrow_notify(&refunded_from, &refunded_id, &Some(notify.clone()));
+    assert_eq!(client.get_escrow_notify(&released_id), Some(notify.clone()));
+    assert_eq!(notify_client.last_status(&released_id), None);
+
+    client.release_escrow(&from, &released_id);
+    client.admin_cancel_escrow(&admin, &refunded_id, &Symbol::new(&env, "support"));
+
+    assert_eq!(
+        notify_client.last_status(&released_id),
+        Some(EscrowStatus::Released)
+    );
+    assert_eq!(
+        notify_client.last_status(&refunded_id),
+        Some(EscrowStatus::Refunded)
+    );
+
+    let result = client.try_set_escrow_notify(&from, &released_id, &None);
+    assert_eq!(result, Err(Ok(QuickexError::EscrowAlreadyResolved)));
+}
+
+#[test]
+fn test_failing_escrow_notify_does_not_block_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let notify = env.register(TrappingReceiver, ());
+    client.register_receiver(&admin, &notify);
+
+    let (from, to, token, escrow_id) = setup_notify_escrow(&env, &client);
+    client.set_escrow_notify(&from, &escrow_id, &Some(notify));
+
+    client.release_escrow(&from, &escrow_id);
+    assert_eq!(token::Client::new(&env, &token).balance(&to), 1_000);
+    assert_eq!(
+        client.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Released
+    );
+}
+
+fn setup_receiver_withdrawal(
+    env: &Env,
+    client: &QuickexContractClient,
+    receiver: &Address,
+) -> (Address, i128, BytesN<32>, Bytes) {
+    let token = create_test_token(env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(env, b"receiver_salt");
+
+    let mut data = Bytes::new(env);
+    data.append(&receiver.clone().to_xdr(env));
+    data.append(&Bytes::from_slice(env, &amount.to_be_bytes()));
+    data.append(&salt);
+    let commitment: BytesN<32> = env.crypto().sha256(&data).into();
+
+    setup_escrow(env, &client.address, &token, amount, commitment.clone());
+    token::StellarAssetClient::new(env, &token).mint(&client.address, &amount);
+
+    (token, amount, commitment, salt)
+}
+
+#[test]
+fn test_registered_receiver_trap_does_not_revert_release() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let receiver = env.register(TrappingReceiver, ());
+    client.register_receiver(&admin, &receiver);
+    assert!(client.is_receiver(&receiver));
+
+    let (token, amount, commitment, salt) = setup_receiver_withdrawal(&env, &client, &receiver);
+    assert!(client.withdraw(&token, &amount, &commitment, &receiver, &salt));
+
+    // Funds moved even though the callback trapped
+    assert_eq!(token::Client::new(&env, &token).balance(&receiver), amount);
+
+    let entry = client.get_escrow_details(&commitment).unwrap();
+    assert_eq!(entry.status, EscrowStatus::Spent);
+    assert!(client.has_callback_failed(&commitment));
+}
+
+#[test]
+fn test_cleared_receiver_is_not_called() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let receiver = env.register(TrappingReceiver, ());
+    client.register_receiver(&admin, &receiver);
+    assert_eq!(
+        client.try_clear_receiver(&Address::generate(&env), &receiver),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+    client.clear_receiver(&admin, &receiver);
+    assert!(!client.is_receiver(&receiver));
+
+    let (token, amount, commitment, salt) = setup_receiver_withdrawal(&env, &client, &receiver);
+    assert!(client.withdraw(&token, &amount, &commitment, &receiver, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&receiver), amount);
+    assert!(!client.has_callback_failed(&commitment));
+}
+
+#[test]
+fn test_unregistered_receiver_is_not_called() {
+    let (env, client) = setup();
+    let receiver = env.register(TrappingReceiver, ());
+
+    let (token, amount, commitment, salt) = setup_receiver_withdrawal(&env, &client, &receiver);
+    client.withdraw(&token, &amount, &commitment, &receiver, &salt);
+
+    assert_eq!(token::Client::new(&env, &token).balance(&receiver), amount);
+    assert!(!client.has_callback_failed(&commitment));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_register_receiver_by_non_admin_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let non_admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let receiver = env.register(TrappingReceiver, ());
+    client.register_receiver(&non_admin, &receiver);
+}
+
+fn put_entry_at(
+    env: &Env,
+    client: &QuickexContractClient,
+    seed: u8,
+    status: EscrowStatus,
+    created_at: u64,
+) -> BytesN<32> {
+    let commitment = BytesN::from_array(env, &[seed; 32]);
+    let entry = EscrowEntry {
+        token: Address::generate(env),
+        amount: 100,
+        owner: Address::generate(env),
+        status,
+        created_at,
+    };
+    env.as_contract(&client.address, || {
+        put_escrow(env, &commitment.clone().into(), &entry);
+    });
+    commitment
+}
+
+#[test]
+fn test_prune_only_spent_entries_past_retention() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_nullifier_retention(&admin, &1_000);
+    env.ledger().set_timestamp(5_000);
+
+    let old_spent = put_entry_at(&env, &client, 1, EscrowStatus::Spent, 3_000);
+    let fresh_spent = put_entry_at(&env, &client, 2, EscrowStatus::Spent, 4_500);
+    let old_pending = put_entry_at(&env, &client, 3, EscrowStatus::Pending, 1_000);
+    let unknown = BytesN::from_array(&env, &[4; 32]);
+
+    let pruned = client.prune_nullifiers(
+        &admin,
+        &vec![
+            &env,
+            old_spent.clone(),
+            fresh_spent.clone(),
+            old_pending.clone(),
+            unknown,
+        ],
+    );
+    assert_eq!(pruned, 1);
+    assert_eq!(client.get_commitment_state(&old_spent), None);
+    assert_eq!(
+        client.get_commitment_state(&fresh_spent),
+        Some(EscrowStatus::Spent)
+    );
+    assert_eq!(
+        client.get_commitment_state(&old_pending),
+        Some(EscrowStatus::Pending)
+    );
+
+    // Once its retention has also elapsed the newer nullifier goes too
+    env.ledger().set_timestamp(5_500);
+    assert_eq!(
+        client.prune_nullifiers(&admin, &vec![&env, fresh_spent.clone()]),
+        1
+    );
+    assert_eq!(client.get_commitment_state(&fresh_spent), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn test_prune_nullifiers_non_admin_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.prune_nullifiers(&Address::generate(&env), &vec![&env]);
+}
+
+fn anchor_leaf(env: &Env, leaf: &BytesN<32>) -> BytesN<32> {
+    let mut payload = Bytes::from_array(env, &[0]);
+    payload.append(&leaf.clone().into());
+    env.crypto().sha256(&payload).into()
+}
+
+fn anchor_node(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut payload = Bytes::from_array(env, &[1]);
+    payload.append(&first.clone().into());
+    payload.append(&second.clone().into());
+    env.crypto().sha256(&payload).into()
+}
+
+#[test]
+fn test_register_commitments_anchored_inclusion_proofs() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let leaves = vec![
+        &env,
+        BytesN::from_array(&env, &[1; 32]),
+        BytesN::from_array(&env, &[2; 32]),
+        BytesN::from_array(&env, &[3; 32]),
+        BytesN::from_array(&env, &[4; 32]),
+        BytesN::from_array(&env, &[5; 32]),
+    ];
+
+    let root = client.register_commitments_anchored(&owner, &leaves);
+
+    // Five leaves: the last one is carried up unpaired to the top level
+    let mut hashed = soroban_sdk::Vec::new(&env);
+    for leaf in leaves.iter() {
+        hashed.push_back(anchor_leaf(&env, &leaf));
+    }
+    let n01 = anchor_node(&env, &hashed.get(0).unwrap(), &hashed.get(1).unwrap());
+    let n23 = anchor_node(&env, &hashed.get(2).unwrap(), &hashed.get(3).unwrap());
+    let n0123 = anchor_node(&env, &n01, &n23);
+    assert_eq!(root, anchor_node(&env, &n0123, &hashed.get(4).unwrap()));
+
+    let record = client.get_anchored_root(&root).unwrap();
+    assert_eq!(record.owner, owner);
+    assert_eq!(record.leaf_count, 5);
+
+    // Every leaf is individually registered
+    for (i, leaf) in leaves.iter().enumerate() {
+        let record = client.get_registered_commitment(&(i as u64 + 1)).unwrap();
+        assert_eq!(record.commitment, leaf);
+        assert_eq!(record.owner, owner);
+    }
+
+    let third = leaves.get(2).unwrap();
+    let proof = vec![
+        &env,
+        hashed.get(3).unwrap(),
+        n01.clone(),
+        hashed.get(4).unwrap(),
+    ];
+    assert!(client.verify_inclusion(&root, &third, &proof));
+    assert!(client.verify_inclusion(&root, &leaves.get(4).unwrap(), &vec![&env, n0123.clone()]));
+
+    // Wrong leaf, wrong proof or an unanchored root all fail
+    assert!(!client.verify_inclusion(&root, &leaves.get(0).unwrap(), &proof));
+    assert!(!client.verify_inclusion(&root, &third, &vec![&env, n01.clone()]));
+    assert!(!client.verify_inclusion(&n0123, &third, &vec![&env, hashed.get(3).unwrap(), n01]));
+}
+
+#[test]
+fn test_verify_inclusion_rejects_over_length_proofs() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+
+    let leaves = vec![
+        &env,
+        BytesN::from_array(&env, &[1; 32]),
+        BytesN::from_array(&env, &[2; 32]),
+    ];
+    let root = client.register_commitments_anchored(&owner, &leaves);
+    let proof = vec![&env, anchor_leaf(&env, &leaves.get(1).unwrap())];
+    assert_eq!(client.get_max_proof_len(), 32);
+    assert!(client.verify_inclusion(&root, &leaves.get(0).unwrap(), &proof));
+
+    let mut huge = soroban_sdk::Vec::new(&env);
+    for i in 0..33u8 {
+        huge.push_back(BytesN::from_array(&env, &[i; 32]));
+    }
+    assert_eq!(
+        client.try_verify_inclusion(&root, &leaves.get(0).unwrap(), &huge),
+        Err(Ok(QuickexError::ProofTooLong))
+    );
+
+    // The cap is the admin's to tune, but never to zero
+    assert_eq!(
+        client.try_set_max_proof_len(&owner, &1),
+        Err(Ok(QuickexError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_max_proof_len(&admin, &0),
+        Err(Ok(QuickexError::InvalidProofLen))
+    );
+    assert_eq!(client.get_max_proof_len(), 32);
+
+    client.set_max_proof_len(&admin, &1);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "MaxProofLenSet"),).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "max_len"), 1u32.into_val(&env)),
+                        (Symbol::new(&env, "admin"), admin.clone().into_val(&env)),
+                    ],
+                )
+                .into_val(&env),
+            )
+        ]
+    );
+    let two = vec![
+        &env,
+        BytesN::from_array(&env, &[7; 32]),
+        BytesN::from_array(&env, &[8; 32]),
+    ];
+    assert_eq!(
+        client.try_verify_inclusion(&root, &leaves.get(0).unwrap(), &two),
+        Err(Ok(QuickexError::ProofTooLong))
+    );
+    assert!(client.verify_inclusion(&root, &leaves.get(0).unwrap(), &proof));
+}
+
+#[test]
+fn test_register_commitments_anchored_rejects_bad_batches() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+
+    let result = client.try_register_commitments_anchored(&owner, &vec![&env]);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidBatch)));
+
+    let leaf = BytesN::from_array(&env, &[9; 32]);
+    client.register_commitment(&owner, &leaf);
+    let batch = vec![&env, BytesN::from_array(&env, &[8; 32]), leaf];
+    let result = client.try_register_commitments_anchored(&owner, &batch);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentAlreadyExists)));
+}
+
+#[test]
+fn test_root_history_at_or_before_ledger() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+
+    let mut roots = soroban_sdk::Vec::new(&env);
+    for (i, ledger) in [10u32, 20, 30].iter().enumerate() {
+        env.ledger().set_sequence_number(*ledger);
+        let leaf = BytesN::from_array(&env, &[i as u8 + 1; 32]);
+        roots.push_back(client.register_commitments_anchored(&owner, &vec![&env, leaf]));
+    }
+
+    assert_eq!(client.get_root_at_or_before(&5), None);
+    assert_eq!(
+        client.get_root_at_or_before(&10),
+        Some((roots.get(0).unwrap(), 10))
+    );
+    assert_eq!(
+        client.get_root_at_or_before(&25),
+        Some((roots.get(1).unwrap(), 20))
+    );
+    assert_eq!(
+        client.get_root_at_or_before(&1_000),
+        Some((roots.get(2).unwrap(), 30))
+    );
+
+    // Once the ring wraps the oldest roots are no longer answerable
+    for i in 0..256u32 {
+        env.ledger().set_sequence_number(100 + i);
+        let mut leaf = [0xAA; 32];
+        leaf[..4].copy_from_slice(&i.to_be_bytes());
+        client.register_commitments_anchored(&owner, &vec![&env, BytesN::from_array(&env, &leaf)]);
+    }
+    assert_eq!(client.get_root_at_or_before(&30), None);
+    assert_eq!(client.get_root_at_or_before(&355).unwrap().1, 355);
+    assert_eq!(client.get_root_at_or_before(&200).unwrap().1, 200);
+}
+
+/// Rebuild a commitment the way an off-chain verifier would, from an
+/// exported bundle plus the known preimage
+fn rebuild_from_bundle(
+    env: &Env,
+    bundle: &CommitmentProofBundle,
+    amount: i128,
+    salt: &Bytes,
+) -> BytesN<32> {
+    assert_eq!(bundle.algorithm, Symbol::new(env, "sha256"));
+
+    let mut payload = bundle.domain.clone();
+    payload.append(&bundle.owner.clone().to_xdr(env));
+    if bundle.scheme == 2 {
+        payload.append(&bundle.token.clone().unwrap().to_xdr(env));
+    }
+    payload.extend_from_array(&amount.to_be_bytes());
+    if bundle.salted {
+        payload.append(salt);
+    }
+    env.crypto().sha256(&payload).into()
+}
+
+#[test]
+fn test_export_commitment_proof_reconstructs_hash() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let token = create_test_token(&env);
+    let salt = Bytes::from_slice(&env, b"bundle_salt");
+
+    let asset = client.create_asset_amount_commitment(&owner, &token, &7_500, &salt);
+    client.register_asset_commitment(&owner, &token, &asset);
+
+    let bundle = client.export_commitment_proof(&asset);
+    assert_eq!(bundle.scheme, 2);
+    assert_eq!(bundle.owner, owner);
+    assert_eq!(bundle.registered_at, env.ledger().sequence());
+    assert_eq!(bundle.merkle_index, None);
+    assert_eq!(bundle.root, None);
+    assert_eq!(bundle.status, EscrowStatus::Pending);
+    assert_eq!(rebuild_from_bundle(&env, &bundle, 7_500, &salt), asset);
+
+    // Anchored v1 commitments report their batch position and root
+    let leaves = vec![
+        &env,
+        client.create_amount_commitment(&owner, &100, &salt),
+        client.create_amount_commitment(&owner, &200, &salt),
+    ];
+    let root = client.register_commitments_anchored(&owner, &leaves);
+
+    let bundle = client.export_commitment_proof(&leaves.get(1).unwrap());
+    assert_eq!(bundle.scheme, 1);
+    assert_eq!(bundle.token, None);
+    assert!(bundle.salted);
+    assert_eq!(bundle.merkle_index, Some(1));
+    assert_eq!(bundle.root, Some(root));
+    assert_eq!(
+        rebuild_from_bundle(&env, &bundle, 200, &salt),
+        leaves.get(1).unwrap()
+    );
+    assert_ne!(
+        rebuild_from_bundle(&env, &bundle, 100, &salt),
+        bundle.commitment
+    );
+
+    let result = client.try_export_commitment_proof(&BytesN::from_array(&env, &[0; 32]));
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentNotFound)));
+}
+
+#[test]
+fn test_recompute_commitments_batch_matches_individual() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let amounts = vec![&env, 100i128, 2_500, 0];
+    let salts = vec![
+        &env,
+        Bytes::from_slice(&env, b"salt_a"),
+        Bytes::from_slice(&env, b"salt_b"),
+        Bytes::from_slice(&env, b"salt_c"),
+    ];
+
+    let batch = client.recompute_commitments_batch(&owner, &amounts, &salts);
+    assert_eq!(batch.len(), 3);
+    for i in 0..3 {
+        let single = client.create_amount_commitment(
+            &owner,
+            &amounts.get_unchecked(i),
+            &salts.get_unchecked(i),
+        );
+        assert_eq!(batch.get_unchecked(i), single);
+    }
+}
+
+#[test]
+fn test_recompute_commitments_batch_rejects_bad_input() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salts = vec![&env, Bytes::from_slice(&env, b"salt_a")];
+
+    let result = client.try_recompute_commitments_batch(&owner, &vec![&env, 1i128, 2], &salts);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidBatch)));
+
+    let result = client.try_recompute_commitments_batch(&owner, &vec![&env, -1i128], &salts);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+}
+
+#[test]
+fn test_test_mode_makes_commitments_deterministic() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+    let salt_a = Bytes::from_slice(&env, b"salt_a");
+    let salt_b = Bytes::from_slice(&env, b"salt_b");
+
+    let normal_a = client.create_amount_commitment(&owner, &1_000, &salt_a);
+    assert_ne!(
+        normal_a,
+        client.create_amount_commitment(&owner, &1_000, &salt_b)
+    );
+
+    client.set_test_mode(&admin, &true);
+    assert!(client.is_test_mode());
+    let fixed = client.create_amount_commitment(&owner, &1_000, &salt_a);
+    assert_eq!(
+        fixed,
+        client.create_amount_commitment(&owner, &1_000, &salt_b)
+    );
+    assert_ne!(
+        fixed,
+        client.create_amount_commitment(&owner, &1_001, &salt_a)
+    );
+
+    client.set_test_mode(&admin, &false);
+    assert_eq!(
+        client.create_amount_commitment(&owner, &1_000, &salt_a),
+        normal_a
+    );
+}
+
+#[test]
+fn test_commitments_keep_the_mode_they_were_deposited_under() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let token = create_test_token(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+    client.initialize(&admin);
+    let salt = Bytes::from_slice(&env, b"mode_salt");
+
+    client.set_test_mode(&admin, &true);
+    assert_eq!(
+        env.events().all(),
+        vec![
+            &env,
+            (
+                client.address.clone(),
+                (Symbol::new(&env, "TestModeChanged"),).into_val(&env),
+                Map::<Symbol, Val>::from_array(
+                    &env,
+                    [
+                        (Symbol::new(&env, "enabled"), true.into_val(&env)),
+                        (Symbol::new(&env, "admin"), admin.clone().into_val(&env)),
+                    ],
+                )
+                .into_val(&env),
+            )
+        ]
+    );
+    let test_deposit = client.deposit(&token, &400, &owner, &salt);
+    client.register_commitment(&owner, &test_deposit);
+
+    client.set_test_mode(&admin, &false);
+    let real_deposit = client.deposit(&token, &600, &owner, &salt);
+    client.register_commitment(&owner, &real_deposit);
+
+    // The global switch no longer decides how either one is opened
+    let bundle = client.export_commitment_proof(&test_deposit);
+    assert!(!bundle.salted);
+    assert_eq!(
+        rebuild_from_bundle(&env, &bundle, 400, &Bytes::new(&env)),
+        test_deposit
+    );
+    assert!(client.export_commitment_proof(&real_deposit).salted);
+
+    assert!(client.verify_proof_view(&400, &salt, &owner));
+    assert!(client.withdraw(&token, &400, &test_deposit, &owner, &salt));
+    assert!(client.withdraw(&token, &600, &real_deposit, &owner, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 1_000);
+}
+
+#[test]
+fn test_test_mode_refused_on_mainnet() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let mainnet: BytesN<32> = env
+        .crypto()
+        .sha256(&Bytes::from_slice(
+            &env,
+            b"Public Global Stellar Network ; September 2015",
+        ))
+        .into();
+    env.ledger()
+        .with_mut(|li| li.network_id = mainnet.to_array());
+
+    let result = client.try_set_test_mode(&admin, &true);
+    assert_eq!(result, Err(Ok(QuickexError::TestModeForbidden)));
+    assert!(!client.is_test_mode());
+
+    let result = client.try_set_test_mode(&Address::generate(&env), &false);
+    assert_eq!(result, Err(Ok(QuickexError::Unauthorized)));
+}
+
+#[test]
+fn test_sealed_bid_auction_picks_highest_reveal() {
+    let (env, client) = setup();
+    let seller = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let item = Symbol::new(&env, "lot_12");
+
+    env.ledger().set_timestamp(1_000);
+    let auction_id = client.open_auction(&seller, &item, &2_000);
+
+    let alice_salt = Bytes::from_slice(&env, b"alice_salt");
+    let bob_salt = Bytes::from_slice(&env, b"bob_salt");
+    let alice_bid = client.create_amount_commitment(&alice, &500, &alice_salt);
+    let bob_bid = client.create_amount_commitment(&bob, &700, &bob_salt);
+    client.submit_bid(&alice, &auction_id, &alice_bid);
+    client.submit_bid(&bob, &auction_id, &bob_bid);
+
+    // Reveals wait for bidding to close
+    let result = client.try_reveal_bid(&alice, &auction_id, &500, &alice_salt);
+    assert_eq!(result, Err(Ok(QuickexError::AuctionNotEnded)));
+
+    env.ledger().set_timestamp(2_000);
+    let late = client.create_amount_commitment(&seller, &900, &alice_salt);
+    let result = client.try_submit_bid(&seller, &auction_id, &late);
+    assert_eq!(result, Err(Ok(QuickexError::BiddingClosed)));
+
+    // A bid only opens with the committed amount
+    let result = client.try_reveal_bid(&bob, &auction_id, &900, &bob_salt);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentMismatch)));
+
+    client.reveal_bid(&bob, &auction_id, &700, &bob_salt);
+    client.reveal_bid(&alice, &auction_id, &500, &alice_salt);
+
+    let result = client.try_finalize_auction(&auction_id);
+    assert_eq!(result, Err(Ok(QuickexError::AuctionNotEnded)));
+
+    env.ledger().set_timestamp(2_000 + 24 * 60 * 60);
+    assert_eq!(client.finalize_auction(&auction_id), Some(bob.clone()));
+
+    let auction = client.get_auction(&auction_id).unwrap();
+    assert_eq!(auction.bid_count, 2);
+    assert_eq!(auction.highest_bid, 700);
+    assert!(auction.finalized);
+
+    let result = client.try_finalize_auction(&auction_id);
+    assert_eq!(result, Err(Ok(QuickexError::AuctionFinalized)));
+}
+
+#[test]
+fn test_auction_bids_reveal_once() {
+    let (env, client) = setup();
+    let seller = Address::generate(&env);
+    let bidder = Address::generate(&env);
+    let auction_id = client.open_auction(&seller, &Symbol::new(&env, "lot_1"), &100);
+
+    let salt = Bytes::from_slice(&env, b"salt");
+    let bid = client.create_amount_commitment(&bidder, &50, &salt);
+    client.submit_bid(&bidder, &auction_id, &bid);
+
+    env.ledger().set_timestamp(100);
+    client.reveal_bid(&bidder, &auction_id, &50, &salt);
+    let result = client.try_reveal_bid(&bidder, &auction_id, &50, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::CommitmentNotFound)));
+
+    let result = client.try_submit_bid(&bidder, &99, &BytesN::from_array(&env, &[0; 32]));
+    assert_eq!(result, Err(Ok(QuickexError::AuctionNotFound)));
+}
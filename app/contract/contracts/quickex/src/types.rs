@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Symbol, Vec};
 
 /// Escrow entry status
 #[contracttype]
@@ -7,6 +7,11 @@ pub enum EscrowStatus {
     Pending,
     Spent,
     Expired,
+    Released,
+    Refunded,
+    Disputed,
+    Frozen,
+    Funding,
 }
 
 /// Escrow entry structure
@@ -19,3 +24,396 @@ pub struct EscrowEntry {
     pub status: EscrowStatus,
     pub created_at: u64,
 }
+
+/// When the protocol fee on an escrow is charged
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeeTiming {
+    OnCreate,
+    OnRelease,
+}
+
+/// Amount in a reference unit (e.g. cents) and its rate to token base units
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Denomination {
+    pub ref_amount: i128,
+    pub rate: i128,
+}
+
+/// One status change in an account's escrow activity feed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActivityEntry {
+    pub escrow_id: u64,
+    pub status: EscrowStatus,
+    pub ledger: u32,
+}
+
+/// Id-based escrow between a sender and a recipient
+#[contracttype]
+#[derive(Clone)]
+pub struct Escrow {
+    pub from: Address,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub status: EscrowStatus,
+    pub memo: Bytes,
+    pub created_at: u64,
+    pub private: bool,
+    pub arbiter: Option<Address>,
+    pub deadline: Option<u64>,
+    pub fee_bps: Option<u32>,
+    pub dispute_opener: Option<Address>,
+    pub dispute_bond: i128,
+    pub split_bps: Option<u32>,
+    pub allowed: Vec<Address>,
+    pub watcher_count: u32,
+    pub frozen: bool,
+    pub funded: i128,
+    pub pull_payment: bool,
+    pub yield_adapter: Option<Address>,
+    pub fee_paid: i128,
+    pub accepted: bool,
+    /// Lock from `hash_claim_code`, for escrows claimed with a code
+    pub claim_code: Option<BytesN<32>>,
+    /// Where refunds go instead of `from`, e.g. a corporate treasury
+    pub refund_to: Option<Address>,
+    /// Seconds per period of a recurring escrow, None for one-off escrows
+    pub interval: Option<u64>,
+    /// Refunds credit the refund address's withdrawable balance instead of
+    /// transferring, so the sender can withdraw many at once
+    pub refund_to_internal: bool,
+    /// Whether `poke` has already announced the approaching deadline
+    pub deadline_warned: bool,
+    /// KYC attestation hashes attached by the sender and the recipient
+    pub from_attestation: Option<BytesN<32>>,
+    pub to_attestation: Option<BytesN<32>>,
+    /// Whether creation is announced with an `EscrowCreated` event
+    pub emit_event: bool,
+}
+
+/// Reusable escrow settings for repeat counterparties
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowTemplate {
+    pub recipient: Address,
+    pub token: Address,
+    pub arbiter: Option<Address>,
+    /// Seconds after creation until the deadline, 0 for no deadline
+    pub deadline_offset: u64,
+    /// Per-escrow fee override in basis points, None for the global default
+    pub fee_bps: Option<u32>,
+}
+
+/// Commitment registry record
+#[contracttype]
+#[derive(Clone)]
+pub struct CommitmentRecord {
+    pub commitment: BytesN<32>,
+    pub owner: Address,
+    pub registered_at: u32,
+    /// Token bound into the commitment, None for the token-less v1 scheme
+    pub token: Option<Address>,
+}
+
+/// Hash of an off-chain message posted during a dispute
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeMessage {
+    pub sender: Address,
+    pub msg_hash: BytesN<32>,
+    pub timestamp: u64,
+}
+
+/// Merkle root over a batch of registered commitments
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnchoredRoot {
+    pub owner: Address,
+    pub leaf_count: u32,
+    pub anchored_at: u32,
+}
+
+/// Everything except the preimage that an off-chain verifier needs to
+/// recompute a registered commitment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentProofBundle {
+    pub commitment: BytesN<32>,
+    /// 1 for `create_amount_commitment`, 2 for the asset-bound scheme
+    pub scheme: u32,
+    pub algorithm: Symbol,
+    pub domain: Bytes,
+    /// False when test mode leaves the salt out of a v1 preimage
+    pub salted: bool,
+    pub owner: Address,
+    /// Token bound into a v2 preimage
+    pub token: Option<Address>,
+    /// Ledger the commitment was registered in
+    pub registered_at: u32,
+    /// Leaf position within its anchored batch, if it was anchored
+    pub merkle_index: Option<u32>,
+    /// Root of that anchored batch
+    pub root: Option<BytesN<32>>,
+    /// Status of a deposit made under the commitment; `Pending` until one is spent
+    pub status: EscrowStatus,
+}
+
+/// Commitment hashing scheme parameters
+///
+/// Commitments are `algorithm(domain || xdr(owner) || amount_be_i128 || salt)`
+/// applied `rounds` times.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentParams {
+    pub algorithm: Symbol,
+    pub rounds: u32,
+    pub domain: Bytes,
+    pub version: u32,
+    pub max_salt_len: u32,
+}
+
+/// Settled escrow totals for one token in one reporting epoch
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SettlementBucket {
+    pub settled_count: u64,
+    pub volume: i128,
+}
+
+/// Aggregate settlement totals over a range of reporting epochs
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AggregateReport {
+    pub from_epoch: u32,
+    pub to_epoch: u32,
+    pub settled_count: u64,
+    pub total_volume: i128,
+}
+
+/// Live check that the contract custodies enough of a token to cover what it owes
+///
+/// `fees_accrued` counts collected protocol fees and the insurance pool;
+/// `credits`, `bonds` and `deposits` are withdrawable credit, held dispute
+/// bonds and unspent commitment deposits; `parked` is escrow principal
+/// deposited with the token's yield adapter. `solvent` is
+/// `balance + parked >= tvl + fees_accrued + credits + bonds + deposits`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveProof {
+    pub token: Address,
+    pub tvl: i128,
+    pub fees_accrued: i128,
+    pub credits: i128,
+    pub bonds: i128,
+    pub deposits: i128,
+    pub balance: i128,
+    pub parked: i128,
+    pub solvent: bool,
+    pub ledger: u32,
+}
+
+/// Settlement record of an account, as a lightweight reputation signal
+///
+/// When `opted_out` is set the account has hidden its record and every
+/// counter reads zero.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CounterpartyStats {
+    pub opted_out: bool,
+    /// Escrows sent that were released without a dispute
+    pub completed_as_sender: u32,
+    /// Escrows received that were released without a dispute
+    pub completed_as_recipient: u32,
+    /// Disputes the other party opened against this account
+    pub disputes_against: u32,
+    /// Disputes ruled wholly against this account
+    pub disputes_lost: u32,
+}
+
+/// Snapshot of an account's open positions, for moving to a new wallet
+///
+/// `digest` is the sha256 of this struct's XDR with `digest` set to zero, so
+/// the receiving wallet can check it got the export unaltered.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountExport {
+    pub owner: Address,
+    /// First page covered, 0 for a full export
+    pub page: u32,
+    /// Whether later pages hold more positions
+    pub has_more: bool,
+    /// Open escrows the account sends
+    pub sent: Vec<u64>,
+    /// Open escrows the account receives
+    pub received: Vec<u64>,
+    /// Commitment deposits that haven't been withdrawn
+    pub commitments: Vec<BytesN<32>>,
+    pub privacy_enabled: bool,
+    pub privacy_level: Option<u32>,
+    pub private_escrows_by_default: bool,
+    pub digest: BytesN<32>,
+}
+
+/// Encrypted salt deposited for a commitment, recoverable by a trusted party
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaltEscrow {
+    pub owner: Address,
+    pub recovery: Address,
+    pub blob: Bytes,
+    pub requested_at: Option<u64>,
+}
+
+/// Human-readable contract information for feature detection
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    pub name: Symbol,
+    pub version: u32,
+    pub features: u32,
+}
+
+/// Modules enabled on a deployment, fixed at initialization and only ever widened
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureFlags {
+    pub escrow_enabled: bool,
+    pub commitments_enabled: bool,
+    pub privacy_enabled: bool,
+}
+
+/// Output side of a swap escrow, settled at a rate committed before funding
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapQuote {
+    pub token_out: Address,
+    pub rate_commitment: BytesN<32>,
+}
+
+/// A sealed-bid auction run on amount commitments
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Auction {
+    pub seller: Address,
+    pub item: Symbol,
+    /// Ledger timestamp at which bidding closes and reveals open
+    pub reveal_after: u64,
+    pub bid_count: u32,
+    pub highest_bidder: Option<Address>,
+    pub highest_bid: i128,
+    pub finalized: bool,
+}
+
+/// Escrows grouped under one owner as parts of a larger deal
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deal {
+    pub owner: Address,
+    pub escrows: Vec<u64>,
+}
+
+/// Aggregate status of a deal's linked escrows
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DealStatus {
+    /// Some linked escrow is unsettled, or none are linked yet
+    Open,
+    /// Every linked escrow was released
+    Complete,
+    /// Every linked escrow settled, but not all were released
+    Failed,
+}
+
+/// A deal with the aggregate status of its escrows
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DealSummary {
+    pub owner: Address,
+    pub escrows: Vec<u64>,
+    pub status: DealStatus,
+}
+
+/// One escrow in a batch created by a single sender
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowBatchItem {
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub memo: Bytes,
+}
+
+/// Admin-tunable parameters, set together with `set_config`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractConfig {
+    pub dispute_bond_bps: u32,
+    pub insurance_share_bps: u32,
+    pub max_escrow_lifetime_secs: u64,
+    pub cooling_off_threshold: i128,
+    pub cooling_off_delay: u64,
+    pub nullifier_retention_secs: u64,
+    pub salt_recovery_delay_secs: u64,
+}
+
+/// Commitment created with contract randomness, and the salt that opens it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MixedCommitment {
+    pub commitment: BytesN<32>,
+    pub effective_salt: Bytes,
+}
+
+/// Full escrow terms a sender commits to before revealing them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermsPreimage {
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+    /// Absolute deadline timestamp, None for no deadline
+    pub deadline: Option<u64>,
+    pub arbiter: Option<Address>,
+    /// Per-escrow fee override in basis points, None for the global default
+    pub fee_bps: Option<u32>,
+}
+
+/// Compact record of a key escrow event, kept on-chain for indexers
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventSummary {
+    pub kind: Symbol,
+    pub subject: u64,
+    pub timestamp: u64,
+}
+
+/// How an escrow settles, for previewing its payouts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Resolution {
+    /// Release to the recipient
+    Release,
+    /// Return the held funds to the sender
+    Refund,
+    /// Dispute ruling awarding the recipient this many bps of the net
+    Split(u32),
+    /// Keeper sweep of an expired escrow
+    Sweep,
+}
+
+/// Payouts of a settlement, as `preview_settlement` reports them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementBreakdown {
+    pub to_recipient: i128,
+    /// Paid to the sender, or to their refund address
+    pub to_sender: i128,
+    pub protocol_fee: i128,
+    pub keeper_reward: i128,
+    /// Dispute bond paid out alongside, and who receives it
+    pub dispute_bond: i128,
+    pub bond_to: Option<Address>,
+}
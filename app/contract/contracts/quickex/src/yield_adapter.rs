@@ -0,0 +1,139 @@
+use crate::admin;
+use crate::errors::QuickexError;
+use crate::events::publish_yield_recall_failed;
+use crate::storage::{
+    get_collected_fees, get_yield_adapter, get_yield_principal, get_yield_shortfall,
+    put_escrow_by_id, set_collected_fees, set_yield_adapter, set_yield_principal,
+    set_yield_shortfall,
+};
+use crate::types::Escrow;
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::{contractclient, vec, Address, Env, IntoVal, Symbol};
+
+/// Minimal interface a yield source must expose to hold escrowed funds
+///
+/// `deposit` pulls `amount` of the adapter's token from `from`, `withdraw`
+/// sends `amount` back to `to`, and `balance_of` reports principal plus
+/// accrued yield for `owner`.
+#[allow(dead_code)]
+#[contractclient(name = "YieldAdapterClient")]
+pub trait YieldAdapter {
+    fn deposit(env: Env, from: Address, amount: i128);
+    fn withdraw(env: Env, to: Address, amount: i128);
+    fn balance_of(env: Env, owner: Address) -> i128;
+}
+
+/// Register or clear the yield adapter for a token (Admin only)
+///
+/// Escrows already parked with a previous adapter keep withdrawing from it.
+pub fn set_adapter(
+    env: &Env,
+    caller: Address,
+    token: Address,
+    adapter: Option<Address>,
+) -> Result<(), QuickexError> {
+    admin::require_admin(env, &caller)?;
+    set_yield_adapter(env, &token, adapter);
+    Ok(())
+}
+
+/// Move a newly funded escrow's balance into its token's yield adapter
+///
+/// Falls back to plain custody, leaving `escrow.yield_adapter` unset, when
+/// no adapter is registered or its deposit fails.
+pub fn park(env: &Env, escrow: &mut Escrow) {
+    let Some(adapter) = get_yield_adapter(env, &escrow.token) else {
+        return;
+    };
+    let amount = crate::escrow::held(escrow);
+    if amount <= 0 {
+        return;
+    }
+
+    let contract = env.current_contract_address();
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: escrow.token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (contract.clone(), adapter.clone(), amount).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    let client = YieldAdapterClient::new(env, &adapter);
+    if let Ok(Ok(())) = client.try_deposit(&contract, &amount) {
+        let principal = get_yield_principal(env, &adapter);
+        set_yield_principal(env, &adapter, principal + amount);
+        escrow.yield_adapter = Some(adapter);
+    }
+}
+
+/// Bring a parked escrow's principal back into custody before it is paid out
+///
+/// When the adapter's withdrawal fails the escrow falls back to plain
+/// custody: the principal is booked as the adapter's shortfall, the escrow
+/// forgets the adapter, and settlement goes ahead from the contract's
+/// balance. `harvest` pays the shortfall back once the adapter recovers.
+pub fn recall(env: &Env, escrow_id: u64, escrow: &Escrow) {
+    let Some(adapter) = escrow.yield_adapter.clone() else {
+        return;
+    };
+
+    let amount = crate::escrow::held(escrow);
+    let client = YieldAdapterClient::new(env, &adapter);
+    let withdrawn = matches!(
+        client.try_withdraw(&env.current_contract_address(), &amount),
+        Ok(Ok(()))
+    );
+
+    let principal = get_yield_principal(env, &adapter);
+    set_yield_principal(env, &adapter, principal - amount);
+
+    if !withdrawn {
+        let shortfall = get_yield_shortfall(env, &adapter);
+        set_yield_shortfall(env, &adapter, shortfall + amount);
+
+        let mut stored = escrow.clone();
+        stored.yield_adapter = None;
+        put_escrow_by_id(env, escrow_id, &stored);
+
+        publish_yield_recall_failed(env, escrow_id, &adapter, amount);
+    }
+}
+
+/// Get the adapter registered for a token, failing with `YieldAdapterNotSet`
+pub fn adapter(env: &Env, token: &Address) -> Result<Address, QuickexError> {
+    get_yield_adapter(env, token).ok_or(QuickexError::YieldAdapterNotSet)
+}
+
+/// Withdraw the yield a token's adapter has earned into collected fees (Admin only)
+///
+/// Only the excess over the principal of parked escrows is taken, so the
+/// parties always get back exactly what they put in. Principal the adapter
+/// failed to return is recovered into custody first and isn't counted as
+/// yield. Returns the yield taken.
+pub fn harvest(env: &Env, caller: Address, token: Address) -> Result<i128, QuickexError> {
+    admin::require_admin(env, &caller)?;
+
+    let adapter = adapter(env, &token)?;
+    let client = YieldAdapterClient::new(env, &adapter);
+    let contract = env.current_contract_address();
+
+    let excess = client.balance_of(&contract) - get_yield_principal(env, &adapter);
+    if excess <= 0 {
+        return Ok(0);
+    }
+    client.withdraw(&contract, &excess);
+
+    let shortfall = get_yield_shortfall(env, &adapter);
+    let recovered = excess.min(shortfall);
+    set_yield_shortfall(env, &adapter, shortfall - recovered);
+
+    let earned = excess - recovered;
+    set_collected_fees(env, &token, get_collected_fees(env, &token) + earned);
+
+    Ok(earned)
+}